@@ -1,23 +1,111 @@
 use anyhow::{Context, Result};
-use dirs;
-use oauth2::{ClientId, ClientSecret};
+use async_trait::async_trait;
+use oauth2::basic::BasicClient;
+use oauth2::reqwest::async_http_client;
+use oauth2::{
+    AuthType, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
+    RedirectUrl, Scope, TokenResponse, TokenUrl,
+};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::data_api::DEFAULT_API_BASE_URL;
+use crate::token_store::{CorruptToken, MissingToken, TokenStore};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenData {
     pub access_token: String,
     pub refresh_token: Option<String>,
     pub expires_at: Option<u64>,
+    // Space-separated scopes Google granted; missing in tokens saved by older
+    // versions, which only ever asked for read-only access
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl TokenData {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        match &self.scope {
+            Some(granted) => granted.split_whitespace().any(|s| s == scope),
+            None => scope == READONLY_SCOPE,
+        }
+    }
+}
+
+pub const READONLY_SCOPE: &str = "https://www.googleapis.com/auth/youtube.readonly";
+// Needed to subscribe, rate videos and edit playlists; only requested with --write-access
+pub const WRITE_SCOPE: &str = "https://www.googleapis.com/auth/youtube";
+
+pub const DEFAULT_OAUTH_BASE_URL: &str = "https://oauth2.googleapis.com";
+pub const DEFAULT_AUTHORIZE_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+
+// How long the browser flow waits for the user to finish signing in
+const LOOPBACK_TIMEOUT_SECS: u64 = 300;
+
+// How a new sign-in is done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthFlow {
+    // Enter a code on any device; needs a "TVs and Limited Input devices" client
+    Device,
+    // Sign in with the browser on this machine, redirected back to a local
+    // listener (PKCE); needs a "Desktop app" client
+    Loopback,
+}
+
+// Opens the authorization URL for the loopback flow
+pub type OpenBrowser = Arc<dyn Fn(&str) + Send + Sync>;
+
+// Added to the device flow's polling interval each time Google says slow_down
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+// The account used when no --account is given; it keeps the original token.json
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevokeOutcome {
+    Revoked,
+    // Google no longer knew the token; the local copy was still removed
+    AlreadyRevoked,
+    NotSignedIn,
+}
+
+// Sign-in choices from the command line. Every account's client is built with
+// them, including accounts switched to from the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthOptions {
+    pub write_access: bool,
+    pub flow: AuthFlow,
+}
+
+impl AuthOptions {
+    pub fn client_for(&self, account: &str) -> Result<AuthClient> {
+        Ok(self.apply(AuthClient::for_account(account)?))
+    }
+
+    pub fn apply(&self, client: AuthClient) -> AuthClient {
+        client
+            .with_write_access(self.write_access)
+            .with_flow(self.flow)
+    }
 }
 
 pub struct AuthClient {
     client_id: ClientId,
     client_secret: ClientSecret,
-    token_path: PathBuf,
+    store: TokenStore,
+    oauth_base_url: String,
+    api_base_url: String,
+    authorize_url: String,
+    write_access: bool,
+    flow: AuthFlow,
+    open_browser: OpenBrowser,
 }
 
 #[derive(Deserialize)]
@@ -36,11 +124,17 @@ struct TokenPollResponse {
     access_token: Option<String>,
     refresh_token: Option<String>,
     expires_in: Option<u64>,
+    scope: Option<String>,
     error: Option<String>,
 }
 
 impl AuthClient {
     pub fn new() -> Result<Self> {
+        Self::for_account(DEFAULT_ACCOUNT)
+    }
+
+    // Auth client for a named account profile, each with its own saved token
+    pub fn for_account(account: &str) -> Result<Self> {
         // Get OAuth credentials from environment
         let client_id = std::env::var("GOOGLE_CLIENT_ID").context(
             "GOOGLE_CLIENT_ID environment variable not set. Please set it before running.",
@@ -49,24 +143,108 @@ impl AuthClient {
             "GOOGLE_CLIENT_SECRET environment variable not set. Please set it before running.",
         )?;
 
-        let config_dir = get_config_dir()?;
-        let token_path = config_dir.join("token.json");
+        let store = TokenStore::for_account(account)?;
+
+        Ok(
+            Self::with_credentials(client_id, client_secret, store.path().to_path_buf())
+                .with_token_store(store),
+        )
+    }
 
-        Ok(Self {
+    // Keeps the token in a plain (0600) file at token_path
+    pub fn with_credentials(client_id: String, client_secret: String, token_path: PathBuf) -> Self {
+        Self {
             client_id: ClientId::new(client_id),
             client_secret: ClientSecret::new(client_secret),
-            token_path,
-        })
+            store: TokenStore::file(token_path),
+            oauth_base_url: DEFAULT_OAUTH_BASE_URL.to_string(),
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+            authorize_url: DEFAULT_AUTHORIZE_URL.to_string(),
+            write_access: false,
+            flow: AuthFlow::Device,
+            open_browser: Arc::new(open_in_browser),
+        }
+    }
+
+    // Whether this account has signed in before
+    pub fn has_token(&self) -> bool {
+        self.store.exists()
+    }
+
+    pub fn with_token_store(mut self, store: TokenStore) -> Self {
+        self.store = store;
+        self
+    }
+
+    // Point the OAuth endpoints (device code, token) at another server
+    pub fn with_oauth_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.oauth_base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    // Point the token validation call at another YouTube Data API root
+    pub fn with_api_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.api_base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    // Point the browser flow's authorization page at another server
+    pub fn with_authorize_url(mut self, url: impl Into<String>) -> Self {
+        self.authorize_url = url.into();
+        self
+    }
+
+    pub fn with_flow(mut self, flow: AuthFlow) -> Self {
+        self.flow = flow;
+        self
+    }
+
+    // Replace how the browser flow opens its URL (tests follow it themselves)
+    pub fn with_browser(mut self, open_browser: OpenBrowser) -> Self {
+        self.open_browser = open_browser;
+        self
+    }
+
+    // Also ask for the write scope. Accounts that signed in read-only are asked
+    // to approve the extra access on their next sign-in.
+    pub fn with_write_access(mut self, write_access: bool) -> Self {
+        self.write_access = write_access;
+        self
+    }
+
+    // Helper: scopes to request, space-separated
+    fn scopes(&self) -> String {
+        if self.write_access {
+            format!("{} {}", READONLY_SCOPE, WRITE_SCOPE)
+        } else {
+            READONLY_SCOPE.to_string()
+        }
+    }
+
+    // Helper: whether a saved token covers everything this client asks for
+    fn has_required_scopes(&self, token: &TokenData) -> bool {
+        self.scopes()
+            .split_whitespace()
+            .all(|scope| token.has_scope(scope))
     }
 
     pub async fn authenticate(&self) -> Result<String> {
         // Check if we have a valid token
         if let Ok(token) = self.load_token().await {
-            if self.is_token_valid(&token).await {
+            if !self.has_required_scopes(&token) {
+                println!("\nAdditional permissions are needed to manage your YouTube account.");
+            } else if self.is_token_valid(&token).await {
                 return Ok(token.access_token);
             }
         }
 
+        match self.flow {
+            AuthFlow::Device => self.device_flow().await,
+            AuthFlow::Loopback => self.loopback_flow().await,
+        }
+    }
+
+    async fn device_flow(&self) -> Result<String> {
         // Start Device Authorization Flow
         println!("\n=== YouTube Authentication ===");
         println!("Starting device authorization flow...\n");
@@ -75,10 +253,10 @@ impl AuthClient {
 
         // Step 1: Request device code
         let device_code_response = http_client
-            .post("https://oauth2.googleapis.com/device/code")
+            .post(format!("{}/device/code", self.oauth_base_url))
             .form(&[
                 ("client_id", self.client_id.as_str()),
-                ("scope", "https://www.googleapis.com/auth/youtube.readonly"),
+                ("scope", self.scopes().as_str()),
             ])
             .send()
             .await
@@ -106,7 +284,7 @@ impl AuthClient {
         println!("\nWaiting for authorization... (Press Ctrl+C to cancel)\n");
 
         // Step 3: Poll for token
-        let poll_interval = Duration::from_secs(device_data.interval);
+        let mut poll_interval = Duration::from_secs(device_data.interval);
         let expires_at = std::time::Instant::now() + Duration::from_secs(device_data.expires_in);
 
         loop {
@@ -117,7 +295,7 @@ impl AuthClient {
             tokio::time::sleep(poll_interval).await;
 
             let token_response = http_client
-                .post("https://oauth2.googleapis.com/token")
+                .post(format!("{}/token", self.oauth_base_url))
                 .form(&[
                     ("client_id", self.client_id.as_str()),
                     ("client_secret", self.client_secret.secret()),
@@ -137,8 +315,9 @@ impl AuthClient {
                         std::io::stdout().flush().ok();
                         continue;
                     }
+                    // Poll less often from now on (RFC 8628 §3.5)
                     "slow_down" => {
-                        tokio::time::sleep(poll_interval * 2).await;
+                        poll_interval += SLOW_DOWN_STEP;
                         continue;
                     }
                     _ => {
@@ -161,6 +340,7 @@ impl AuthClient {
                     access_token: access_token.clone(),
                     refresh_token,
                     expires_at,
+                    scope: token_data.scope.or_else(|| Some(self.scopes())),
                 };
 
                 self.save_token(&token_data).await?;
@@ -170,37 +350,175 @@ impl AuthClient {
         }
     }
 
+    // Installed-app flow: the browser sends the authorization code back to a
+    // listener on 127.0.0.1, and PKCE ties it to this process
+    async fn loopback_flow(&self) -> Result<String> {
+        println!("\n=== YouTube Authentication ===");
+        println!("Starting browser sign-in...\n");
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("Failed to start local listener for the sign-in redirect")?;
+        let redirect_url = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+
+        let client = BasicClient::new(
+            self.client_id.clone(),
+            Some(self.client_secret.clone()),
+            AuthUrl::new(self.authorize_url.clone())?,
+            Some(TokenUrl::new(format!("{}/token", self.oauth_base_url))?),
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_redirect_uri(RedirectUrl::new(redirect_url)?);
+
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let (authorize_url, csrf_state) = client
+            .authorize_url(CsrfToken::new_random)
+            .add_scopes(
+                self.scopes()
+                    .split_whitespace()
+                    .map(|scope| Scope::new(scope.to_string())),
+            )
+            .set_pkce_challenge(pkce_challenge)
+            // Ask for a refresh token, and keep scopes granted earlier
+            .add_extra_param("access_type", "offline")
+            .add_extra_param("include_granted_scopes", "true")
+            .url();
+
+        println!("Opening your browser to sign in. If it doesn't open, visit:\n");
+        println!("  {}\n", authorize_url);
+        println!("Waiting for authorization... (Press Ctrl+C to cancel)\n");
+        (self.open_browser)(authorize_url.as_str());
+
+        let code = tokio::time::timeout(
+            Duration::from_secs(LOOPBACK_TIMEOUT_SECS),
+            wait_for_redirect(&listener, csrf_state.secret()),
+        )
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!("Timed out waiting for the browser sign-in. Please try again.")
+        })??;
+
+        let token = client
+            .exchange_code(AuthorizationCode::new(code))
+            .set_pkce_verifier(pkce_verifier)
+            .request_async(async_http_client)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to exchange authorization code: {}", e))?;
+
+        let granted_scopes = token.scopes().map(|scopes| {
+            scopes
+                .iter()
+                .map(|scope| scope.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+        let token_data = TokenData {
+            access_token: token.access_token().secret().clone(),
+            refresh_token: token.refresh_token().map(|t| t.secret().clone()),
+            expires_at: token.expires_in().map(|d| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + d.as_secs()
+            }),
+            scope: granted_scopes.or_else(|| Some(self.scopes())),
+        };
+
+        self.save_token(&token_data).await?;
+        println!("✓ Authentication successful!\n");
+        Ok(token_data.access_token)
+    }
+
+    // Sign out: revoke the saved token with Google and delete it
+    pub async fn revoke(&self) -> Result<RevokeOutcome> {
+        if !self.store.exists() {
+            return Ok(RevokeOutcome::NotSignedIn);
+        }
+
+        let outcome = match self.load_token().await {
+            Ok(token) => {
+                // Revoking the refresh token also invalidates its access tokens
+                let token_to_revoke = token
+                    .refresh_token
+                    .as_deref()
+                    .unwrap_or(&token.access_token);
+
+                let response = Client::new()
+                    .post(format!("{}/revoke", self.oauth_base_url))
+                    .form(&[("token", token_to_revoke)])
+                    .send()
+                    .await
+                    .context("Failed to reach Google to revoke the token")?;
+
+                let status = response.status();
+                if status.is_success() {
+                    RevokeOutcome::Revoked
+                } else {
+                    let error = response.text().await.unwrap_or_default();
+                    // Tokens that were already revoked (or expired) are rejected as invalid_token
+                    if status == reqwest::StatusCode::BAD_REQUEST && error.contains("invalid_token")
+                    {
+                        RevokeOutcome::AlreadyRevoked
+                    } else {
+                        return Err(anyhow::anyhow!(
+                            "Failed to revoke token ({}): {}",
+                            status,
+                            error
+                        ));
+                    }
+                }
+            }
+            // The keyring entry is already gone, just clean up
+            Err(e) if e.downcast_ref::<MissingToken>().is_some() => RevokeOutcome::AlreadyRevoked,
+            // Keep the token so the user can unlock it (passphrase, keyring) and retry;
+            // a corrupt one is only dropped through forget_corrupt_token
+            Err(e) => return Err(e.context("Couldn't load the saved token; it was kept")),
+        };
+
+        self.store.delete().await?;
+
+        Ok(outcome)
+    }
+
+    // Delete a saved token that can't be read back, after the user confirmed it.
+    // Anything else (a missing passphrase, a locked keyring) is left alone.
+    pub async fn forget_corrupt_token(&self) -> Result<RevokeOutcome> {
+        if !self.store.exists() {
+            return Ok(RevokeOutcome::NotSignedIn);
+        }
+        match self.load_token().await {
+            Err(e) if is_corrupt_token(&e) => {
+                self.store.delete().await?;
+                Ok(RevokeOutcome::AlreadyRevoked)
+            }
+            Err(e) => Err(e),
+            Ok(_) => Err(anyhow::anyhow!(
+                "The saved token is readable; sign out to revoke it"
+            )),
+        }
+    }
+
     async fn load_token(&self) -> Result<TokenData> {
-        let content = fs::read_to_string(&self.token_path).context("Failed to read token file")?;
-        let token: TokenData =
-            serde_json::from_str(&content).context("Failed to parse token file")?;
-        Ok(token)
+        self.store.load().await
     }
 
     async fn save_token(&self, token: &TokenData) -> Result<()> {
-        if let Some(parent) = self.token_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let content = serde_json::to_string_pretty(token)?;
-        fs::write(&self.token_path, content)?;
-        Ok(())
+        self.store.save(token).await
     }
 
     async fn is_token_valid(&self, token: &TokenData) -> bool {
-        if let Some(expires_at) = token.expires_at {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            if now >= expires_at {
-                return false;
-            }
+        if is_expired(token, 0) {
+            return false;
         }
 
         // Test token by making a simple API call
         let client = Client::new();
         let response = client
-            .get("https://www.googleapis.com/youtube/v3/channels?part=snippet&mine=true")
+            .get(format!(
+                "{}/channels?part=snippet&mine=true",
+                self.api_base_url
+            ))
             .bearer_auth(&token.access_token)
             .send()
             .await;
@@ -210,13 +528,21 @@ impl AuthClient {
 
     pub async fn get_access_token(&self) -> Result<String> {
         if let Ok(token) = self.load_token().await {
+            // Refreshing can't add scopes; that needs a new sign-in
+            if !self.has_required_scopes(&token) {
+                return self.authenticate().await;
+            }
+
             if self.is_token_valid(&token).await {
                 return Ok(token.access_token);
             }
 
             // Try to refresh token
             if let Some(refresh_token) = &token.refresh_token {
-                if let Ok(new_token) = self.refresh_token(refresh_token).await {
+                if let Ok(new_token) = self
+                    .refresh_token(refresh_token, token.scope.as_deref())
+                    .await
+                {
                     return Ok(new_token.access_token);
                 }
             }
@@ -226,10 +552,11 @@ impl AuthClient {
         self.authenticate().await
     }
 
-    async fn refresh_token(&self, refresh_token: &str) -> Result<TokenData> {
+    // `scope` is what the old token had, kept in case Google doesn't repeat it
+    async fn refresh_token(&self, refresh_token: &str, scope: Option<&str>) -> Result<TokenData> {
         let client = Client::new();
         let response = client
-            .post("https://oauth2.googleapis.com/token")
+            .post(format!("{}/token", self.oauth_base_url))
             .form(&[
                 ("client_id", self.client_id.as_str()),
                 ("client_secret", self.client_secret.secret()),
@@ -252,7 +579,13 @@ impl AuthClient {
 
         let token_data = TokenData {
             access_token,
-            refresh_token: Some(refresh_token.to_string()),
+            // Google normally keeps the refresh token, but use a new one if it sends one
+            refresh_token: Some(
+                data["refresh_token"]
+                    .as_str()
+                    .unwrap_or(refresh_token)
+                    .to_string(),
+            ),
             expires_at: data["expires_in"].as_u64().map(|d| {
                 std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -260,6 +593,7 @@ impl AuthClient {
                     .as_secs()
                     + d
             }),
+            scope: data["scope"].as_str().or(scope).map(str::to_string),
         };
 
         self.save_token(&token_data).await?;
@@ -267,6 +601,270 @@ impl AuthClient {
     }
 }
 
+// Supplies access tokens to API clients for as long as the session runs
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    // A token that should still be accepted, refreshing it first if it has expired
+    async fn access_token(&self) -> Result<String>;
+
+    // Called when the API rejected `rejected` (HTTP 401); returns a fresh token
+    async fn refresh(&self, rejected: &str) -> Result<String>;
+}
+
+// A fixed token that is never refreshed
+pub struct StaticToken(pub String);
+
+#[async_trait]
+impl TokenProvider for StaticToken {
+    async fn access_token(&self) -> Result<String> {
+        Ok(self.0.clone())
+    }
+
+    async fn refresh(&self, _rejected: &str) -> Result<String> {
+        Err(anyhow::anyhow!(
+            "Access token was rejected and cannot be refreshed"
+        ))
+    }
+}
+
+// Refresh tokens this long before they actually expire
+const EXPIRY_MARGIN_SECS: u64 = 60;
+
+// Token provider backed by the saved token file: refreshes through the AuthClient
+// when the token expires or is rejected, and saves the new TokenData
+pub struct AuthTokenProvider {
+    auth: AuthClient,
+    current: Mutex<Option<TokenData>>,
+}
+
+impl AuthTokenProvider {
+    pub fn new(auth: AuthClient) -> Self {
+        Self {
+            auth,
+            current: Mutex::new(None),
+        }
+    }
+
+    async fn refresh_locked(&self, current: &mut Option<TokenData>) -> Result<String> {
+        let refresh_token = current
+            .as_ref()
+            .and_then(|token| token.refresh_token.clone())
+            .context("Session expired and no refresh token is available. Please restart to sign in again.")?;
+        let scope = current.as_ref().and_then(|token| token.scope.clone());
+
+        let token = self
+            .auth
+            .refresh_token(&refresh_token, scope.as_deref())
+            .await
+            .context("Failed to refresh access token")?;
+        let access_token = token.access_token.clone();
+        *current = Some(token);
+        Ok(access_token)
+    }
+}
+
+#[async_trait]
+impl TokenProvider for AuthTokenProvider {
+    async fn access_token(&self) -> Result<String> {
+        let mut current = self.current.lock().await;
+
+        if current.is_none() {
+            *current = Some(self.auth.load_token().await?);
+        }
+
+        if let Some(token) = current.as_ref() {
+            if !is_expired(token, EXPIRY_MARGIN_SECS) {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        self.refresh_locked(&mut current).await
+    }
+
+    async fn refresh(&self, rejected: &str) -> Result<String> {
+        let mut current = self.current.lock().await;
+
+        if current.is_none() {
+            *current = Some(self.auth.load_token().await?);
+        }
+
+        // Another request may have refreshed it while we were waiting for the lock
+        if let Some(token) = current.as_ref() {
+            if token.access_token != rejected && !is_expired(token, EXPIRY_MARGIN_SECS) {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        self.refresh_locked(&mut current).await
+    }
+}
+
+fn is_expired(token: &TokenData, margin_secs: u64) -> bool {
+    let Some(expires_at) = token.expires_at else {
+        return false;
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    now + margin_secs >= expires_at
+}
+
+// Whether revoke failed because the saved token can't be read back at all,
+// so the only way out is to forget it (see forget_corrupt_token)
+pub fn is_corrupt_token(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<CorruptToken>().is_some()
+}
+
+// Token file for an account: token.json for the default account,
+// accounts/<name>.json for every other one
+pub fn account_token_path(account: &str) -> Result<PathBuf> {
+    validate_account_name(account)?;
+    let config_dir = get_config_dir()?;
+    if account == DEFAULT_ACCOUNT {
+        Ok(config_dir.join("token.json"))
+    } else {
+        Ok(config_dir
+            .join("accounts")
+            .join(format!("{}.json", account)))
+    }
+}
+
+// Accounts that have a saved token, default account first
+pub fn list_accounts() -> Result<Vec<String>> {
+    let config_dir = get_config_dir()?;
+    let mut accounts = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(config_dir.join("accounts")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                if validate_account_name(name).is_ok() && name != DEFAULT_ACCOUNT {
+                    accounts.push(name.to_string());
+                }
+            }
+        }
+    }
+    accounts.sort();
+
+    if config_dir.join("token.json").exists() {
+        accounts.insert(0, DEFAULT_ACCOUNT.to_string());
+    }
+
+    Ok(accounts)
+}
+
+// Account names end up in file names, so keep them to a safe set of characters
+pub fn validate_account_name(account: &str) -> Result<()> {
+    let valid = !account.is_empty()
+        && !account.starts_with('.')
+        && account
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@'));
+    if !valid {
+        return Err(anyhow::anyhow!(
+            "Invalid account name '{}': use letters, digits, '-', '_', '.' or '@'",
+            account
+        ));
+    }
+    Ok(())
+}
+
+// Helper: Accept connections on the loopback listener until the browser is
+// redirected back with an authorization code (or an error). Anything else that
+// reaches the port is answered and ignored, so it can't end the sign-in.
+async fn wait_for_redirect(listener: &TcpListener, expected_state: &str) -> Result<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+
+        let mut buffer = vec![0u8; 8192];
+        let n = stream.read(&mut buffer).await.unwrap_or(0);
+        let request = String::from_utf8_lossy(&buffer[..n]);
+        let Some(target) = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+        else {
+            continue;
+        };
+
+        let Ok(url) = url::Url::parse(&format!("http://127.0.0.1{}", target)) else {
+            respond(&mut stream, "400 Bad Request", "Invalid request.").await;
+            continue;
+        };
+        let params: std::collections::HashMap<String, String> =
+            url.query_pairs().into_owned().collect();
+
+        // Browsers also ask for things like /favicon.ico
+        if !params.contains_key("code") && !params.contains_key("error") {
+            respond(&mut stream, "404 Not Found", "").await;
+            continue;
+        }
+
+        // Not the redirect for this sign-in; keep waiting for the real one
+        if params.get("state").map(String::as_str) != Some(expected_state) {
+            respond(&mut stream, "400 Bad Request", "Invalid sign-in state.").await;
+            continue;
+        }
+
+        if let Some(error) = params.get("error") {
+            respond(
+                &mut stream,
+                "200 OK",
+                "Sign-in was cancelled. You can close this tab.",
+            )
+            .await;
+            return Err(anyhow::anyhow!("Authorization error: {}", error));
+        }
+
+        respond(
+            &mut stream,
+            "200 OK",
+            "Signed in to rustyoutube. You can close this tab and return to the terminal.",
+        )
+        .await;
+        return Ok(params["code"].clone());
+    }
+}
+
+async fn respond(stream: &mut tokio::net::TcpStream, status: &str, message: &str) {
+    let body = format!("<html><body><p>{}</p></body></html>", message);
+    let reply = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(reply.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+// Helper: Best effort; the URL is printed as well
+fn open_in_browser(url: &str) {
+    // Not `cmd /C start`: cmd would split the URL at every `&`
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("rundll32")
+        .args(["url.dll,FileProtocolHandler", url])
+        .spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg(url).spawn();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open").arg(url).spawn();
+
+    let _ = result;
+}
+
+// Whether OAuth client credentials are configured; without them the app can
+// only browse anonymously
+pub fn has_oauth_credentials() -> bool {
+    ["GOOGLE_CLIENT_ID", "GOOGLE_CLIENT_SECRET"]
+        .iter()
+        .all(|name| std::env::var(name).is_ok_and(|value| !value.is_empty()))
+}
+
 fn get_config_dir() -> Result<PathBuf> {
     let dir = dirs::config_dir()
         .or_else(|| dirs::home_dir().map(|d| d.join(".config")))
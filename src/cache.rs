@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::token_store::write_private;
+
+// How long cached data is used before asking the sources again. Older entries
+// are still used when the sources can't be reached.
+pub const VIDEO_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+pub const LIST_TTL: Duration = Duration::from_secs(30 * 60);
+pub const FEED_TTL: Duration = Duration::from_secs(15 * 60);
+
+// While the app runs, changes are written at most this often; the rest is
+// written when the cache is dropped (e.g. on exit)
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+// Entries not updated for this long are dropped when the cache is saved
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+// Cache file name used when no account is signed in
+pub const ANONYMOUS_CACHE: &str = "anonymous";
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    entries: HashMap<String, Entry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Entry {
+    // Seconds since the Unix epoch
+    stored_at: u64,
+    value: serde_json::Value,
+}
+
+// On-disk cache of videos, channels and playlists, keyed like "video:<id>" or
+// "playlist-videos:<id>". Each account has its own file since subscriptions and
+// private playlists are cached too.
+pub struct MetadataCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, Entry>>,
+    // Changed since the last save
+    dirty: AtomicBool,
+    // When the file was last written by this process
+    last_saved: Mutex<Option<Instant>>,
+}
+
+impl MetadataCache {
+    // Load the cache at `path`; a missing or unreadable file starts empty
+    pub fn open(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .map(|file| file.entries)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            entries: Mutex::new(entries),
+            dirty: AtomicBool::new(false),
+            last_saved: Mutex::new(None),
+        }
+    }
+
+    // The cache file for an account (or `ANONYMOUS_CACHE`)
+    pub fn for_account(account: &str) -> Result<Self> {
+        Ok(Self::open(account_cache_path(account)?))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // A value stored less than `ttl` ago
+    pub fn get<T: DeserializeOwned>(&self, key: &str, ttl: Duration) -> Option<T> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+        if now().saturating_sub(entry.stored_at) >= ttl.as_secs() {
+            return None;
+        }
+        serde_json::from_value(entry.value.clone()).ok()
+    }
+
+    // A value of any age, for when the sources can't be reached
+    pub fn get_stale<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let entries = self.entries.lock().unwrap();
+        serde_json::from_value(entries.get(key)?.value.clone()).ok()
+    }
+
+    // Store a value in memory; `save` or `save_if_due` writes it to disk
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) {
+        let Ok(value) = serde_json::to_value(value) else {
+            return;
+        };
+        self.entries.lock().unwrap().insert(
+            key.to_string(),
+            Entry {
+                stored_at: now(),
+                value,
+            },
+        );
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    // Forget every entry whose key starts with `prefix`
+    pub fn remove_prefix(&self, prefix: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| !key.starts_with(prefix));
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    // Write the cache to disk if anything changed and the last write was at
+    // least SAVE_INTERVAL ago
+    pub fn save_if_due(&self) -> Result<()> {
+        // Not `is_none_or`: that needs Rust 1.82
+        #[allow(clippy::unnecessary_map_or)]
+        let due = self
+            .last_saved
+            .lock()
+            .unwrap()
+            .map_or(true, |saved| saved.elapsed() >= SAVE_INTERVAL);
+        if !due {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    // Write the cache to disk if anything changed
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        *self.last_saved.lock().unwrap() = Some(Instant::now());
+
+        let file = {
+            let mut entries = self.entries.lock().unwrap();
+            let now = now();
+            entries.retain(|_, entry| now.saturating_sub(entry.stored_at) < MAX_AGE.as_secs());
+            CacheFile {
+                entries: entries.clone(),
+            }
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write a temporary file first so a crash never leaves half a cache behind.
+        // Private, like the token: it holds the account's subscriptions and playlists.
+        let temp_path = self.path.with_extension("json.tmp");
+        write_private(&temp_path, &serde_json::to_vec(&file)?)?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    // Drop everything, in memory and on disk (e.g. when signing out)
+    pub fn clear(&self) -> Result<()> {
+        self.entries.lock().unwrap().clear();
+        self.dirty.store(false, Ordering::SeqCst);
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).context("Failed to delete cache"),
+        }
+    }
+}
+
+impl Drop for MetadataCache {
+    fn drop(&mut self) {
+        self.save().ok();
+    }
+}
+
+// Helper: cache/<account>.json in the config directory
+fn account_cache_path(account: &str) -> Result<PathBuf> {
+    let dir = dirs::config_dir()
+        .or_else(|| dirs::home_dir().map(|d| d.join(".config")))
+        .context("Failed to find config directory")?
+        .join("rustyoutube")
+        .join("cache");
+    Ok(dir.join(format!("{}.json", account)))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn age_entry(cache: &MetadataCache, key: &str, age: Duration) {
+        let mut entries = cache.entries.lock().unwrap();
+        entries.get_mut(key).unwrap().stored_at = now() - age.as_secs();
+    }
+
+    #[test]
+    fn expired_entries_are_only_used_as_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = MetadataCache::open(dir.path().join("cache.json"));
+        cache.put("video:abc", &"Title".to_string());
+        assert_eq!(
+            cache.get::<String>("video:abc", LIST_TTL).as_deref(),
+            Some("Title")
+        );
+
+        age_entry(&cache, "video:abc", LIST_TTL + Duration::from_secs(1));
+        assert_eq!(cache.get::<String>("video:abc", LIST_TTL), None);
+        assert_eq!(
+            cache.get_stale::<String>("video:abc").as_deref(),
+            Some("Title")
+        );
+    }
+
+    #[test]
+    fn saved_cache_is_reloaded_without_old_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache").join("default.json");
+        let cache = MetadataCache::open(path.clone());
+        cache.put("video:new", &1);
+        cache.put("video:ancient", &2);
+        age_entry(&cache, "video:ancient", MAX_AGE);
+        cache.save().unwrap();
+
+        let reopened = MetadataCache::open(path);
+        assert_eq!(reopened.get::<i32>("video:new", VIDEO_TTL), Some(1));
+        assert_eq!(reopened.get_stale::<i32>("video:ancient"), None);
+    }
+
+    #[test]
+    fn writes_are_batched_and_flushed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let cache = MetadataCache::open(path.clone());
+        cache.put("video:first", &1);
+        cache.save_if_due().unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode, 0o600);
+        }
+
+        // Too soon after the last write
+        cache.put("video:second", &2);
+        cache.save_if_due().unwrap();
+        let on_disk = MetadataCache::open(path.clone());
+        assert_eq!(on_disk.get_stale::<i32>("video:second"), None);
+        drop(on_disk);
+
+        drop(cache);
+        let reopened = MetadataCache::open(path);
+        assert_eq!(reopened.get_stale::<i32>("video:second"), Some(2));
+    }
+}
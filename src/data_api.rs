@@ -0,0 +1,750 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::auth::TokenProvider;
+
+use crate::youtube::{
+    title_looks_like_short, unsupported, Playlist, Rating, Subscription, Video, VideoSource,
+};
+
+pub const DEFAULT_API_BASE_URL: &str = "https://www.googleapis.com/youtube/v3";
+
+// Most items the API returns per page
+const MAX_PAGE_SIZE: usize = 50;
+
+// A channel's videos are its latest uploads, one page of them; whole upload
+// histories can run to thousands of videos
+const CHANNEL_VIDEOS_LIMIT: usize = 50;
+
+// Uploads taken from each subscription for the recommendations
+const RECOMMENDATIONS_PER_CHANNEL: usize = 10;
+
+// How requests are authorized
+#[derive(Clone)]
+enum Credentials {
+    // A signed-in account; everything is available
+    OAuth(Arc<dyn TokenProvider>),
+    // An API key; only public data (channels, playlists) can be read
+    ApiKey(String),
+}
+
+// Video source backed by the YouTube Data API v3 (an OAuth access token, or an API
+// key for public data)
+#[derive(Clone)]
+pub struct DataApiSource {
+    client: Client,
+    credentials: Credentials,
+    base_url: String,
+}
+
+impl DataApiSource {
+    pub fn new(client: Client, tokens: Arc<dyn TokenProvider>) -> Self {
+        Self {
+            client,
+            credentials: Credentials::OAuth(tokens),
+            base_url: DEFAULT_API_BASE_URL.to_string(),
+        }
+    }
+
+    // Public data only, without signing in
+    pub fn public(client: Client, api_key: impl Into<String>) -> Self {
+        Self {
+            client,
+            credentials: Credentials::ApiKey(api_key.into()),
+            base_url: DEFAULT_API_BASE_URL.to_string(),
+        }
+    }
+
+    // Point the source at another API root (e.g. a local test server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    // Helper: GET an API URL; on a 401 refresh the access token and retry once
+    async fn get(&self, url: &str) -> Result<Response> {
+        self.send(|| self.client.get(url)).await
+    }
+
+    // Helper: Send an authorized request built by `request`, retrying once with a
+    // refreshed token on a 401
+    async fn send(&self, request: impl Fn() -> RequestBuilder) -> Result<Response> {
+        let tokens = match &self.credentials {
+            Credentials::OAuth(tokens) => tokens,
+            Credentials::ApiKey(key) => {
+                return Ok(request().query(&[("key", key)]).send().await?);
+            }
+        };
+
+        let token = tokens.access_token().await?;
+        let response = request().bearer_auth(&token).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        let token = match tokens.refresh(&token).await {
+            Ok(token) => token,
+            Err(e) => {
+                // Report the API's own error, with the refresh failure as the cause
+                let error_text = response.text().await.unwrap_or_default();
+                let api_error = api_error(StatusCode::UNAUTHORIZED, &error_text);
+                return Err(e.context(api_error.to_string()));
+            }
+        };
+        Ok(request().bearer_auth(&token).send().await?)
+    }
+
+    // Helper: Account data and changes need a signed-in account
+    fn require_account(&self, operation: &'static str) -> Result<()> {
+        match self.credentials {
+            Credentials::OAuth(_) => Ok(()),
+            Credentials::ApiKey(_) => Err(unsupported("YouTube Data API (API key)", operation)),
+        }
+    }
+
+    // Helper: Send an account change and check that it went through
+    async fn change(&self, request: impl Fn() -> RequestBuilder) -> Result<()> {
+        let response = self.send(request).await?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        let status = response.status();
+        let error_text = response.text().await?;
+        let error = api_error(status, &error_text);
+        // Tokens from before write access was granted only have the read-only scope
+        if status == StatusCode::FORBIDDEN
+            && (error_text.contains("insufficientPermissions")
+                || error_text.contains("ACCESS_TOKEN_SCOPE_INSUFFICIENT"))
+        {
+            return Err(error.context(
+                "This needs permission to manage your YouTube account. Restart with --write-access to grant it.",
+            ));
+        }
+        Err(error)
+    }
+
+    // Helper: Videos of a playlist, in playlist order. With a `limit` only the
+    // first page is fetched, holding at most that many videos.
+    async fn playlist_items(&self, playlist_id: &str, limit: Option<usize>) -> Result<Vec<Video>> {
+        let page_size = limit.map_or(MAX_PAGE_SIZE, |limit| limit.clamp(1, MAX_PAGE_SIZE));
+        let mut videos = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/playlistItems?part=snippet,contentDetails&playlistId={}&maxResults={}",
+                self.base_url, playlist_id, page_size
+            );
+            if let Some(token) = &page_token {
+                url.push_str(&format!("&pageToken={}", token));
+            }
+
+            let response = self.get(&url).await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(api_error(status, &error_text));
+            }
+
+            let response_text = response.text().await?;
+            let data: PlaylistItemsResponse =
+                serde_json::from_str(&response_text).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to parse playlist items response: {}\nResponse: {}",
+                        e,
+                        response_text
+                    )
+                })?;
+
+            for item in data.items {
+                // Try to get video ID from content_details first, then from resourceId
+                let video_id = if let Some(content_details) = &item.content_details {
+                    Some(content_details.video_id.clone())
+                } else if let Some(resource_id) = &item.snippet.resource_id {
+                    resource_id.video_id.clone()
+                } else {
+                    None
+                };
+
+                if let Some(vid_id) = video_id {
+                    let snippet = item.snippet;
+                    // Prefer the video's own publish date over when it was added to the playlist
+                    let published_at = item
+                        .content_details
+                        .and_then(|cd| cd.video_published_at)
+                        .or(snippet.published_at)
+                        .unwrap_or_else(|| "Unknown date".to_string());
+                    videos.push(Video {
+                        id: vid_id,
+                        is_short: title_looks_like_short(&snippet.title),
+                        title: snippet.title,
+                        channel_id: snippet.video_owner_channel_id.unwrap_or_default(),
+                        channel_title: snippet
+                            .video_owner_channel_title
+                            .or(snippet.channel_title)
+                            .unwrap_or_else(|| "Unknown Channel".to_string()),
+                        published_at,
+                        thumbnail_url: snippet.thumbnails.default.url,
+                        playlist_item_id: Some(item.id).filter(|id| !id.is_empty()),
+                        ..Default::default()
+                    });
+                } else {
+                    // If we can't get video ID, skip this item
+                    eprintln!(
+                        "Warning: Playlist item missing video ID, skipping: {}",
+                        item.snippet.title
+                    );
+                }
+            }
+
+            page_token = data.next_page_token;
+            if page_token.is_none() || limit.is_some() {
+                break;
+            }
+        }
+
+        Ok(videos)
+    }
+
+    // Helper: A channel's newest `limit` uploads (via the channel's uploads playlist)
+    async fn uploads(&self, channel_id: &str, limit: usize) -> Result<Vec<Video>> {
+        // Get uploads playlist ID
+        let url = format!(
+            "{}/channels?part=contentDetails&id={}",
+            self.base_url, channel_id
+        );
+        let response = self.get(&url).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(api_error(status, &error_text).context("Failed to get channel info"));
+        }
+
+        let data: ChannelListResponse = response
+            .json()
+            .await
+            .context("Failed to parse channel response")?;
+        let uploads_playlist_id = data
+            .items
+            .into_iter()
+            .next()
+            .and_then(|item| item.content_details.related_playlists.uploads)
+            .context("No uploads playlist found")?;
+
+        // Get videos from uploads playlist
+        self.playlist_items(&uploads_playlist_id, Some(limit)).await
+    }
+}
+
+#[async_trait]
+impl VideoSource for DataApiSource {
+    fn name(&self) -> &'static str {
+        "YouTube Data API"
+    }
+
+    fn is_authenticated(&self) -> bool {
+        matches!(self.credentials, Credentials::OAuth(_))
+    }
+
+    // Get subscriptions (requires authentication)
+    async fn get_subscriptions(&self) -> Result<Vec<Subscription>> {
+        self.require_account("subscriptions")?;
+        let mut subscriptions = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/subscriptions?part=snippet&mine=true&maxResults=50",
+                self.base_url
+            );
+            if let Some(token) = &page_token {
+                url.push_str(&format!("&pageToken={}", token));
+            }
+
+            let response = self.get(&url).await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(api_error(status, &error_text));
+            }
+
+            let response_text = response.text().await?;
+            let data: SubscriptionResponse = serde_json::from_str(&response_text).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to parse subscription response: {}\nResponse: {}",
+                    e,
+                    response_text
+                )
+            })?;
+
+            let items_count = data.items.len();
+            for item in data.items {
+                if let Some(resource_id) = &item.snippet.resource_id {
+                    subscriptions.push(Subscription {
+                        channel_id: resource_id.channel_id.clone(),
+                        channel_title: item.snippet.title.clone(),
+                        thumbnail_url: item.snippet.thumbnails.default.url.clone(),
+                    });
+                } else {
+                    // Log warning for items without resource_id
+                    eprintln!(
+                        "Warning: Subscription item missing resource_id: {:?}",
+                        item.snippet.title
+                    );
+                }
+            }
+
+            // If we got items but none had resource_id, log warning
+            if items_count > 0 && subscriptions.is_empty() {
+                eprintln!(
+                    "Warning: Received {} subscription items but none had resource_id",
+                    items_count
+                );
+            }
+
+            page_token = data.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(subscriptions)
+    }
+
+    // Get playlists
+    async fn get_playlists(&self) -> Result<Vec<Playlist>> {
+        self.require_account("playlists")?;
+        let mut playlists = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/playlists?part=snippet,contentDetails&mine=true&maxResults=50",
+                self.base_url
+            );
+            if let Some(token) = &page_token {
+                url.push_str(&format!("&pageToken={}", token));
+            }
+
+            let response = self.get(&url).await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(api_error(status, &error_text));
+            }
+
+            let response_text = response.text().await?;
+            let data: PlaylistResponse = serde_json::from_str(&response_text).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to parse playlist response: {}\nResponse: {}",
+                    e,
+                    response_text
+                )
+            })?;
+
+            for item in data.items {
+                let item_count = item
+                    .content_details
+                    .as_ref()
+                    .map(|cd| cd.item_count)
+                    .unwrap_or(0);
+                playlists.push(Playlist {
+                    id: item.id,
+                    title: item.snippet.title,
+                    description: item.snippet.description,
+                    item_count,
+                });
+            }
+
+            page_token = data.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(playlists)
+    }
+
+    // Get videos from a playlist
+    async fn get_playlist_videos(&self, playlist_id: &str) -> Result<Vec<Video>> {
+        self.playlist_items(playlist_id, None).await
+    }
+
+    // Get a channel's latest uploads
+    async fn get_channel_videos_by_id(&self, channel_id: &str) -> Result<Vec<Video>> {
+        self.uploads(channel_id, CHANNEL_VIDEOS_LIMIT).await
+    }
+
+    // Get channel playlists by channel ID
+    async fn get_channel_playlists(&self, channel_id: &str) -> Result<Vec<Playlist>> {
+        let mut playlists = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/playlists?part=snippet,contentDetails&channelId={}&maxResults=50",
+                self.base_url, channel_id
+            );
+            if let Some(token) = &page_token {
+                url.push_str(&format!("&pageToken={}", token));
+            }
+
+            let response = self.get(&url).await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await?;
+                return Err(api_error(status, &error_text));
+            }
+
+            let response_text = response.text().await?;
+            let data: PlaylistResponse = serde_json::from_str(&response_text).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to parse playlist response: {}\nResponse: {}",
+                    e,
+                    response_text
+                )
+            })?;
+
+            for item in data.items {
+                let item_count = item
+                    .content_details
+                    .as_ref()
+                    .map(|cd| cd.item_count)
+                    .unwrap_or(0);
+                playlists.push(Playlist {
+                    id: item.id,
+                    title: item.snippet.title,
+                    description: item.snippet.description,
+                    item_count,
+                });
+            }
+
+            page_token = data.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(playlists)
+    }
+
+    // Gets recent videos from user's subscriptions as personalized recommendations
+    async fn get_recommendations(&self) -> Result<Vec<Video>> {
+        // Note: The 'home' parameter in activities.list is deprecated
+        // So we get videos from user's subscriptions as a form of recommendations
+        let subscriptions = self.get_subscriptions().await?;
+
+        if subscriptions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Get recent videos from first few subscriptions
+        let mut all_videos = Vec::new();
+        for sub in subscriptions.iter().take(5) {
+            if let Ok(videos) = self
+                .uploads(&sub.channel_id, RECOMMENDATIONS_PER_CHANNEL)
+                .await
+            {
+                all_videos.extend(videos);
+            }
+        }
+
+        // Limit to 50 videos
+        all_videos.truncate(50);
+        Ok(all_videos)
+    }
+
+    async fn subscribe(&self, channel_id: &str) -> Result<()> {
+        self.require_account("subscribing")?;
+        let url = format!("{}/subscriptions?part=snippet", self.base_url);
+        let body = serde_json::json!({
+            "snippet": {
+                "resourceId": { "kind": "youtube#channel", "channelId": channel_id }
+            }
+        });
+        self.change(|| self.client.post(&url).json(&body)).await
+    }
+
+    // Subscriptions are deleted by their own ID, so look it up for the channel first
+    async fn unsubscribe(&self, channel_id: &str) -> Result<()> {
+        self.require_account("unsubscribing")?;
+        let url = format!(
+            "{}/subscriptions?part=id&mine=true&forChannelId={}",
+            self.base_url, channel_id
+        );
+        let response = self.get(&url).await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(api_error(status, &error_text));
+        }
+
+        let data: SubscriptionIdResponse = response
+            .json()
+            .await
+            .context("Failed to parse subscription response")?;
+        let subscription = data
+            .items
+            .into_iter()
+            .next()
+            .context("You are not subscribed to this channel")?;
+
+        let url = format!("{}/subscriptions?id={}", self.base_url, subscription.id);
+        self.change(|| self.client.delete(&url)).await
+    }
+
+    async fn rate_video(&self, video_id: &str, rating: Rating) -> Result<()> {
+        self.require_account("rating videos")?;
+        let url = format!(
+            "{}/videos/rate?id={}&rating={}",
+            self.base_url,
+            video_id,
+            rating.as_str()
+        );
+        // Empty body, but Google still wants a Content-Length
+        self.change(|| self.client.post(&url).header(CONTENT_LENGTH, 0))
+            .await
+    }
+
+    async fn add_to_playlist(&self, playlist_id: &str, video_id: &str) -> Result<()> {
+        self.require_account("adding to playlists")?;
+        let url = format!("{}/playlistItems?part=snippet", self.base_url);
+        let body = serde_json::json!({
+            "snippet": {
+                "playlistId": playlist_id,
+                "resourceId": { "kind": "youtube#video", "videoId": video_id }
+            }
+        });
+        self.change(|| self.client.post(&url).json(&body)).await
+    }
+
+    async fn remove_from_playlist(&self, playlist_item_id: &str) -> Result<()> {
+        self.require_account("removing from playlists")?;
+        let url = format!("{}/playlistItems?id={}", self.base_url, playlist_item_id);
+        self.change(|| self.client.delete(&url)).await
+    }
+}
+
+// Helper: Turn an API error response into a readable error. Google wraps errors as
+// {"error": {"code": 403, "message": "...", "errors": [{"reason": "quotaExceeded"}]}}
+fn api_error(status: StatusCode, body: &str) -> anyhow::Error {
+    match serde_json::from_str::<ApiErrorResponse>(body) {
+        Ok(response) => {
+            let reasons: Vec<&str> = response
+                .error
+                .errors
+                .iter()
+                .map(|e| e.reason.as_str())
+                .filter(|reason| !reason.is_empty())
+                .collect();
+            if reasons.is_empty() {
+                anyhow::anyhow!("API error ({}): {}", status, response.error.message)
+            } else {
+                anyhow::anyhow!(
+                    "API error ({}): {} [{}]",
+                    status,
+                    response.error.message,
+                    reasons.join(", ")
+                )
+            }
+        }
+        Err(_) => anyhow::anyhow!("API error ({}): {}", status, body),
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiErrorResponse {
+    error: ApiErrorBody,
+}
+
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    errors: Vec<ApiErrorDetail>,
+}
+
+#[derive(Deserialize)]
+struct ApiErrorDetail {
+    #[serde(default)]
+    reason: String,
+}
+
+// API Response structures (the API uses camelCase field names throughout)
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionResponse {
+    #[serde(default)]
+    items: Vec<SubscriptionItem>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionIdResponse {
+    #[serde(default)]
+    items: Vec<SubscriptionId>,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionId {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionItem {
+    snippet: SubscriptionSnippet,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionSnippet {
+    title: String,
+    #[serde(default)]
+    resource_id: Option<ResourceId>,
+    #[serde(default)]
+    thumbnails: Thumbnails,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceId {
+    channel_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistResponse {
+    #[serde(default)]
+    items: Vec<PlaylistItem>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItem {
+    id: String,
+    snippet: PlaylistSnippet,
+    #[serde(default)]
+    content_details: Option<PlaylistContentDetails>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistSnippet {
+    title: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistContentDetails {
+    #[serde(default)]
+    item_count: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistItemsResponse {
+    #[serde(default)]
+    items: Vec<PlaylistVideoItem>,
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistVideoItem {
+    #[serde(default)]
+    id: String,
+    snippet: PlaylistVideoSnippet,
+    #[serde(default)]
+    content_details: Option<PlaylistVideoContentDetails>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistVideoSnippet {
+    title: String,
+    // Owner of the playlist, not necessarily of the video
+    #[serde(default)]
+    channel_title: Option<String>,
+    #[serde(default)]
+    video_owner_channel_title: Option<String>,
+    #[serde(default)]
+    video_owner_channel_id: Option<String>,
+    // When the video was added to the playlist
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    thumbnails: Thumbnails,
+    #[serde(default)]
+    resource_id: Option<PlaylistResourceId>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistResourceId {
+    #[serde(default)]
+    video_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaylistVideoContentDetails {
+    video_id: String,
+    #[serde(default)]
+    video_published_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelListResponse {
+    #[serde(default)]
+    items: Vec<ChannelItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelItem {
+    content_details: ChannelContentDetails,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelContentDetails {
+    related_playlists: RelatedPlaylists,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelatedPlaylists {
+    #[serde(default)]
+    uploads: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct Thumbnails {
+    #[serde(default)]
+    default: Thumbnail,
+}
+
+#[derive(Deserialize, Default)]
+struct Thumbnail {
+    #[serde(default)]
+    url: String,
+}
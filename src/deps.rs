@@ -1,6 +1,9 @@
 use anyhow::Result;
 use std::fs;
-use std::path::{Path, PathBuf};
+#[cfg(windows)]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(windows)]
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command as TokioCommand;
 
@@ -14,9 +17,20 @@ const YTDLP_CMD: &str = "yt-dlp.exe";
 #[cfg(not(windows))]
 const YTDLP_CMD: &str = "yt-dlp";
 
+#[cfg(windows)]
+const FFMPEG_CMD: &str = "ffmpeg.exe";
+#[cfg(not(windows))]
+const FFMPEG_CMD: &str = "ffmpeg";
+
 pub async fn ensure_dependencies() -> Result<()> {
     ensure_mpv().await?;
     ensure_ytdlp().await?;
+    // Optional: only audio downloads can't do without it
+    if check_ffmpeg().await {
+        println!("✓ ffmpeg is installed");
+    } else {
+        println!("ffmpeg not found - audio downloads won't be available");
+    }
     Ok(())
 }
 
@@ -112,7 +126,7 @@ pub async fn ensure_mpv() -> Result<()> {
         if check_command("apt").await {
             println!("Installing mpv via apt...");
             let status = TokioCommand::new("sudo")
-                .args(&["apt", "install", "-y", "mpv"])
+                .args(["apt", "install", "-y", "mpv"])
                 .status()
                 .await;
 
@@ -126,7 +140,7 @@ pub async fn ensure_mpv() -> Result<()> {
         if check_command("pacman").await {
             println!("Installing mpv via pacman...");
             let status = TokioCommand::new("sudo")
-                .args(&["pacman", "-S", "--noconfirm", "mpv"])
+                .args(["pacman", "-S", "--noconfirm", "mpv"])
                 .status()
                 .await;
 
@@ -297,7 +311,7 @@ pub async fn ensure_ytdlp() -> Result<()> {
             };
             println!("Installing yt-dlp via pip...");
             let status = TokioCommand::new(pip_cmd)
-                .args(&["install", "--user", "--upgrade", "yt-dlp"])
+                .args(["install", "--user", "--upgrade", "yt-dlp"])
                 .status()
                 .await;
 
@@ -313,7 +327,7 @@ pub async fn ensure_ytdlp() -> Result<()> {
         if check_command("apt").await {
             println!("Installing yt-dlp via apt...");
             let status = TokioCommand::new("sudo")
-                .args(&["apt", "install", "-y", "yt-dlp"])
+                .args(["apt", "install", "-y", "yt-dlp"])
                 .status()
                 .await;
 
@@ -329,7 +343,7 @@ pub async fn ensure_ytdlp() -> Result<()> {
         if check_command("pacman").await {
             println!("Installing yt-dlp via pacman...");
             let status = TokioCommand::new("sudo")
-                .args(&["pacman", "-S", "--noconfirm", "yt-dlp"])
+                .args(["pacman", "-S", "--noconfirm", "yt-dlp"])
                 .status()
                 .await;
 
@@ -439,7 +453,7 @@ async fn upgrade_ytdlp() -> Result<()> {
                 "pip"
             };
             let status = TokioCommand::new(pip_cmd)
-                .args(&["install", "--user", "--upgrade", "yt-dlp"])
+                .args(["install", "--user", "--upgrade", "yt-dlp"])
                 .status()
                 .await;
 
@@ -671,6 +685,15 @@ pub async fn get_ytdlp_path() -> Option<PathBuf> {
     None
 }
 
+// yt-dlp command to run - prefers the local copy if one was downloaded
+pub async fn ytdlp_cmd() -> String {
+    if let Some(local_ytdlp) = get_ytdlp_path().await {
+        local_ytdlp.to_string_lossy().to_string()
+    } else {
+        YTDLP_CMD.to_string()
+    }
+}
+
 #[cfg(windows)]
 async fn download_mpv_from_github() -> Result<()> {
     use reqwest::Client;
@@ -942,6 +965,17 @@ pub async fn check_ytdlp() -> bool {
     check_command(YTDLP_CMD).await
 }
 
+// ffmpeg isn't installed by the app; yt-dlp needs it to extract audio and to
+// embed metadata and cover art
+pub async fn check_ffmpeg() -> bool {
+    // ffmpeg only knows "-version"
+    TokioCommand::new(FFMPEG_CMD)
+        .arg("-version")
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
 // Helper function to check if we should check for updates (once per day)
 async fn should_check_for_updates(tool: &str) -> bool {
     let app_data = match dirs::data_local_dir() {
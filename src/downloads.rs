@@ -0,0 +1,1411 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command as TokioCommand;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::deps;
+use crate::player::{DownloadOptions, AUDIO_FORMAT_SELECTOR};
+use crate::settings::{SegmentAction, SponsorBlockPrefs, SubtitleMode, SubtitlePrefs};
+use crate::youtube::Video;
+
+// yt-dlp prints these lines for us; the prefixes tell them apart from its other output
+const PROGRESS_PREFIX: &str = "[rustyoutube-progress]";
+const FILE_PREFIX: &str = "[rustyoutube-file]";
+const ITEM_PREFIX: &str = "[rustyoutube-item]";
+// What yt-dlp says about playlist items in the archive
+const ARCHIVED_SUFFIX: &str = " has already been recorded in the archive";
+
+// Completed downloads kept in downloads.json
+const MAX_COMPLETED: usize = 1_000;
+
+// Folder levels searched for a file that was already downloaded
+const SCAN_DEPTH: usize = 3;
+
+// Files that count as a downloaded video or audio file (partial and sidecar
+// files don't)
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "webm", "mov", "avi", "flv"];
+const AUDIO_EXTENSIONS: &[&str] = &["m4a", "mp3", "opus", "ogg", "flac", "wav", "aac"];
+
+// Longest value put into a file name, in bytes; leaves room for the rest of
+// the template within the usual 255-byte limit
+const MAX_NAME_BYTES: usize = 150;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobState {
+    Queued,
+    Running,
+    // Stopped by the user; resuming continues the partial file
+    Paused,
+    Finished,
+    Failed(String),
+    Cancelled,
+    // A file of this video was already on disk; retrying downloads it anyway
+    Skipped,
+}
+
+// Progress of the file being downloaded, from yt-dlp's progress template
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    // Exact or estimated size in bytes
+    pub total: Option<u64>,
+    // Bytes per second
+    pub speed: Option<f64>,
+    // Seconds left
+    pub eta: Option<u64>,
+}
+
+impl DownloadProgress {
+    pub fn percent(&self) -> Option<f64> {
+        let total = self.total.filter(|total| *total > 0)?;
+        Some((self.downloaded as f64 / total as f64 * 100.0).min(100.0))
+    }
+}
+
+// What a job downloads
+#[derive(Debug, Clone)]
+pub enum DownloadTarget {
+    Video(Box<Video>),
+    Playlist { id: String, title: String },
+    // A channel's uploads; `id` is a channel ID, an @handle or a URL
+    Channel { id: String, title: String },
+}
+
+impl DownloadTarget {
+    pub fn title(&self) -> &str {
+        match self {
+            DownloadTarget::Video(video) => &video.title,
+            DownloadTarget::Playlist { title, .. } | DownloadTarget::Channel { title, .. } => title,
+        }
+    }
+
+    // Playlists and channels: many videos, each with its own status
+    pub fn is_collection(&self) -> bool {
+        !matches!(self, DownloadTarget::Video(_))
+    }
+
+    pub fn url(&self) -> String {
+        match self {
+            DownloadTarget::Video(video) => {
+                format!("https://www.youtube.com/watch?v={}", video.id)
+            }
+            DownloadTarget::Playlist { id, .. } => {
+                format!("https://www.youtube.com/playlist?list={}", id)
+            }
+            DownloadTarget::Channel { id, .. } if id.starts_with("http") => id.clone(),
+            DownloadTarget::Channel { id, .. } if id.starts_with('@') => {
+                format!("https://www.youtube.com/{}/videos", id)
+            }
+            DownloadTarget::Channel { id, .. } => {
+                format!("https://www.youtube.com/channel/{}/videos", id)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemState {
+    Running,
+    Finished,
+    // Downloaded before, according to the archive
+    Archived,
+    Failed(String),
+}
+
+// One video of a playlist or channel job
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadItem {
+    pub video_id: String,
+    pub title: String,
+    pub state: ItemState,
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadJob {
+    pub id: u64,
+    pub target: DownloadTarget,
+    pub options: DownloadOptions,
+    pub state: JobState,
+    // Progress of the file being downloaded
+    pub progress: Option<DownloadProgress>,
+    // Where the file ended up (or the existing file of a skipped job)
+    pub file: Option<PathBuf>,
+    // Videos of a playlist or channel seen so far in this run
+    pub items: Vec<DownloadItem>,
+    // Videos in the playlist or channel, once yt-dlp knows
+    pub item_count: Option<usize>,
+}
+
+impl DownloadJob {
+    // Helper: The item being downloaded
+    fn current_item(&mut self) -> Option<&mut DownloadItem> {
+        self.items
+            .iter_mut()
+            .rev()
+            .find(|item| item.state == ItemState::Running)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedDownload {
+    pub video_id: String,
+    pub title: String,
+    pub path: PathBuf,
+    // Unix timestamp
+    pub finished_at: i64,
+}
+
+// Runs downloads in the background, at most `concurrency` at a time. Cloning
+// gives another handle to the same downloads.
+#[derive(Clone)]
+pub struct DownloadManager {
+    inner: Arc<Mutex<Inner>>,
+    // Version of the completed list last written to downloads.json
+    saved_version: Arc<Mutex<u64>>,
+}
+
+struct Inner {
+    jobs: Vec<DownloadJob>,
+    next_id: u64,
+    concurrency: usize,
+    // yt-dlp to run; found through deps when not set
+    program: Option<String>,
+    log_tx: Option<mpsc::UnboundedSender<String>>,
+    // Stops a running job's yt-dlp
+    stops: HashMap<u64, oneshot::Sender<()>>,
+    // Jobs whose run hasn't ended yet. A paused or cancelled job keeps its slot
+    // until its yt-dlp has exited, so two never work on the same .part file.
+    active: HashSet<u64>,
+    completed: Vec<CompletedDownload>,
+    // Bumped on every change to `completed`
+    completed_version: u64,
+    // downloads.json (completed downloads are not saved without one)
+    record_path: Option<PathBuf>,
+    // yt-dlp's --download-archive: IDs of the videos playlist and channel
+    // downloads fetched, so they only fetch new ones next time (audio
+    // extractions use a sibling archive, see archive_for)
+    archive_path: Option<PathBuf>,
+}
+
+impl DownloadManager {
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                jobs: Vec::new(),
+                next_id: 1,
+                concurrency: concurrency.max(1),
+                program: None,
+                log_tx: None,
+                stops: HashMap::new(),
+                active: HashSet::new(),
+                completed: Vec::new(),
+                completed_version: 0,
+                record_path: None,
+                archive_path: None,
+            })),
+            saved_version: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    // A manager that records completed downloads at `record_path`
+    pub fn open(record_path: PathBuf, concurrency: usize) -> Self {
+        let manager = Self::new(concurrency);
+        {
+            let mut inner = manager.inner.lock().unwrap();
+            inner.completed = fs::read_to_string(&record_path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default();
+            inner.record_path = Some(record_path);
+        }
+        manager
+    }
+
+    pub fn open_default(concurrency: usize) -> Result<Self> {
+        let dir = dirs::config_dir()
+            .or_else(|| dirs::home_dir().map(|d| d.join(".config")))
+            .context("Failed to find config directory")?
+            .join("rustyoutube");
+        Ok(Self::open(dir.join("downloads.json"), concurrency)
+            .with_archive(dir.join("archive.txt")))
+    }
+
+    // Keep the download archive at `path`
+    pub fn with_archive(self, path: PathBuf) -> Self {
+        self.inner.lock().unwrap().archive_path = Some(path);
+        self
+    }
+
+    // Run this program instead of yt-dlp
+    pub fn with_program(self, program: impl Into<String>) -> Self {
+        self.inner.lock().unwrap().program = Some(program.into());
+        self
+    }
+
+    // Send yt-dlp's messages here
+    pub fn with_log(self, log_tx: mpsc::UnboundedSender<String>) -> Self {
+        self.inner.lock().unwrap().log_tx = Some(log_tx);
+        self
+    }
+
+    pub fn set_concurrency(&self, concurrency: usize) {
+        self.inner.lock().unwrap().concurrency = concurrency.max(1);
+        self.schedule();
+    }
+
+    pub fn jobs(&self) -> Vec<DownloadJob> {
+        self.inner.lock().unwrap().jobs.clone()
+    }
+
+    pub fn job(&self, id: u64) -> Option<DownloadJob> {
+        let inner = self.inner.lock().unwrap();
+        inner.jobs.iter().find(|job| job.id == id).cloned()
+    }
+
+    // Recorded downloads, newest first
+    pub fn completed(&self) -> Vec<CompletedDownload> {
+        self.inner.lock().unwrap().completed.clone()
+    }
+
+    // Add a download; it starts once a slot is free. Returns the job's ID.
+    pub fn enqueue(&self, video: Video, options: DownloadOptions) -> u64 {
+        self.enqueue_target(DownloadTarget::Video(Box::new(video)), options)
+    }
+
+    // Add a download of a video, a whole playlist or a channel's uploads
+    pub fn enqueue_target(&self, target: DownloadTarget, options: DownloadOptions) -> u64 {
+        let id = {
+            let mut inner = self.inner.lock().unwrap();
+            let id = inner.next_id;
+            inner.next_id += 1;
+            inner.jobs.push(DownloadJob {
+                id,
+                target,
+                options,
+                state: JobState::Queued,
+                progress: None,
+                file: None,
+                items: Vec::new(),
+                item_count: None,
+            });
+            id
+        };
+        self.schedule();
+        id
+    }
+
+    // Stop a queued or running download for now
+    pub fn pause(&self, id: u64) -> bool {
+        self.stop(id, JobState::Paused, &[JobState::Queued, JobState::Running])
+    }
+
+    pub fn resume(&self, id: u64) -> bool {
+        self.requeue(id, |state| *state == JobState::Paused)
+    }
+
+    pub fn cancel(&self, id: u64) -> bool {
+        self.stop(
+            id,
+            JobState::Cancelled,
+            &[JobState::Queued, JobState::Running, JobState::Paused],
+        )
+    }
+
+    // Try a failed, cancelled or skipped download again
+    pub fn retry(&self, id: u64) -> bool {
+        self.requeue(id, |state| {
+            matches!(
+                state,
+                JobState::Failed(_) | JobState::Cancelled | JobState::Skipped
+            )
+        })
+    }
+
+    // Drop a job that isn't running from the list
+    pub fn remove(&self, id: u64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.jobs.len();
+        inner
+            .jobs
+            .retain(|job| job.id != id || job.state == JobState::Running);
+        inner.jobs.len() != before
+    }
+
+    // Drop finished, skipped and cancelled jobs from the list
+    pub fn clear_finished(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.jobs.retain(|job| {
+            !matches!(
+                job.state,
+                JobState::Finished | JobState::Cancelled | JobState::Skipped
+            )
+        });
+    }
+
+    // Helper: Move a job to `state`, stopping its yt-dlp if it runs
+    fn stop(&self, id: u64, state: JobState, from: &[JobState]) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) else {
+            return false;
+        };
+        if !from.contains(&job.state) {
+            return false;
+        }
+        job.state = state;
+        if let Some(stop) = inner.stops.remove(&id) {
+            let _ = stop.send(());
+        }
+        true
+    }
+
+    // Helper: Queue a job again if its state allows it
+    fn requeue(&self, id: u64, allowed: impl Fn(&JobState) -> bool) -> bool {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) else {
+                return false;
+            };
+            if !allowed(&job.state) {
+                return false;
+            }
+            // The user saw the existing file and wants it downloaded again
+            if job.state == JobState::Skipped {
+                job.options.force = true;
+            }
+            job.state = JobState::Queued;
+            job.progress = None;
+            // The archive tells which videos are done when it runs again
+            job.items.clear();
+        }
+        self.schedule();
+        true
+    }
+
+    // Helper: Start queued jobs while there are free slots. A job resumed
+    // before its last run ended waits for that run.
+    fn schedule(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let free = inner.concurrency.saturating_sub(inner.active.len());
+        let to_start: Vec<u64> = inner
+            .jobs
+            .iter()
+            .filter(|job| job.state == JobState::Queued && !inner.active.contains(&job.id))
+            .take(free)
+            .map(|job| job.id)
+            .collect();
+        for id in to_start {
+            let (stop_tx, stop_rx) = oneshot::channel();
+            inner.stops.insert(id, stop_tx);
+            inner.active.insert(id);
+            let archive = inner.archive_path.clone();
+            if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                job.state = JobState::Running;
+                let args = download_args(&job.target, &job.options, archive.as_deref());
+                tokio::spawn(self.clone().run_job(job.clone(), args, stop_rx));
+            }
+        }
+    }
+
+    // Helper: Run one yt-dlp and keep the job up to date. Videos already on
+    // disk are skipped unless the job is forced.
+    async fn run_job(self, job: DownloadJob, args: Vec<String>, stop_rx: oneshot::Receiver<()>) {
+        let id = job.id;
+        let existing = match &job.target {
+            DownloadTarget::Video(video) if !job.options.force => {
+                self.existing_file(video, &job.options).await
+            }
+            _ => None,
+        };
+        let result = match existing {
+            Some(path) => {
+                let mut inner = self.inner.lock().unwrap();
+                if let Some(tx) = &inner.log_tx {
+                    let _ = tx.send(format!("Already downloaded: {}", path.display()));
+                }
+                if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                    job.file = Some(path);
+                }
+                Ok(Some(JobState::Skipped))
+            }
+            None => self
+                .download(id, args, stop_rx)
+                .await
+                .map(|done| done.then_some(JobState::Finished)),
+        };
+        let save = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.stops.remove(&id);
+            inner.active.remove(&id);
+            // Paused and cancelled jobs were already moved on
+            let state = match result {
+                Ok(state) => state,
+                Err(e) => Some(JobState::Failed(format!("{:#}", e))),
+            };
+            match state {
+                Some(state) => {
+                    if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                        job.state = state;
+                    }
+                    inner.record_finished(id)
+                }
+                None => None,
+            }
+        };
+        self.schedule();
+        if let Some(save) = save {
+            self.save_completed(save).await;
+        }
+    }
+
+    // Helper: A file of the job's video already on disk: a recorded download
+    // that is still there, or a media file with the video's ID in its name
+    // under the download folder
+    async fn existing_file(&self, video: &Video, options: &DownloadOptions) -> Option<PathBuf> {
+        let recorded: Vec<PathBuf> = {
+            let inner = self.inner.lock().unwrap();
+            inner
+                .completed
+                .iter()
+                .filter(|entry| entry.video_id == video.id)
+                .map(|entry| entry.path.clone())
+                .collect()
+        };
+        let directory = options.directory.clone();
+        let video_id = video.id.clone();
+        // An audio download isn't a copy of the video, nor the other way round
+        let extensions = if options.audio.is_some() {
+            AUDIO_EXTENSIONS
+        } else {
+            VIDEO_EXTENSIONS
+        };
+        tokio::task::spawn_blocking(move || {
+            recorded
+                .into_iter()
+                .find(|path| path.is_file() && has_extension(path, extensions))
+                .or_else(|| {
+                    directory
+                        .and_then(|dir| find_video_file(&dir, &video_id, extensions, SCAN_DEPTH))
+                })
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    // Helper: Download the job's video; false when it was stopped
+    async fn download(
+        &self,
+        id: u64,
+        args: Vec<String>,
+        mut stop_rx: oneshot::Receiver<()>,
+    ) -> Result<bool> {
+        let (program, log_tx) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.program.clone(), inner.log_tx.clone())
+        };
+        let program = match program {
+            Some(program) => program,
+            None => {
+                if !deps::check_ytdlp().await {
+                    deps::ensure_ytdlp().await?;
+                }
+                deps::ytdlp_cmd().await
+            }
+        };
+        // Paused or cancelled before it got going
+        if stop_rx.try_recv().is_ok() {
+            return Ok(false);
+        }
+
+        let mut child = TokioCommand::new(&program)
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", program))?;
+
+        // Progress and the final file name come on stdout
+        let stdout = child.stdout.take();
+        let manager = self.clone();
+        let stdout_log = log_tx.clone();
+        let stdout_task = tokio::spawn(async move {
+            let Some(stdout) = stdout else {
+                return;
+            };
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                manager.handle_line(id, &line, stdout_log.as_ref());
+            }
+        });
+        // Keep the last error yt-dlp printed for the job's status
+        let stderr = child.stderr.take();
+        let manager = self.clone();
+        let stderr_task = tokio::spawn(async move {
+            let mut last_error = String::new();
+            if let Some(stderr) = stderr {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if let Some(tx) = &log_tx {
+                        let _ = tx.send(line.clone());
+                    }
+                    manager.handle_error(id, &line);
+                    last_error = line;
+                }
+            }
+            last_error
+        });
+
+        let status = tokio::select! {
+            status = child.wait() => Some(status?),
+            _ = &mut stop_rx => {
+                let _ = child.kill().await;
+                None
+            }
+        };
+        let Some(status) = status else {
+            // Its output no longer matters, and processes it started may keep
+            // the pipes open
+            stdout_task.abort();
+            stderr_task.abort();
+            return Ok(false);
+        };
+        let _ = stdout_task.await;
+        let last_error = stderr_task.await.unwrap_or_default();
+
+        match status {
+            status if status.success() => Ok(true),
+            status if last_error.is_empty() => Err(anyhow::anyhow!(
+                "yt-dlp failed with exit code: {:?}",
+                status.code()
+            )),
+            _ => Err(anyhow::anyhow!(
+                "{}",
+                last_error.trim_start_matches("ERROR: ")
+            )),
+        }
+    }
+
+    // Helper: Take in one line of yt-dlp's stdout
+    fn handle_line(&self, id: u64, line: &str, log_tx: Option<&mpsc::UnboundedSender<String>>) {
+        let line = line.trim();
+        if let Some(progress) = parse_progress(line) {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                job.progress = Some(progress);
+            }
+        } else if let Some(path) = line.strip_prefix(FILE_PREFIX) {
+            let path = PathBuf::from(path.trim());
+            let mut inner = self.inner.lock().unwrap();
+            let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) else {
+                return;
+            };
+            job.file = Some(path.clone());
+            // A video of a playlist or channel is done
+            let entry = job.current_item().map(|item| {
+                item.state = ItemState::Finished;
+                item.file = Some(path.clone());
+                CompletedDownload {
+                    video_id: item.video_id.clone(),
+                    title: item.title.clone(),
+                    path,
+                    finished_at: chrono::Utc::now().timestamp(),
+                }
+            });
+            let save = entry.and_then(|entry| inner.record(entry));
+            drop(inner);
+            if let Some(save) = save {
+                tokio::spawn(self.clone().save_completed(save));
+            }
+        } else if let Some((video_id, count, title)) = parse_item(line) {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                job.progress = None;
+                job.item_count = count.or(job.item_count);
+                job.items.push(DownloadItem {
+                    video_id,
+                    title,
+                    state: ItemState::Running,
+                    file: None,
+                });
+            }
+        } else if let Some((video_id, title)) = parse_archived(line) {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                job.items.push(DownloadItem {
+                    video_id,
+                    title,
+                    state: ItemState::Archived,
+                    file: None,
+                });
+            }
+        } else if !line.is_empty() {
+            if let Some(tx) = log_tx {
+                let _ = tx.send(line.to_string());
+            }
+        }
+    }
+
+    // Helper: Mark the item an error line from yt-dlp is about as failed
+    fn handle_error(&self, id: u64, line: &str) {
+        let Some((video_id, error)) = parse_item_error(line) else {
+            return;
+        };
+        let mut inner = self.inner.lock().unwrap();
+        let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) else {
+            return;
+        };
+        if !job.target.is_collection() {
+            return;
+        }
+        let state = ItemState::Failed(error.to_string());
+        match job.items.iter_mut().find(|item| item.video_id == video_id) {
+            Some(item) => item.state = state,
+            // Failed before its download started
+            None => job.items.push(DownloadItem {
+                video_id: video_id.to_string(),
+                title: video_id.to_string(),
+                state,
+                file: None,
+            }),
+        }
+    }
+
+    // Helper: Write a snapshot of the completed downloads to downloads.json,
+    // off the async threads. Snapshots older than the file are dropped.
+    async fn save_completed(self, save: PendingSave) {
+        let saved_version = self.saved_version.clone();
+        let log_tx = self.inner.lock().unwrap().log_tx.clone();
+        let result = tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut saved = saved_version.lock().unwrap();
+            if *saved >= save.version {
+                return Ok(());
+            }
+            if let Some(parent) = save.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // Write a temporary file first so a crash never leaves half a record behind
+            let temp_path = save.path.with_extension("json.tmp");
+            fs::write(&temp_path, serde_json::to_vec(&save.completed)?)?;
+            fs::rename(&temp_path, &save.path)?;
+            *saved = save.version;
+            Ok(())
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+        if let (Err(e), Some(tx)) = (result, log_tx) {
+            let _ = tx.send(format!("Failed to record the download: {}", e));
+        }
+    }
+}
+
+// The completed downloads as they were after a change, to be written to disk
+struct PendingSave {
+    version: u64,
+    path: PathBuf,
+    completed: Vec<CompletedDownload>,
+}
+
+impl Inner {
+    // Helper: Remember a finished video's file (playlist and channel videos
+    // are recorded as they finish). Single videos stay out of the archive, so
+    // a one-off download doesn't hold the video back from a later playlist.
+    fn record_finished(&mut self, id: u64) -> Option<PendingSave> {
+        let job = self.jobs.iter().find(|job| job.id == id)?;
+        let (DownloadTarget::Video(video), JobState::Finished, Some(path)) =
+            (&job.target, &job.state, &job.file)
+        else {
+            return None;
+        };
+        let entry = CompletedDownload {
+            video_id: video.id.clone(),
+            title: video.title.clone(),
+            path: path.clone(),
+            finished_at: chrono::Utc::now().timestamp(),
+        };
+        self.record(entry)
+    }
+
+    // Helper: Add a download to the completed list; returns what to write to
+    // downloads.json once the lock is released
+    fn record(&mut self, entry: CompletedDownload) -> Option<PendingSave> {
+        self.completed.insert(0, entry);
+        self.completed.truncate(MAX_COMPLETED);
+        self.completed_version += 1;
+
+        Some(PendingSave {
+            version: self.completed_version,
+            path: self.record_path.clone()?,
+            completed: self.completed.clone(),
+        })
+    }
+}
+
+// Helper: yt-dlp arguments for a download; playlists and channels go through
+// the archive and carry on past videos that fail
+fn download_args(
+    target: &DownloadTarget,
+    options: &DownloadOptions,
+    archive: Option<&Path>,
+) -> Vec<String> {
+    let format = options
+        .format
+        .clone()
+        .unwrap_or_else(|| match options.audio {
+            Some(_) => AUDIO_FORMAT_SELECTOR.to_string(),
+            None => options.quality.format_selector(),
+        });
+    let mut args = vec!["--format".to_string(), format];
+    if options.audio.is_none() {
+        args.extend(subtitle_args(&options.subtitles));
+    }
+    args.extend(sponsorblock_args(&options.sponsorblock));
+    match &options.audio {
+        Some(audio) => {
+            args.extend([
+                "--extract-audio".to_string(),
+                "--audio-format".to_string(),
+                audio.codec.clone(),
+                "--audio-quality".to_string(),
+                // 0 is yt-dlp's best variable bitrate
+                audio
+                    .bitrate
+                    .map_or_else(|| "0".to_string(), |kbps| format!("{}K", kbps)),
+                // Title and date; the channel as the artist
+                "--embed-metadata".to_string(),
+                "--parse-metadata".to_string(),
+                "%(channel)s:%(meta_artist)s".to_string(),
+                // Cover art; m4a can't hold YouTube's webp thumbnails
+                "--embed-thumbnail".to_string(),
+                "--convert-thumbnails".to_string(),
+                "jpg".to_string(),
+            ]);
+        }
+        None => {
+            if let Some(container) = &options.quality.container {
+                args.push("--merge-output-format".to_string());
+                args.push(container.clone());
+            }
+        }
+    }
+    if let Some(directory) = &options.directory {
+        args.push("--paths".to_string());
+        args.push(directory.to_string_lossy().to_string());
+    }
+    if target.is_collection() {
+        if let Some(archive) = archive {
+            args.push("--download-archive".to_string());
+            args.push(archive_for(archive, options).to_string_lossy().to_string());
+        }
+        args.extend([
+            "--yes-playlist".to_string(),
+            "--ignore-errors".to_string(),
+            "--print".to_string(),
+            format!(
+                "before_dl:{}%(id)s %(playlist_count)s %(title)s",
+                ITEM_PREFIX
+            ),
+            // --print implies --quiet, which hides the "already recorded in the
+            // archive" lines archived items are read from
+            "--no-quiet".to_string(),
+        ]);
+    }
+    args.extend([
+        "--newline".to_string(),
+        "--progress".to_string(),
+        "--progress-template".to_string(),
+        format!(
+            "download:{}%(progress.downloaded_bytes)s %(progress.total_bytes)s \
+             %(progress.total_bytes_estimate)s %(progress.speed)s %(progress.eta)s",
+            PROGRESS_PREFIX
+        ),
+        "--print".to_string(),
+        format!("after_move:{}%(filepath)s", FILE_PREFIX),
+        "--no-simulate".to_string(),
+        // Fields yt-dlp fills in get the same safe names as ours
+        "--windows-filenames".to_string(),
+        "--output".to_string(),
+        options
+            .output
+            .clone()
+            .unwrap_or_else(|| "%(title)s.%(ext)s".to_string()),
+        target.url(),
+    ]);
+    args
+}
+
+// Helper: The archive for this kind of download. Audio extractions keep their
+// own (archive-audio.txt next to archive.txt), so saving a video as mp3
+// doesn't stop it from being downloaded as a video, or the other way round.
+fn archive_for(archive: &Path, options: &DownloadOptions) -> PathBuf {
+    if options.audio.is_none() {
+        return archive.to_path_buf();
+    }
+    let stem = archive
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "archive".to_string());
+    let name = match archive.extension() {
+        Some(ext) => format!("{}-audio.{}", stem, ext.to_string_lossy()),
+        None => format!("{}-audio", stem),
+    };
+    archive.with_file_name(name)
+}
+
+// Helper: yt-dlp arguments for saving subtitles, following the download mode
+fn subtitle_args(subtitles: &SubtitlePrefs) -> Vec<String> {
+    if subtitles.download == SubtitleMode::Off || subtitles.languages.is_empty() {
+        return Vec::new();
+    }
+    let mut args = vec![
+        "--write-subs".to_string(),
+        "--sub-langs".to_string(),
+        subtitles.languages.join(","),
+    ];
+    if subtitles.auto_captions {
+        args.push("--write-auto-subs".to_string());
+    }
+    if subtitles.download == SubtitleMode::Embed {
+        // The separate files are deleted once they are in the video
+        args.push("--embed-subs".to_string());
+    }
+    args
+}
+
+// Helper: yt-dlp arguments for cutting out skipped SponsorBlock segments and
+// adding marked ones as chapters
+fn sponsorblock_args(prefs: &SponsorBlockPrefs) -> Vec<String> {
+    if !prefs.enabled || !prefs.apply_to_downloads {
+        return Vec::new();
+    }
+    let mut args = Vec::new();
+    let remove = prefs.categories_with(SegmentAction::Skip);
+    if !remove.is_empty() {
+        args.extend(["--sponsorblock-remove".to_string(), remove.join(",")]);
+    }
+    let mark = prefs.categories_with(SegmentAction::Mark);
+    if !mark.is_empty() {
+        args.extend(["--sponsorblock-mark".to_string(), mark.join(",")]);
+    }
+    if !args.is_empty() {
+        args.extend(["--sponsorblock-api".to_string(), prefs.api_url.clone()]);
+    }
+    args
+}
+
+// Helper: A playlist item starting: its ID, the number of items and its title
+fn parse_item(line: &str) -> Option<(String, Option<usize>, String)> {
+    let rest = line.strip_prefix(ITEM_PREFIX)?;
+    let mut parts = rest.splitn(3, ' ');
+    let video_id = parts.next().filter(|id| !id.is_empty())?;
+    let count = parts.next().and_then(|count| count.parse().ok());
+    let title = parts.next().unwrap_or(video_id);
+    Some((video_id.to_string(), count, title.to_string()))
+}
+
+// Helper: An item skipped because of the archive, from yt-dlp's message:
+// [download] <id>: "<title>" has already been recorded in the archive
+fn parse_archived(line: &str) -> Option<(String, String)> {
+    let rest = line
+        .strip_prefix("[download] ")?
+        .strip_suffix(ARCHIVED_SUFFIX)?;
+    let (video_id, title) = match rest.split_once(": ") {
+        Some((video_id, title)) => (video_id, title.trim_matches('"')),
+        None => (rest, rest),
+    };
+    Some((video_id.to_string(), title.to_string()))
+}
+
+// Helper: The video and message of an error such as
+// ERROR: [youtube] <id>: Video unavailable
+fn parse_item_error(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("ERROR: ")?;
+    let rest = match rest.strip_prefix('[') {
+        Some(rest) => rest.split_once("] ")?.1,
+        None => rest,
+    };
+    let (video_id, error) = rest.split_once(": ")?;
+    if video_id.is_empty() || video_id.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((video_id, error))
+}
+
+// Helper: A file with one of `extensions` under `dir` (down to `depth` folder
+// levels) with the video's ID in its name
+fn find_video_file(
+    dir: &Path,
+    video_id: &str,
+    extensions: &[&str],
+    depth: usize,
+) -> Option<PathBuf> {
+    if video_id.is_empty() || depth == 0 {
+        return None;
+    }
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            subdirs.push(path);
+            continue;
+        }
+        let has_id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.contains(video_id));
+        if has_id && has_extension(&path, extensions) {
+            return Some(path);
+        }
+    }
+    subdirs
+        .into_iter()
+        .find_map(|subdir| find_video_file(&subdir, video_id, extensions, depth - 1))
+}
+
+// Helper: Whether the file's extension is one of `extensions`
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+// Values for a file name template's placeholders; the ones left out are filled
+// in by yt-dlp
+#[derive(Debug, Clone, Default)]
+pub struct TemplateValues {
+    pub title: Option<String>,
+    pub id: Option<String>,
+    pub channel: Option<String>,
+    pub playlist: Option<String>,
+    // Position in the playlist, from 1
+    pub index: Option<usize>,
+    // YYYY-MM-DD
+    pub upload_date: Option<String>,
+}
+
+impl TemplateValues {
+    // What the app already knows about a video
+    pub fn for_video(video: &Video) -> Self {
+        let known = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        let upload_date = video
+            .published_at
+            .get(..10)
+            .filter(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+            .map(str::to_string);
+        Self {
+            title: known(&video.title),
+            id: known(&video.id),
+            channel: known(&video.channel_title),
+            playlist: None,
+            index: None,
+            upload_date,
+        }
+    }
+}
+
+// Turn a file name template ("{channel}/{title} [{id}].{ext}") into a yt-dlp
+// output template. Known values are sanitized and put in as they are, the rest
+// become yt-dlp fields; unknown placeholders are kept as text.
+pub fn output_template(template: &str, values: &TemplateValues) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&escape_template(&rest[..start]));
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[start..start + len + 1];
+        match template_value(&placeholder[1..len], values) {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(&escape_template(placeholder)),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    output.push_str(&escape_template(rest));
+    output
+}
+
+// Helper: What a placeholder becomes in the yt-dlp output template
+fn template_value(name: &str, values: &TemplateValues) -> Option<String> {
+    let (value, field) = match name {
+        "title" => (values.title.clone(), "%(title)s"),
+        "id" => (values.id.clone(), "%(id)s"),
+        "channel" => (values.channel.clone(), "%(channel)s"),
+        "playlist" => (values.playlist.clone(), "%(playlist_title)s"),
+        "index" => (
+            values.index.map(|index| format!("{:03}", index)),
+            "%(playlist_index)03d",
+        ),
+        "upload_date" => (values.upload_date.clone(), "%(upload_date>%Y-%m-%d)s"),
+        "ext" => (None, "%(ext)s"),
+        _ => return None,
+    };
+    Some(match value {
+        Some(value) => escape_template(&sanitize_filename(&value)),
+        None => field.to_string(),
+    })
+}
+
+// Helper: yt-dlp reads "%" as the start of a field
+fn escape_template(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+// Make a value safe as part of a file name on every system: no path separators,
+// characters Windows forbids or control characters, no leading dots, no
+// trailing dots or spaces, no reserved device names and not too long
+pub fn sanitize_filename(name: &str) -> String {
+    let mut clean: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    if clean.len() > MAX_NAME_BYTES {
+        let mut end = MAX_NAME_BYTES;
+        while !clean.is_char_boundary(end) {
+            end -= 1;
+        }
+        clean.truncate(end);
+    }
+    let clean = clean
+        .trim_start_matches(|c: char| c == '.' || c.is_whitespace())
+        .trim_end_matches(|c: char| c == '.' || c.is_whitespace());
+    if clean.is_empty() {
+        return "_".to_string();
+    }
+    // CON, NUL, COM1 and friends can't be file names on Windows, even with an extension
+    let stem = clean
+        .split('.')
+        .next()
+        .unwrap_or(clean)
+        .to_ascii_uppercase();
+    let reserved = matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || (stem.len() == 4
+            && (stem.starts_with("COM") || stem.starts_with("LPT"))
+            && stem.as_bytes()[3].is_ascii_digit());
+    if reserved {
+        format!("{}_", clean)
+    } else {
+        clean.to_string()
+    }
+}
+
+// Parse a progress line printed through our `--progress-template`: downloaded
+// bytes, total, estimated total, speed and ETA, with "NA" for unknown values
+pub fn parse_progress(line: &str) -> Option<DownloadProgress> {
+    let fields: Vec<&str> = line
+        .trim()
+        .strip_prefix(PROGRESS_PREFIX)?
+        .split_whitespace()
+        .collect();
+    let [downloaded, total, estimate, speed, eta] = fields[..] else {
+        return None;
+    };
+    let number = |field: &str| field.parse::<f64>().ok().filter(|n| n.is_finite());
+    Some(DownloadProgress {
+        downloaded: number(downloaded)? as u64,
+        total: number(total).or_else(|| number(estimate)).map(|n| n as u64),
+        speed: number(speed),
+        eta: number(eta).map(|n| n as u64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{AudioPrefs, QualityPrefs};
+
+    #[test]
+    fn progress_lines_are_parsed() {
+        let progress =
+            parse_progress("[rustyoutube-progress]1048576 4194304 NA 524288.5 6").unwrap();
+        assert_eq!(progress.downloaded, 1048576);
+        assert_eq!(progress.total, Some(4194304));
+        assert_eq!(progress.speed, Some(524288.5));
+        assert_eq!(progress.eta, Some(6));
+        assert_eq!(progress.percent(), Some(25.0));
+
+        // Only an estimate of the size, speed and ETA not known yet
+        let progress = parse_progress("[rustyoutube-progress]100 NA 400.0 NA NA").unwrap();
+        assert_eq!(progress.total, Some(400));
+        assert_eq!(progress.speed, None);
+        assert_eq!(progress.eta, None);
+
+        assert!(parse_progress("[download]  25.0% of 4.00MiB").is_none());
+        assert!(parse_progress("[rustyoutube-progress]NA NA NA NA NA").is_none());
+    }
+
+    #[test]
+    fn download_args_follow_the_options() {
+        let options = DownloadOptions {
+            format: Some("137+bestaudio".to_string()),
+            ..Default::default()
+        };
+        let args = download_args(&video_target("abc"), &options, None);
+        assert_eq!(args[..2], ["--format", "137+bestaudio"]);
+        assert!(!args.contains(&"--merge-output-format".to_string()));
+        assert_eq!(args.last().unwrap(), "https://www.youtube.com/watch?v=abc");
+        assert!(!args.contains(&"--paths".to_string()));
+
+        let options = DownloadOptions {
+            directory: Some(PathBuf::from("/videos")),
+            output: Some("%(title)s [%(id)s].%(ext)s".to_string()),
+            ..Default::default()
+        };
+        let args = download_args(&video_target("abc"), &options, None);
+        let after = |flag: &str| {
+            let at = args.iter().position(|arg| arg == flag).unwrap();
+            args[at + 1].clone()
+        };
+        assert_eq!(after("--paths"), "/videos");
+        assert_eq!(after("--output"), "%(title)s [%(id)s].%(ext)s");
+    }
+
+    fn video_target(id: &str) -> DownloadTarget {
+        DownloadTarget::Video(Box::new(Video {
+            id: id.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn audio_downloads_extract_and_tag_the_audio() {
+        let options = DownloadOptions {
+            quality: QualityPrefs {
+                container: Some("mkv".to_string()),
+                ..Default::default()
+            },
+            audio: Some(AudioPrefs {
+                codec: "opus".to_string(),
+                bitrate: None,
+            }),
+            ..Default::default()
+        };
+        let args = download_args(&video_target("abc"), &options, None);
+        let after = |flag: &str| {
+            let at = args.iter().position(|arg| arg == flag).unwrap();
+            args[at + 1].clone()
+        };
+        assert_eq!(after("--format"), "bestaudio/best");
+        assert_eq!(after("--audio-format"), "opus");
+        assert_eq!(after("--audio-quality"), "0");
+        assert_eq!(after("--parse-metadata"), "%(channel)s:%(meta_artist)s");
+        assert!(args.contains(&"--embed-thumbnail".to_string()));
+        assert!(!args.contains(&"--merge-output-format".to_string()));
+
+        let options = DownloadOptions {
+            audio: Some(AudioPrefs::default()),
+            ..Default::default()
+        };
+        let args = download_args(&video_target("abc"), &options, None);
+        let at = args
+            .iter()
+            .position(|arg| arg == "--audio-quality")
+            .unwrap();
+        assert_eq!(args[at + 1], "192K");
+    }
+
+    #[test]
+    fn subtitles_follow_the_download_mode() {
+        let mut options = DownloadOptions::default();
+        let args = download_args(&video_target("abc"), &options, None);
+        assert!(!args.contains(&"--write-subs".to_string()));
+
+        options.subtitles = SubtitlePrefs {
+            languages: vec!["en".to_string(), "de".to_string()],
+            auto_captions: false,
+            download: SubtitleMode::Sidecar,
+        };
+        let args = download_args(&video_target("abc"), &options, None);
+        let at = args.iter().position(|arg| arg == "--sub-langs").unwrap();
+        assert_eq!(args[at + 1], "en,de");
+        assert!(args.contains(&"--write-subs".to_string()));
+        assert!(!args.contains(&"--write-auto-subs".to_string()));
+        assert!(!args.contains(&"--embed-subs".to_string()));
+
+        options.subtitles.auto_captions = true;
+        options.subtitles.download = SubtitleMode::Embed;
+        let args = download_args(&video_target("abc"), &options, None);
+        assert!(args.contains(&"--write-auto-subs".to_string()));
+        assert!(args.contains(&"--embed-subs".to_string()));
+
+        // Not for audio files
+        options.audio = Some(Default::default());
+        let args = download_args(&video_target("abc"), &options, None);
+        assert!(!args.contains(&"--write-subs".to_string()));
+    }
+
+    #[test]
+    fn sponsor_segments_are_cut_when_applied_to_downloads() {
+        let mut options = DownloadOptions::default();
+        let args = download_args(&video_target("abc"), &options, None);
+        assert!(!args.iter().any(|arg| arg.starts_with("--sponsorblock")));
+
+        options.sponsorblock.apply_to_downloads = true;
+        options.sponsorblock.api_url = "http://127.0.0.1:9999".to_string();
+        let args = download_args(&video_target("abc"), &options, None);
+        let value = |flag: &str| {
+            let at = args.iter().position(|arg| arg == flag).unwrap();
+            args[at + 1].clone()
+        };
+        assert_eq!(
+            value("--sponsorblock-remove"),
+            "intro,outro,selfpromo,sponsor"
+        );
+        assert_eq!(value("--sponsorblock-mark"), "interaction");
+        assert_eq!(value("--sponsorblock-api"), "http://127.0.0.1:9999");
+
+        options.sponsorblock.enabled = false;
+        let args = download_args(&video_target("abc"), &options, None);
+        assert!(!args.iter().any(|arg| arg.starts_with("--sponsorblock")));
+    }
+
+    #[test]
+    fn collections_use_the_archive() {
+        let archive = Path::new("/config/archive.txt");
+        let args = download_args(&video_target("abc"), &Default::default(), Some(archive));
+        assert!(!args.contains(&"--download-archive".to_string()));
+
+        let playlist = DownloadTarget::Playlist {
+            id: "PL1".to_string(),
+            title: "Talks".to_string(),
+        };
+        let args = download_args(&playlist, &Default::default(), Some(archive));
+        let at = args
+            .iter()
+            .position(|arg| arg == "--download-archive")
+            .unwrap();
+        assert_eq!(args[at + 1], "/config/archive.txt");
+
+        // Audio extractions are tracked apart from videos
+        let audio = DownloadOptions {
+            audio: Some(Default::default()),
+            ..Default::default()
+        };
+        let args = download_args(&playlist, &audio, Some(archive));
+        let at = args
+            .iter()
+            .position(|arg| arg == "--download-archive")
+            .unwrap();
+        assert_eq!(args[at + 1], "/config/archive-audio.txt");
+        assert!(args.contains(&"--ignore-errors".to_string()));
+        assert!(args.contains(&"--no-quiet".to_string()));
+        assert_eq!(
+            args.last().unwrap(),
+            "https://www.youtube.com/playlist?list=PL1"
+        );
+
+        let channel = |id: &str| DownloadTarget::Channel {
+            id: id.to_string(),
+            title: String::new(),
+        };
+        assert_eq!(
+            channel("UC1").url(),
+            "https://www.youtube.com/channel/UC1/videos"
+        );
+        assert_eq!(
+            channel("@rust").url(),
+            "https://www.youtube.com/@rust/videos"
+        );
+    }
+
+    #[test]
+    fn item_lines_are_parsed() {
+        assert_eq!(
+            parse_item("[rustyoutube-item]abc 12 A title with spaces"),
+            Some((
+                "abc".to_string(),
+                Some(12),
+                "A title with spaces".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_item("[rustyoutube-item]abc NA Title"),
+            Some(("abc".to_string(), None, "Title".to_string()))
+        );
+        assert_eq!(
+            parse_archived(
+                "[download] abc: \"Some title\" has already been recorded in the archive"
+            ),
+            Some(("abc".to_string(), "Some title".to_string()))
+        );
+        assert_eq!(
+            parse_item_error("ERROR: [youtube] abc: Private video. Sign in"),
+            Some(("abc", "Private video. Sign in"))
+        );
+        assert_eq!(parse_item_error("ERROR: Unable to download webpage"), None);
+        assert_eq!(parse_item_error("WARNING: [youtube] abc: slow"), None);
+    }
+
+    #[test]
+    fn file_names_are_sanitized() {
+        assert_eq!(sanitize_filename("AC/DC: Live?"), "AC_DC_ Live_");
+        assert_eq!(sanitize_filename("tab\there"), "tab_here");
+        assert_eq!(sanitize_filename("...hidden. "), "hidden");
+        assert_eq!(sanitize_filename(" . "), "_");
+        assert_eq!(sanitize_filename("con"), "con_");
+        assert_eq!(sanitize_filename("LPT1.txt"), "LPT1.txt_");
+        assert_eq!(sanitize_filename("Console"), "Console");
+
+        let long = sanitize_filename(&"é".repeat(200));
+        assert!(long.len() <= MAX_NAME_BYTES);
+        assert!(long.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn templates_use_known_values_and_fall_back_to_ytdlp_fields() {
+        let values = TemplateValues {
+            title: Some("100% Rust: part 1/2".to_string()),
+            id: Some("abc".to_string()),
+            playlist: Some("Talks".to_string()),
+            index: Some(7),
+            ..Default::default()
+        };
+        assert_eq!(
+            output_template("{playlist}/{index} - {title} [{id}].{ext}", &values),
+            "Talks/007 - 100%% Rust_ part 1_2 [abc].%(ext)s"
+        );
+        assert_eq!(
+            output_template("{channel}/{upload_date} {unknown} 50%.{ext}", &values),
+            "%(channel)s/%(upload_date>%Y-%m-%d)s {unknown} 50%%.%(ext)s"
+        );
+        assert_eq!(output_template("{title", &values), "{title");
+    }
+
+    #[test]
+    fn existing_files_are_found_by_video_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let channel = dir.path().join("Channel");
+        fs::create_dir_all(&channel).unwrap();
+        fs::write(channel.join("Title [abc].mkv.part"), "").unwrap();
+        fs::write(channel.join("Title [abc].en.vtt"), "").unwrap();
+        assert!(find_video_file(dir.path(), "abc", VIDEO_EXTENSIONS, SCAN_DEPTH).is_none());
+
+        fs::write(channel.join("Title [abc].mkv"), "").unwrap();
+        assert_eq!(
+            find_video_file(dir.path(), "abc", VIDEO_EXTENSIONS, SCAN_DEPTH),
+            Some(channel.join("Title [abc].mkv"))
+        );
+        assert!(find_video_file(dir.path(), "abd", VIDEO_EXTENSIONS, SCAN_DEPTH).is_none());
+        assert!(find_video_file(dir.path(), "abc", VIDEO_EXTENSIONS, 1).is_none());
+        // The video doesn't count as an audio download
+        assert!(find_video_file(dir.path(), "abc", AUDIO_EXTENSIONS, SCAN_DEPTH).is_none());
+    }
+}
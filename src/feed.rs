@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
+use reqwest::Client;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::youtube::{title_looks_like_short, Subscription, Video, VideoSource, VideoThumbnail};
+
+pub const DEFAULT_FEED_BASE_URL: &str = "https://www.youtube.com/feeds/videos.xml";
+
+// Channel feeds fetched at the same time
+const DEFAULT_CONCURRENCY: usize = 8;
+
+// Chronological feed of everything the user is subscribed to, built from each
+// channel's public RSS feed. Costs no Data API quota; the subscriptions are
+// listed, and the merged feed cached, by the caller.
+#[derive(Clone)]
+pub struct FeedSource {
+    client: Client,
+    base_url: String,
+    concurrency: usize,
+    // Last videos loaded per channel, standing in when a feed fails to load
+    last_loaded: Arc<Mutex<HashMap<String, Vec<Video>>>>,
+}
+
+impl FeedSource {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            base_url: DEFAULT_FEED_BASE_URL.to_string(),
+            concurrency: DEFAULT_CONCURRENCY,
+            last_loaded: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // Point the source at another feed URL (e.g. a local test server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    // Helper: One channel's recent uploads
+    async fn channel_feed(&self, channel_id: &str) -> Result<Vec<Video>> {
+        let response = self
+            .client
+            .get(&self.base_url)
+            .query(&[("channel_id", channel_id)])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to load feed for channel {} ({})",
+                channel_id,
+                response.status()
+            ));
+        }
+        let xml = response.text().await?;
+        let videos = parse_feed(&xml)
+            .with_context(|| format!("Failed to parse feed for channel {}", channel_id))?;
+
+        self.last_loaded
+            .lock()
+            .unwrap()
+            .insert(channel_id.to_string(), videos.clone());
+        Ok(videos)
+    }
+
+    // Helper: Stale videos for a channel whose feed couldn't be loaded
+    fn cached_videos(&self, channel_id: &str) -> Option<Vec<Video>> {
+        self.last_loaded.lock().unwrap().get(channel_id).cloned()
+    }
+}
+
+#[async_trait]
+impl VideoSource for FeedSource {
+    fn name(&self) -> &'static str {
+        "RSS feeds"
+    }
+
+    async fn get_subscription_feed(
+        &self,
+        subscriptions: &[Subscription],
+        _refresh: bool,
+    ) -> Result<Vec<Video>> {
+        let channel_ids: Vec<String> = subscriptions
+            .iter()
+            .map(|sub| sub.channel_id.clone())
+            .collect();
+        let results: Vec<(String, Result<Vec<Video>>)> = stream::iter(channel_ids)
+            .map(|channel_id| async move {
+                let videos = self.channel_feed(&channel_id).await;
+                (channel_id, videos)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+
+        // One broken feed shouldn't hide the rest
+        let mut videos = Vec::new();
+        let mut last_error = None;
+        let mut loaded_any = false;
+        for (channel_id, result) in results {
+            match result {
+                Ok(channel_videos) => {
+                    loaded_any = true;
+                    videos.extend(channel_videos);
+                }
+                Err(e) => {
+                    if let Some(cached) = self.cached_videos(&channel_id) {
+                        loaded_any = true;
+                        videos.extend(cached);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        if !loaded_any {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
+
+        Ok(merge_newest_first(videos))
+    }
+}
+
+// Helper: Drop duplicates and sort newest first
+pub fn merge_newest_first(videos: Vec<Video>) -> Vec<Video> {
+    let mut seen = HashSet::new();
+    let mut videos: Vec<Video> = videos
+        .into_iter()
+        .filter(|video| seen.insert(video.id.clone()))
+        .collect();
+
+    // Feeds use offsets like +00:00; compare the instants, not the strings
+    videos.sort_by_cached_key(|video| {
+        std::cmp::Reverse(
+            chrono::DateTime::parse_from_rfc3339(&video.published_at)
+                .map(|date| date.timestamp())
+                .unwrap_or(i64::MIN),
+        )
+    });
+    videos
+}
+
+// Helper: Parse a channel's Atom feed (feeds/videos.xml)
+pub fn parse_feed(xml: &str) -> Result<Vec<Video>> {
+    let document = roxmltree::Document::parse(xml)?;
+
+    let mut videos = Vec::new();
+    for entry in document
+        .root_element()
+        .children()
+        .filter(|n| n.has_tag_name("entry"))
+    {
+        let id = child_text(entry, "videoId");
+        if id.is_empty() {
+            continue;
+        }
+
+        let title = child_text(entry, "title");
+        let link = child(entry, "link")
+            .and_then(|n| n.attribute("href"))
+            .unwrap_or_default();
+        let group = child(entry, "group");
+        let thumbnails: Vec<VideoThumbnail> = group
+            .and_then(|g| child(g, "thumbnail"))
+            .and_then(|t| {
+                Some(VideoThumbnail {
+                    url: t.attribute("url")?.to_string(),
+                    width: t.attribute("width").and_then(|w| w.parse().ok()),
+                    height: t.attribute("height").and_then(|h| h.parse().ok()),
+                })
+            })
+            .into_iter()
+            .collect();
+        let view_count = group
+            .and_then(|g| child(g, "community"))
+            .and_then(|c| child(c, "statistics"))
+            .and_then(|s| s.attribute("views"))
+            .and_then(|views| views.parse().ok());
+
+        videos.push(Video {
+            channel_id: child_text(entry, "channelId"),
+            channel_title: child(entry, "author")
+                .map(|author| child_text(author, "name"))
+                .unwrap_or_default(),
+            published_at: child_text(entry, "published"),
+            thumbnail_url: thumbnails
+                .first()
+                .map(|t| t.url.clone())
+                .unwrap_or_else(|| format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id)),
+            description: group
+                .map(|g| child_text(g, "description"))
+                .unwrap_or_default(),
+            view_count,
+            is_short: link.contains("/shorts/") || title_looks_like_short(&title),
+            thumbnails,
+            title,
+            id,
+            ..Default::default()
+        });
+    }
+
+    Ok(videos)
+}
+
+// Helper: First child element with this local name (namespaces are ignored)
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn child_text(node: roxmltree::Node<'_, '_>, name: &str) -> String {
+    child(node, name)
+        .and_then(|n| n.text())
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
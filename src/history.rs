@@ -0,0 +1,436 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::youtube::Video;
+
+// Entries kept; the oldest are dropped beyond this. The whole file is written
+// on each change, so it has to stay small.
+const MAX_ENTRIES: usize = 2_000;
+
+// Share of a video that has to be played for it to count as watched
+pub const WATCHED_THRESHOLD: f64 = 0.9;
+// Positions this close to the start aren't worth resuming from (seconds)
+const MIN_RESUME_POSITION: u64 = 10;
+
+// Held for every read-modify-write of history.json. Playback, downloads and the
+// UI each update the file (through their own `WatchHistory`), and an update
+// running between another one's load and save would be lost.
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+// One watched video
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryEntry {
+    pub video_id: String,
+    pub title: String,
+    pub channel_id: String,
+    pub channel_title: String,
+    // Seconds since the Unix epoch
+    pub watched_at: i64,
+    pub duration: Option<u64>,
+    // Where playback stopped, in seconds
+    pub position: Option<u64>,
+    // Played to the end (or past `WATCHED_THRESHOLD`)
+    pub watched: bool,
+}
+
+impl HistoryEntry {
+    pub fn from_video(video: &Video, watched_at: i64) -> Self {
+        Self {
+            video_id: video.id.clone(),
+            title: video.title.clone(),
+            channel_id: video.channel_id.clone(),
+            channel_title: video.channel_title.clone(),
+            watched_at,
+            duration: video.duration,
+            position: None,
+            watched: false,
+        }
+    }
+
+    // For the video lists; only what the entry knows is filled in
+    pub fn to_video(&self) -> Video {
+        Video {
+            id: self.video_id.clone(),
+            title: self.title.clone(),
+            channel_id: self.channel_id.clone(),
+            channel_title: self.channel_title.clone(),
+            duration: self.duration,
+            thumbnail_url: format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", self.video_id),
+            ..Default::default()
+        }
+    }
+
+    pub fn watched_at_local(&self) -> DateTime<Local> {
+        Local
+            .timestamp_opt(self.watched_at, 0)
+            .single()
+            .unwrap_or_else(Local::now)
+    }
+
+    // Local calendar day the video was watched on
+    pub fn watched_on(&self) -> NaiveDate {
+        self.watched_at_local().date_naive()
+    }
+
+    // How much of the video has been played, from 0.0 to 1.0
+    pub fn progress(&self) -> Option<f64> {
+        if self.watched {
+            return Some(1.0);
+        }
+        let duration = self.duration.filter(|d| *d > 0)?;
+        Some((self.position? as f64 / duration as f64).min(1.0))
+    }
+
+    // Where to pick up playback, for videos that were stopped part way through
+    pub fn resume_position(&self) -> Option<u64> {
+        if self.watched {
+            return None;
+        }
+        self.position.filter(|p| *p >= MIN_RESUME_POSITION)
+    }
+
+    // Entries migrated from history.txt only have the video ID
+    pub fn needs_details(&self) -> bool {
+        self.title.is_empty()
+    }
+}
+
+// Watch history, newest first, stored as history.json in the config directory
+pub struct WatchHistory {
+    path: PathBuf,
+    // history.txt from older versions (one video ID per line)
+    legacy_path: PathBuf,
+}
+
+impl WatchHistory {
+    pub fn new(path: PathBuf, legacy_path: PathBuf) -> Self {
+        Self { path, legacy_path }
+    }
+
+    pub fn open_default() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .or_else(|| dirs::home_dir().map(|d| d.join(".config")))
+            .context("Failed to find config directory")?
+            .join("rustyoutube");
+        Ok(Self::new(dir.join("history.json"), dir.join("history.txt")))
+    }
+
+    pub fn load(&self) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return self.migrate_legacy();
+        }
+        let content = fs::read_to_string(&self.path).context("Failed to read watch history")?;
+        serde_json::from_str(&content).context("Failed to parse watch history")
+    }
+
+    // Helper: Load, change and save the entries as one step. The file is only
+    // written when the change did something.
+    fn update<T>(&self, change: impl FnOnce(&mut Vec<HistoryEntry>) -> T) -> Result<T> {
+        let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let before = self.load()?;
+        let mut entries = before.clone();
+        let result = change(&mut entries);
+        if entries != before {
+            self.save(&entries)?;
+        }
+        Ok(result)
+    }
+
+    fn save(&self, entries: &[HistoryEntry]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string(entries)?)
+            .context("Failed to write watch history")?;
+        fs::rename(&temp_path, &self.path).context("Failed to write watch history")
+    }
+
+    // Helper: Turn history.txt into entries. It has no times, so the entries get
+    // the file's modification time, a second apart to keep their order.
+    fn migrate_legacy(&self) -> Result<Vec<HistoryEntry>> {
+        let Ok(content) = fs::read_to_string(&self.legacy_path) else {
+            return Ok(Vec::new());
+        };
+        let modified = fs::metadata(&self.legacy_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_else(|| chrono::Utc::now().timestamp());
+
+        let entries: Vec<HistoryEntry> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(i, video_id)| HistoryEntry {
+                video_id: video_id.to_string(),
+                watched_at: modified - i as i64,
+                ..Default::default()
+            })
+            .collect();
+
+        self.save(&entries)?;
+        // Keep the old file around, but out of the way
+        fs::rename(
+            &self.legacy_path,
+            self.legacy_path.with_extension("txt.bak"),
+        )
+        .ok();
+        Ok(entries)
+    }
+
+    // Put the video at the top, keeping where playback stopped last time
+    pub fn record(&self, video: &Video) -> Result<()> {
+        self.update(|entries| {
+            let previous = entries
+                .iter()
+                .position(|e| e.video_id == video.id)
+                .map(|i| entries.remove(i));
+
+            let mut entry = HistoryEntry::from_video(video, chrono::Utc::now().timestamp());
+            if let Some(previous) = previous {
+                entry.position = previous.position;
+                entry.watched = previous.watched;
+                if entry.title.is_empty() {
+                    entry.title = previous.title;
+                    entry.channel_id = previous.channel_id;
+                    entry.channel_title = previous.channel_title;
+                }
+                entry.duration = entry.duration.or(previous.duration);
+            }
+            entries.insert(0, entry);
+            entries.truncate(MAX_ENTRIES);
+        })
+    }
+
+    // Remember where playback of a video stopped; past `WATCHED_THRESHOLD` it
+    // counts as watched. Without a duration the watched flag is left as it was.
+    pub fn set_position(&self, video_id: &str, position: u64) -> Result<()> {
+        self.update(|entries| {
+            if let Some(entry) = entries.iter_mut().find(|e| e.video_id == video_id) {
+                entry.position = Some(position);
+                if let Some(duration) = entry.duration.filter(|d| *d > 0) {
+                    entry.watched = position as f64 >= duration as f64 * WATCHED_THRESHOLD;
+                }
+            }
+        })
+    }
+
+    // The video was played to the end
+    pub fn mark_watched(&self, video_id: &str) -> Result<()> {
+        self.update(|entries| {
+            if let Some(entry) = entries.iter_mut().find(|e| e.video_id == video_id) {
+                entry.watched = true;
+                entry.position = entry.duration;
+            }
+        })
+    }
+
+    // Fill in title, channel and duration for entries that lack them
+    pub fn update_details(&self, videos: &[Video]) -> Result<()> {
+        self.update(|entries| {
+            for entry in entries.iter_mut() {
+                if let Some(video) = videos.iter().find(|v| v.id == entry.video_id) {
+                    entry.title = video.title.clone();
+                    entry.channel_id = video.channel_id.clone();
+                    entry.channel_title = video.channel_title.clone();
+                    entry.duration = entry.duration.or(video.duration);
+                }
+            }
+        })
+    }
+
+    pub fn remove(&self, video_id: &str) -> Result<()> {
+        self.update(|entries| entries.retain(|e| e.video_id != video_id))
+    }
+
+    // Remove everything watched between the two days (inclusive, local time);
+    // returns how many entries were removed
+    pub fn remove_between(&self, from: NaiveDate, to: NaiveDate) -> Result<usize> {
+        self.update(|entries| {
+            let before = entries.len();
+            entries.retain(|e| {
+                let day = e.watched_on();
+                day < from || day > to
+            });
+            before - entries.len()
+        })
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.update(|entries| entries.clear())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(dir: &tempfile::TempDir) -> WatchHistory {
+        WatchHistory::new(
+            dir.path().join("history.json"),
+            dir.path().join("history.txt"),
+        )
+    }
+
+    fn video(id: &str) -> Video {
+        Video {
+            id: id.to_string(),
+            title: format!("Video {}", id),
+            channel_title: "Channel".to_string(),
+            duration: Some(600),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn legacy_history_is_migrated_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("history.txt"), "newest\nmiddle\n\noldest\n").unwrap();
+
+        let entries = history(&dir).load().unwrap();
+        let ids: Vec<&str> = entries.iter().map(|e| e.video_id.as_str()).collect();
+        assert_eq!(ids, ["newest", "middle", "oldest"]);
+        assert!(entries[0].watched_at > entries[2].watched_at);
+        assert!(entries.iter().all(HistoryEntry::needs_details));
+
+        assert!(dir.path().join("history.json").exists());
+        assert!(!dir.path().join("history.txt").exists());
+        assert!(dir.path().join("history.txt.bak").exists());
+    }
+
+    #[test]
+    fn rewatching_moves_entry_up_and_keeps_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history(&dir);
+        history.record(&video("a")).unwrap();
+        history.set_position("a", 125).unwrap();
+        history.record(&video("b")).unwrap();
+        history.record(&video("a")).unwrap();
+
+        let entries = history.load().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].video_id, "a");
+        assert_eq!(entries[0].position, Some(125));
+        assert_eq!(entries[0].duration, Some(600));
+    }
+
+    #[test]
+    fn positions_past_the_threshold_count_as_watched() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history(&dir);
+        history.record(&video("a")).unwrap();
+
+        history.set_position("a", 5).unwrap();
+        let entry = history.load().unwrap().remove(0);
+        assert_eq!(entry.resume_position(), None); // too close to the start
+        assert!(!entry.watched);
+
+        history.set_position("a", 300).unwrap();
+        let entry = history.load().unwrap().remove(0);
+        assert_eq!(entry.resume_position(), Some(300));
+        assert_eq!(entry.progress(), Some(0.5));
+
+        history.set_position("a", 560).unwrap(); // 93% of 600
+        let entry = history.load().unwrap().remove(0);
+        assert!(entry.watched);
+        assert_eq!(entry.resume_position(), None);
+        assert_eq!(entry.progress(), Some(1.0));
+    }
+
+    #[test]
+    fn unknown_duration_keeps_the_watched_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history(&dir);
+        history
+            .record(&Video {
+                duration: None,
+                ..video("live")
+            })
+            .unwrap();
+        history.mark_watched("live").unwrap();
+
+        history.set_position("live", 42).unwrap();
+        let entry = history.load().unwrap().remove(0);
+        assert!(entry.watched);
+        assert_eq!(entry.position, Some(42));
+    }
+
+    #[test]
+    fn unchanged_history_is_not_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history(&dir);
+        history.record(&video("a")).unwrap();
+        let path = dir.path().join("history.json");
+        fs::write(&path, fs::read_to_string(&path).unwrap() + " ").unwrap();
+
+        // Nothing to change for a video that isn't in the history
+        history.set_position("missing", 30).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().ends_with(' '));
+
+        history.set_position("a", 30).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.ends_with(' '));
+        assert!(!content.contains('\n'));
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let threads: Vec<_> = (0..8)
+            .map(|n| {
+                // Each caller has its own handle on the same file
+                let history = history(&dir);
+                std::thread::spawn(move || {
+                    for i in 0..5 {
+                        history.record(&video(&format!("{}-{}", n, i))).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(history(&dir).load().unwrap().len(), 40);
+    }
+
+    #[test]
+    fn entries_are_removed_by_item_and_by_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history(&dir);
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 7, d).unwrap();
+        let at = |d: u32, hour: u32| {
+            day(d)
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+                .and_local_timezone(Local)
+                .unwrap()
+                .timestamp()
+        };
+        let entries = vec![
+            HistoryEntry::from_video(&video("d3"), at(3, 9)),
+            HistoryEntry::from_video(&video("d2-late"), at(2, 23)),
+            HistoryEntry::from_video(&video("d2-early"), at(2, 1)),
+            HistoryEntry::from_video(&video("d1"), at(1, 12)),
+        ];
+        history.save(&entries).unwrap();
+
+        assert_eq!(history.remove_between(day(2), day(2)).unwrap(), 2);
+        history.remove("d1").unwrap();
+
+        let ids: Vec<String> = history
+            .load()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.video_id)
+            .collect();
+        assert_eq!(ids, ["d3"]);
+    }
+}
@@ -3,7 +3,7 @@ use serde_yaml::Value;
 use std::collections::HashMap;
 use std::fs;
 
-static TRANSLATIONS: Lazy<HashMap<String, String>> = Lazy::new(|| load_translations());
+static TRANSLATIONS: Lazy<HashMap<String, String>> = Lazy::new(load_translations);
 
 fn load_translations() -> HashMap<String, String> {
     let mut translations = HashMap::new();
@@ -16,7 +16,7 @@ fn load_translations() -> HashMap<String, String> {
     if let Ok(content) = fs::read_to_string(&locale_file) {
         if let Ok(yaml) = serde_yaml::from_str::<Value>(&content) {
             if let Some(lang_map) = yaml.as_mapping() {
-                if let Some(translations_map) = lang_map.get(&Value::String(lang.clone())) {
+                if let Some(translations_map) = lang_map.get(Value::String(lang.clone())) {
                     if let Some(trans_map) = translations_map.as_mapping() {
                         for (key, value) in trans_map {
                             if let (Some(k), Some(v)) = (key.as_str(), value.as_str()) {
@@ -34,7 +34,7 @@ fn load_translations() -> HashMap<String, String> {
         if let Ok(content) = fs::read_to_string("locales/en.yml") {
             if let Ok(yaml) = serde_yaml::from_str::<Value>(&content) {
                 if let Some(lang_map) = yaml.as_mapping() {
-                    if let Some(translations_map) = lang_map.get(&Value::String("en".to_string())) {
+                    if let Some(translations_map) = lang_map.get(Value::String("en".to_string())) {
                         if let Some(trans_map) = translations_map.as_mapping() {
                             for (key, value) in trans_map {
                                 if let (Some(k), Some(v)) = (key.as_str(), value.as_str()) {
@@ -0,0 +1,17 @@
+pub mod auth;
+pub mod cache;
+pub mod data_api;
+pub mod deps;
+pub mod downloads;
+pub mod feed;
+pub mod history;
+pub mod i18n;
+pub mod mock;
+pub mod player;
+pub mod queue;
+pub mod settings;
+pub mod sponsorblock;
+pub mod token_store;
+pub mod ui;
+pub mod youtube;
+pub mod ytdlp;
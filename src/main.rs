@@ -1,16 +1,163 @@
-mod auth;
-mod deps;
-mod i18n;
-mod player;
-mod ui;
-mod youtube;
-
 use anyhow::Result;
+use rustyoutube::{auth, cache, deps, mock, ui, youtube};
+use std::sync::Arc;
 
 // i18n is initialized lazily when first used
 
+const USAGE: &str = "Usage: rustyoutube [--account NAME] [--auth-flow device|browser] [--write-access] [--logout] [--anonymous] [--mock]
+
+Options:
+  --account NAME       Use a named Google account (each has its own saved token)
+  --auth-flow FLOW     How to sign in: \"device\" (enter a code on any device, the
+                       default) or \"browser\" (sign in with the browser on this machine)
+  --write-access       Allow subscribing, liking and editing playlists (asks Google for
+                       permission to manage your YouTube account)
+  --logout             Sign out: revoke the account's token and delete it
+  --anonymous          Browse without signing in (also used when GOOGLE_CLIENT_ID and
+                       GOOGLE_CLIENT_SECRET are not set). Set YOUTUBE_API_KEY to read
+                       public channel and playlist data from the YouTube Data API
+  --mock               Run the UI against built-in sample data (no network, no login)
+  -h, --help           Show this help";
+
+struct Args {
+    account: String,
+    auth_flow: auth::AuthFlow,
+    write_access: bool,
+    logout: bool,
+    anonymous: bool,
+    mock: bool,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        account: auth::DEFAULT_ACCOUNT.to_string(),
+        auth_flow: auth::AuthFlow::Device,
+        write_access: false,
+        logout: false,
+        anonymous: false,
+        mock: false,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--mock" => args.mock = true,
+            "--write-access" => args.write_access = true,
+            "--logout" => args.logout = true,
+            "--anonymous" => args.anonymous = true,
+            "--account" => {
+                args.account = iter
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--account needs a name\n\n{}", USAGE))?;
+            }
+            "--auth-flow" => {
+                let flow = iter
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--auth-flow needs a value\n\n{}", USAGE))?;
+                args.auth_flow = parse_auth_flow(&flow)?;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => {
+                if let Some(account) = arg.strip_prefix("--account=") {
+                    args.account = account.to_string();
+                } else if let Some(flow) = arg.strip_prefix("--auth-flow=") {
+                    args.auth_flow = parse_auth_flow(flow)?;
+                } else {
+                    return Err(anyhow::anyhow!("Unknown argument: {}\n\n{}", arg, USAGE));
+                }
+            }
+        }
+    }
+
+    auth::validate_account_name(&args.account)?;
+    Ok(args)
+}
+
+fn parse_auth_flow(value: &str) -> Result<auth::AuthFlow> {
+    match value {
+        "device" => Ok(auth::AuthFlow::Device),
+        "browser" => Ok(auth::AuthFlow::Loopback),
+        _ => Err(anyhow::anyhow!(
+            "Unknown --auth-flow: {} (use \"device\" or \"browser\")\n\n{}",
+            value,
+            USAGE
+        )),
+    }
+}
+
+// Helper: YouTube client for a signed-in account; the token provider keeps the
+// token fresh while the UI runs
+fn client_for(auth_client: auth::AuthClient, account: &str) -> youtube::YouTubeClient {
+    let tokens = Arc::new(auth::AuthTokenProvider::new(auth_client));
+    with_cache(
+        youtube::YouTubeClient::builder()
+            .http_client(reqwest::Client::new())
+            .token_provider(tokens),
+        account,
+    )
+    .build()
+}
+
+// Helper: Keep metadata on disk; the app still works if the cache can't be used
+fn with_cache(
+    builder: youtube::YouTubeClientBuilder,
+    account: &str,
+) -> youtube::YouTubeClientBuilder {
+    match cache::MetadataCache::for_account(account) {
+        Ok(cache) => builder.cache(cache),
+        Err(_) => builder,
+    }
+}
+
+// Helper: --logout, revokes and forgets the account's token
+async fn logout(account: &str) -> Result<()> {
+    let auth_client = auth::AuthClient::for_account(account)?;
+    let outcome = match auth_client.revoke().await {
+        Ok(outcome) => outcome,
+        // Nothing can revoke a token that can't be read; only drop it if asked to
+        Err(e) if auth::is_corrupt_token(&e) => {
+            println!(
+                "The saved token for '{}' can't be read, so it can't be revoked. Delete it anyway? [y/N]",
+                account
+            );
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer)?;
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                return Err(e);
+            }
+            auth_client.forget_corrupt_token().await?
+        }
+        Err(e) => return Err(e),
+    };
+    // Cached subscriptions and playlists belonged to the account
+    cache::MetadataCache::for_account(account)?.clear()?;
+    match outcome {
+        auth::RevokeOutcome::Revoked => println!("✓ Signed out of '{}'", account),
+        auth::RevokeOutcome::AlreadyRevoked => println!(
+            "✓ Access for '{}' was already revoked; removed the saved token",
+            account
+        ),
+        auth::RevokeOutcome::NotSignedIn => println!("'{}' is not signed in", account),
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = parse_args()?;
+
+    if args.mock {
+        let youtube_client = youtube::YouTubeClient::new(Arc::new(mock::MockSource::sample()));
+        return ui::run(youtube_client, None).await;
+    }
+
+    if args.logout {
+        return logout(&args.account).await;
+    }
+
     println!("Checking dependencies...");
 
     // Ensure mpv and yt-dlp are installed
@@ -25,43 +172,81 @@ async fn main() -> Result<()> {
         std::io::stdout().flush().ok();
     }
 
+    // Search, channels and recommendations work without an account
+    if args.anonymous || !auth::has_oauth_credentials() {
+        if !args.anonymous {
+            if args.write_access {
+                return Err(anyhow::anyhow!(
+                    "--write-access needs GOOGLE_CLIENT_ID and GOOGLE_CLIENT_SECRET to be set"
+                ));
+            }
+            println!("GOOGLE_CLIENT_ID and GOOGLE_CLIENT_SECRET are not set; continuing without an account.");
+            println!("To sign in and see your subscriptions and playlists:");
+            println!("  1. Go to https://console.cloud.google.com/");
+            println!("  2. Create a project and enable YouTube Data API v3");
+            println!("  3. Create OAuth 2.0 credentials (TVs and Limited Input devices, or");
+            println!("     Desktop app for --auth-flow browser)");
+            println!("  4. Set the environment variables");
+        }
+
+        let youtube_client = with_cache(
+            youtube::YouTubeClient::builder()
+                .http_client(reqwest::Client::new())
+                .api_key_from_env(),
+            cache::ANONYMOUS_CACHE,
+        )
+        .build();
+
+        println!("Starting UI...");
+        return ui::run(youtube_client, None).await;
+    }
+
     // Initialize auth client
-    let auth_client = match auth::AuthClient::new() {
+    let auth_options = auth::AuthOptions {
+        write_access: args.write_access,
+        flow: args.auth_flow,
+    };
+    let auth_client = match auth_options.client_for(&args.account) {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Error: {}", e);
-            eprintln!("\nPlease set the following environment variables:");
-            eprintln!("  GOOGLE_CLIENT_ID=your-client-id");
-            eprintln!("  GOOGLE_CLIENT_SECRET=your-client-secret");
-            eprintln!("\nTo get these credentials:");
-            eprintln!("  1. Go to https://console.cloud.google.com/");
-            eprintln!("  2. Create a project and enable YouTube Data API v3");
-            eprintln!("  3. Create OAuth 2.0 credentials (TVs and Limited Input devices)");
-            eprintln!("  4. Set the environment variables");
             return Err(e);
         }
     };
 
     // Authenticate (or load existing token)
-    println!("Authenticating with YouTube...");
-    let access_token = match auth_client.get_access_token().await {
-        Ok(token) => {
-            println!("✓ Authenticated successfully!");
-            token
-        }
-        Err(e) => {
-            eprintln!("Authentication failed: {}", e);
-            return Err(e);
-        }
-    };
+    if args.account == auth::DEFAULT_ACCOUNT {
+        println!("Authenticating with YouTube...");
+    } else {
+        println!("Authenticating with YouTube as '{}'...", args.account);
+    }
+    if let Err(e) = auth_client.get_access_token().await {
+        eprintln!("Authentication failed: {}", e);
+        return Err(e);
+    }
+    println!("✓ Authenticated successfully!");
 
-    // Initialize YouTube client with authentication
-    let http_client = reqwest::Client::new();
-    let youtube_client = youtube::YouTubeClient::with_auth(http_client, access_token);
+    let youtube_client = client_for(auth_client, &args.account);
+
+    // Other accounts can be switched to from the UI once they have signed in
+    let account_switcher = ui::AccountSwitcher {
+        current: Some(args.account),
+        connect: Box::new(move |account| {
+            let auth_client = auth_options.client_for(account)?;
+            if !auth_client.has_token() {
+                return Err(anyhow::anyhow!(
+                    "Account '{}' is not signed in. Run `rustyoutube --account {}` to sign in.",
+                    account,
+                    account
+                ));
+            }
+            Ok(client_for(auth_client, account))
+        }),
+    };
 
     // Run the UI
     println!("Starting UI...");
-    if let Err(e) = ui::run(youtube_client).await {
+    if let Err(e) = ui::run(youtube_client, Some(account_switcher)).await {
         eprintln!("Error running UI: {}", e);
         return Err(e);
     }
@@ -0,0 +1,244 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::youtube::{Playlist, Rating, Subscription, Video, VideoSource};
+
+// In-memory video source, used by tests and by `--mock` to run the TUI offline
+#[derive(Clone, Default)]
+pub struct MockSource {
+    pub videos: Vec<Video>,
+    pub subscriptions: Vec<Subscription>,
+    pub playlists: Vec<Playlist>,
+    pub playlist_videos: HashMap<String, Vec<Video>>,
+    pub authenticated: bool,
+    // Account changes made through this source, e.g. "subscribe UC-mock-rust"
+    pub actions: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockSource {
+    // A small fixed library with a bit of everything the UI can show
+    pub fn sample() -> Self {
+        let video = |id: &str, title: &str, channel_id: &str, channel: &str, date: &str| Video {
+            id: id.to_string(),
+            title: title.to_string(),
+            channel_id: channel_id.to_string(),
+            channel_title: channel.to_string(),
+            published_at: date.to_string(),
+            ..Default::default()
+        };
+
+        let videos = vec![
+            Video {
+                duration: Some(3723),
+                view_count: Some(1_523_401),
+                ..video(
+                    "mock-video-1",
+                    "Rust | Full Course for Beginners",
+                    "UC-mock-rust",
+                    "Rust Channel",
+                    "2024-06-14",
+                )
+            },
+            Video {
+                duration: Some(845),
+                view_count: Some(48_210),
+                ..video(
+                    "mock-video-2",
+                    "Ownership and Borrowing Explained",
+                    "UC-mock-rust",
+                    "Rust Channel",
+                    "2024-05-02",
+                )
+            },
+            Video {
+                is_live: true,
+                ..video(
+                    "mock-live-1",
+                    "Live coding a terminal UI",
+                    "UC-mock-stream",
+                    "Stream Channel",
+                    "2024-06-20",
+                )
+            },
+            Video {
+                duration: Some(31),
+                view_count: Some(902_344),
+                is_short: true,
+                ..video(
+                    "mock-short-1",
+                    "Rust in 30 seconds #shorts",
+                    "UC-mock-stream",
+                    "Stream Channel",
+                    "2024-06-18",
+                )
+            },
+        ];
+
+        let subscriptions = vec![
+            Subscription {
+                channel_id: "UC-mock-rust".to_string(),
+                channel_title: "Rust Channel".to_string(),
+                thumbnail_url: String::new(),
+            },
+            Subscription {
+                channel_id: "UC-mock-stream".to_string(),
+                channel_title: "Stream Channel".to_string(),
+                thumbnail_url: String::new(),
+            },
+        ];
+
+        let playlists = vec![Playlist {
+            id: "PL-mock-1".to_string(),
+            title: "Learning Rust".to_string(),
+            description: "Everything to get started".to_string(),
+            item_count: 2,
+        }];
+
+        let mut playlist_videos = HashMap::new();
+        playlist_videos.insert(
+            "PL-mock-1".to_string(),
+            videos[..2]
+                .iter()
+                .enumerate()
+                .map(|(i, video)| Video {
+                    playlist_item_id: Some(format!("PLI-mock-1-{}", i + 1)),
+                    ..video.clone()
+                })
+                .collect(),
+        );
+
+        Self {
+            videos,
+            subscriptions,
+            playlists,
+            playlist_videos,
+            authenticated: true,
+            actions: Arc::default(),
+        }
+    }
+
+    // Helper: remember an account change so tests can check it
+    fn record(&self, action: String) -> Result<()> {
+        self.actions.lock().unwrap().push(action);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl VideoSource for MockSource {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    async fn search_videos(&self, query: &str) -> Result<Vec<Video>> {
+        let query = query.to_lowercase();
+        Ok(self
+            .videos
+            .iter()
+            .filter(|v| v.title.to_lowercase().contains(&query))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_channel_videos(&self, channel_url: &str) -> Result<Vec<Video>> {
+        // Match either the channel ID or the handle ("@Rust Channel" -> "rustchannel")
+        let wanted = channel_url.to_lowercase();
+        Ok(self
+            .videos
+            .iter()
+            .filter(|v| {
+                let handle: String = v.channel_title.to_lowercase().split_whitespace().collect();
+                (!v.channel_id.is_empty() && channel_url.contains(&v.channel_id))
+                    || wanted.contains(&format!("@{}", handle))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_channel_videos_by_id(&self, channel_id: &str) -> Result<Vec<Video>> {
+        Ok(self
+            .videos
+            .iter()
+            .filter(|v| v.channel_id == channel_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_channel_playlists(&self, _channel_id: &str) -> Result<Vec<Playlist>> {
+        Ok(self.playlists.clone())
+    }
+
+    async fn get_recommendations(&self) -> Result<Vec<Video>> {
+        Ok(self.videos.clone())
+    }
+
+    async fn get_subscriptions(&self) -> Result<Vec<Subscription>> {
+        Ok(self.subscriptions.clone())
+    }
+
+    async fn get_playlists(&self) -> Result<Vec<Playlist>> {
+        Ok(self.playlists.clone())
+    }
+
+    async fn get_subscription_feed(
+        &self,
+        subscriptions: &[Subscription],
+        _refresh: bool,
+    ) -> Result<Vec<Video>> {
+        let mut videos: Vec<Video> = self
+            .videos
+            .iter()
+            .filter(|v| {
+                subscriptions
+                    .iter()
+                    .any(|sub| sub.channel_id == v.channel_id)
+            })
+            .cloned()
+            .collect();
+        videos.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+        Ok(videos)
+    }
+
+    async fn get_playlist_videos(&self, playlist_id: &str) -> Result<Vec<Video>> {
+        Ok(self
+            .playlist_videos
+            .get(playlist_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn get_video(&self, video_id: &str) -> Result<Video> {
+        self.videos
+            .iter()
+            .chain(self.playlist_videos.values().flatten())
+            .find(|v| v.id == video_id)
+            .cloned()
+            .with_context(|| format!("Unknown video: {}", video_id))
+    }
+
+    async fn subscribe(&self, channel_id: &str) -> Result<()> {
+        self.record(format!("subscribe {}", channel_id))
+    }
+
+    async fn unsubscribe(&self, channel_id: &str) -> Result<()> {
+        self.record(format!("unsubscribe {}", channel_id))
+    }
+
+    async fn rate_video(&self, video_id: &str, rating: Rating) -> Result<()> {
+        self.record(format!("rate {} {}", video_id, rating.as_str()))
+    }
+
+    async fn add_to_playlist(&self, playlist_id: &str, video_id: &str) -> Result<()> {
+        self.record(format!("add {} {}", playlist_id, video_id))
+    }
+
+    async fn remove_from_playlist(&self, playlist_item_id: &str) -> Result<()> {
+        self.record(format!("remove {}", playlist_item_id))
+    }
+}
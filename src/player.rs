@@ -1,12 +1,73 @@
 use crate::deps;
-use anyhow::Result;
-use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use crate::history::WATCHED_THRESHOLD;
+use crate::settings::{AudioPrefs, QualityPrefs, SegmentAction, SponsorBlockPrefs, SubtitlePrefs};
+use crate::sponsorblock::{self, SponsorBlockClient};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::process::Command as TokioCommand;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc, oneshot};
 
-// Format selector: prefer av01, then vp09, then anything else
-const FORMAT_SELECTOR: &str = "bestvideo[vcodec^=av01][height<=1080]+bestaudio/best[vcodec^=av01][height<=1080]/bestvideo[vcodec^=vp09][height<=1080]+bestaudio/best[vcodec^=vp09][height<=1080]/best[height<=1080]";
+// Audio-only playback: the best audio stream, or a full file when there is none
+pub const AUDIO_FORMAT_SELECTOR: &str = "bestaudio/best";
+
+// How a playback ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaybackEnd {
+    // mpv was closed at this position (seconds)
+    StoppedAt(u64),
+    // The video played to the end
+    Finished,
+    // mpv closed without saying where it stopped
+    Unknown,
+}
+
+// How to play a video
+#[derive(Clone, Default)]
+pub struct PlayOptions {
+    // Start this many seconds in
+    pub start_at: Option<u64>,
+    // Play only the audio, without opening a window
+    pub audio_only: bool,
+    // Preferred codecs and limits
+    pub quality: QualityPrefs,
+    // A format picked for this video (yt-dlp selector), used instead of the above
+    pub format: Option<String>,
+    // Subtitle languages to load
+    pub subtitles: SubtitlePrefs,
+    // Segments to skip or mark while playing
+    pub sponsorblock: SponsorBlockPrefs,
+    // Gets a controller for the player once its IPC socket is up
+    pub controller_tx: Option<mpsc::UnboundedSender<Arc<MpvController>>>,
+}
+
+// How to download a video
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    // Preferred codecs, limits and container
+    pub quality: QualityPrefs,
+    // A format picked for this video (yt-dlp selector), used instead of the above
+    pub format: Option<String>,
+    // Folder to save into (None: where the app was started)
+    pub directory: Option<PathBuf>,
+    // yt-dlp output template, relative to the folder (None: the video's title)
+    pub output: Option<String>,
+    // Download even when the video is already on disk
+    pub force: bool,
+    // Keep only the audio, converted to this codec, with the video's details
+    // and thumbnail embedded
+    pub audio: Option<AudioPrefs>,
+    // Subtitles to save with the video (none unless a download mode is set)
+    pub subtitles: SubtitlePrefs,
+    // Segments to cut out or mark as chapters (only if applied to downloads)
+    pub sponsorblock: SponsorBlockPrefs,
+}
 
 // Helper: Get mpv command path
 async fn get_mpv_cmd() -> String {
@@ -24,22 +85,6 @@ async fn get_mpv_cmd() -> String {
     }
 }
 
-// Helper: Get yt-dlp command path
-async fn get_ytdlp_path() -> String {
-    #[cfg(windows)]
-    {
-        if let Some(local_ytdlp) = deps::get_ytdlp_path().await {
-            local_ytdlp.to_string_lossy().to_string()
-        } else {
-            "yt-dlp.exe".to_string()
-        }
-    }
-    #[cfg(not(windows))]
-    {
-        "yt-dlp".to_string()
-    }
-}
-
 // Helper: Capture output from stdout and send to log channel
 fn capture_output(
     stream: Option<tokio::process::ChildStdout>,
@@ -68,34 +113,6 @@ fn capture_output(
     }
 }
 
-// Helper: Capture stderr and send to log channel (simple version for downloads)
-fn capture_stderr_simple(
-    stderr: Option<tokio::process::ChildStderr>,
-    log_tx: Option<mpsc::UnboundedSender<String>>,
-) {
-    if let Some(stderr) = stderr {
-        let mut reader = BufReader::new(stderr);
-        tokio::spawn(async move {
-            let mut line = String::new();
-            loop {
-                line.clear();
-                match reader.read_line(&mut line).await {
-                    Ok(0) => break,
-                    Ok(_) => {
-                        let trimmed = line.trim();
-                        if !trimmed.is_empty() {
-                            if let Some(ref tx) = log_tx {
-                                let _ = tx.send(trimmed.to_string());
-                            }
-                        }
-                    }
-                    Err(_) => break,
-                }
-            }
-        });
-    }
-}
-
 // Helper: Capture stderr and collect it for error messages
 fn capture_stderr(
     stderr: Option<tokio::process::ChildStderr>,
@@ -138,7 +155,7 @@ async fn build_mpv_args_with_ytdlp(
     let mut args = build_mpv_args(caps);
 
     // Configure mpv to use yt-dlp
-    let ytdlp_path = get_ytdlp_path().await;
+    let ytdlp_path = deps::ytdlp_cmd().await;
     args.push(format!("--script-opts=ytdl_hook-ytdl_path={}", ytdlp_path));
 
     // Set format selector if provided
@@ -150,6 +167,222 @@ async fn build_mpv_args_with_ytdlp(
     args
 }
 
+// Helper: mpv saves the position it was closed at into this directory
+fn watch_later_dir(video_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "rustyoutube-watch-later-{}-{}",
+        std::process::id(),
+        video_id
+    ))
+}
+
+// Helper: Start at `start_at` and save the position when mpv is closed
+fn resume_args(start_at: Option<u64>, watch_later_dir: &Path) -> Vec<String> {
+    let mut args = vec![
+        "--save-position-on-quit".to_string(),
+        format!("--watch-later-directory={}", watch_later_dir.display()),
+    ];
+    if let Some(start) = start_at {
+        args.push(format!("--start={}", start));
+    }
+    args
+}
+
+// What mpv reported over IPC while playing, to tell how the playback ended
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PlaybackReport {
+    // Why the file stopped: "eof" when it played to the end, "quit", "error", ...
+    end_reason: Option<String>,
+    // Last known position and duration (seconds)
+    position: Option<f64>,
+    duration: Option<f64>,
+}
+
+// How long to wait for the last IPC events once mpv has exited
+const REPORT_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Helper: Follow the playback over mpv's IPC socket until mpv exits
+fn track_playback(socket: &Path) -> tokio::task::JoinHandle<PlaybackReport> {
+    let socket = socket.to_path_buf();
+    tokio::spawn(async move {
+        let mut report = PlaybackReport::default();
+        let Ok(controller) = MpvController::connect_when_ready(&socket, IPC_CONNECT_TIMEOUT).await
+        else {
+            return report;
+        };
+        let mut events = controller.events();
+        let _ = controller.observe("time-pos").await;
+        let _ = controller.observe("duration").await;
+        loop {
+            match events.recv().await {
+                Ok(MpvEvent::PropertyChange { name, data }) => {
+                    // Both turn null once the file is closed; keep the last values
+                    let Some(value) = data.as_f64() else { continue };
+                    match name.as_str() {
+                        "time-pos" => report.position = Some(value),
+                        "duration" => report.duration = Some(value),
+                        _ => {}
+                    }
+                }
+                Ok(MpvEvent::EndFile { reason }) => report.end_reason = Some(reason),
+                Ok(MpvEvent::Shutdown) | Err(broadcast::error::RecvError::Closed) => break,
+                Ok(MpvEvent::Other(_)) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            }
+        }
+        report
+    })
+}
+
+// Helper: The tracked report once mpv has exited (empty if it never connected)
+async fn finish_tracking(tracker: tokio::task::JoinHandle<PlaybackReport>) -> PlaybackReport {
+    match tokio::time::timeout(REPORT_TIMEOUT, tracker).await {
+        Ok(Ok(report)) => report,
+        _ => PlaybackReport::default(),
+    }
+}
+
+// Helper: Work out where playback stopped. mpv saves a position when it's closed
+// before the end; otherwise the IPC report decides, and without one the end is
+// unknown rather than finished.
+fn playback_end(watch_later_dir: &Path, report: &PlaybackReport) -> PlaybackEnd {
+    let saved = fs::read_dir(watch_later_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .find_map(|content| parse_watch_later(&content));
+    let _ = fs::remove_dir_all(watch_later_dir);
+    if let Some(position) = saved {
+        return PlaybackEnd::StoppedAt(position);
+    }
+    if report.end_reason.as_deref() == Some("eof") {
+        return PlaybackEnd::Finished;
+    }
+    match (report.position, report.duration) {
+        (Some(position), Some(duration))
+            if duration > 0.0 && position >= duration * WATCHED_THRESHOLD =>
+        {
+            PlaybackEnd::Finished
+        }
+        (Some(position), _) => PlaybackEnd::StoppedAt(position.max(0.0) as u64),
+        _ => PlaybackEnd::Unknown,
+    }
+}
+
+// Helper: The `start=<seconds>` line of an mpv watch-later file
+pub fn parse_watch_later(content: &str) -> Option<u64> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("start="))
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|seconds| *seconds >= 0.0)
+        .map(|seconds| seconds as u64)
+}
+
+// Helper: mpv arguments for audio-only playback
+fn audio_only_args(audio_only: bool) -> Vec<String> {
+    if audio_only {
+        vec!["--no-video".to_string(), "--force-window=no".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+// Helper: mpv arguments for the preferred subtitles. mpv's ytdl hook asks
+// yt-dlp for every subtitle unless languages are given through its raw options.
+fn subtitle_args(subtitles: &SubtitlePrefs) -> Vec<String> {
+    if subtitles.languages.is_empty() {
+        return Vec::new();
+    }
+    let languages = subtitles.languages.join(",");
+    let mut args = vec![
+        format!("--slang={}", languages),
+        "--ytdl-raw-options-append=write-subs=".to_string(),
+        format!("--ytdl-raw-options-append=sub-langs={}", languages),
+    ];
+    if subtitles.auto_captions {
+        args.push("--ytdl-raw-options-append=write-auto-subs=".to_string());
+    }
+    args
+}
+
+// Helper: A fresh IPC socket path (a named pipe on Windows) for one mpv instance
+fn ipc_socket_path() -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let name = format!(
+        "rustyoutube-mpv-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::SeqCst)
+    );
+    #[cfg(windows)]
+    {
+        PathBuf::from(format!(r"\\.\pipe\{}", name))
+    }
+    #[cfg(not(windows))]
+    {
+        std::env::temp_dir().join(format!("{}.sock", name))
+    }
+}
+
+// Helper: Connect to mpv once it has started and hand the controller to the UI
+fn attach_controller(
+    socket: &Path,
+    controller_tx: Option<mpsc::UnboundedSender<Arc<MpvController>>>,
+) {
+    let Some(controller_tx) = controller_tx else {
+        return;
+    };
+    let socket = socket.to_path_buf();
+    tokio::spawn(async move {
+        if let Ok(controller) =
+            MpvController::connect_when_ready(&socket, IPC_CONNECT_TIMEOUT).await
+        {
+            let _ = controller.observe_playback().await;
+            let _ = controller_tx.send(Arc::new(controller));
+        }
+    });
+}
+
+// Helper: Look up the video's SponsorBlock segments and skip or mark them once
+// mpv is up. Playback goes on without them if SponsorBlock can't be reached.
+fn attach_sponsorblock(
+    socket: &Path,
+    video_id: &str,
+    prefs: &SponsorBlockPrefs,
+    log_tx: Option<mpsc::UnboundedSender<String>>,
+) {
+    let mut categories = prefs.categories_with(SegmentAction::Skip);
+    categories.extend(prefs.categories_with(SegmentAction::Mark));
+    if !prefs.enabled || categories.is_empty() {
+        return;
+    }
+    let socket = socket.to_path_buf();
+    let video_id = video_id.to_string();
+    let prefs = prefs.clone();
+    tokio::spawn(async move {
+        let send_log = |msg: String| {
+            if let Some(ref tx) = log_tx {
+                let _ = tx.send(msg);
+            }
+        };
+        let client = SponsorBlockClient::new(reqwest::Client::new()).with_base_url(&prefs.api_url);
+        let segments = match client.segments(&video_id, &categories).await {
+            Ok(segments) if segments.is_empty() => return,
+            Ok(segments) => segments,
+            Err(e) => {
+                send_log(format!("SponsorBlock unavailable: {:#}", e));
+                return;
+            }
+        };
+        send_log(format!("SponsorBlock: {} segment(s) found", segments.len()));
+        if let Ok(controller) =
+            MpvController::connect_when_ready(&socket, IPC_CONNECT_TIMEOUT).await
+        {
+            let _ = sponsorblock::follow_playback(&controller, &segments, &prefs, log_tx).await;
+        }
+    });
+}
+
 #[derive(Debug, Clone)]
 struct HardwareCapabilities {
     hwdec_available: Vec<String>,
@@ -288,8 +521,9 @@ fn build_mpv_args(caps: &HardwareCapabilities) -> Vec<String> {
 
 pub async fn play_video(
     video_id: &str,
+    options: &PlayOptions,
     log_tx: Option<mpsc::UnboundedSender<String>>,
-) -> Result<()> {
+) -> Result<PlaybackEnd> {
     // Helper function to send log messages
     let send_log = |msg: &str| {
         if let Some(ref tx) = log_tx {
@@ -323,13 +557,43 @@ pub async fn play_video(
         caps.performance_level
     ));
 
-    // Build mpv arguments with yt-dlp config and AV01 format preference
-    let mut mpv_args = build_mpv_args_with_ytdlp(&caps, Some(FORMAT_SELECTOR)).await;
+    // Build mpv arguments with yt-dlp config and the preferred formats
+    let format = match &options.format {
+        Some(format) => format.clone(),
+        None if options.audio_only => AUDIO_FORMAT_SELECTOR.to_string(),
+        None => options.quality.format_selector(),
+    };
+    let mut mpv_args = build_mpv_args_with_ytdlp(&caps, Some(&format)).await;
+    mpv_args.extend(audio_only_args(options.audio_only));
+    mpv_args.extend(subtitle_args(&options.subtitles));
+    let watch_later = watch_later_dir(video_id);
+    mpv_args.extend(resume_args(options.start_at, &watch_later));
+    if let Some(start) = options.start_at {
+        send_log(&format!("Resuming at {}s", start));
+    }
+    let ipc_socket = ipc_socket_path();
+    mpv_args.push(format!("--input-ipc-server={}", ipc_socket.display()));
 
     // Add the YouTube URL
     mpv_args.push(url);
 
-    send_log("Streaming video with mpv (preferring av01 > vp09 > other)...");
+    if options.format.is_some() {
+        send_log(&format!("Streaming format {} with mpv...", format));
+    } else if options.audio_only {
+        send_log("Streaming audio only with mpv...");
+    } else {
+        send_log(&format!(
+            "Streaming video with mpv (preferring {})...",
+            options
+                .quality
+                .codecs
+                .iter()
+                .map(String::as_str)
+                .chain(["other"])
+                .collect::<Vec<_>>()
+                .join(" > ")
+        ));
+    }
 
     let mut mpv = TokioCommand::new(&mpv_cmd)
         .args(&mpv_args)
@@ -340,9 +604,13 @@ pub async fn play_video(
     // Capture output streams
     capture_output(mpv.stdout.take(), log_tx.clone());
     let stderr_handle = capture_stderr(mpv.stderr.take(), log_tx.clone());
+    attach_controller(&ipc_socket, options.controller_tx.clone());
+    attach_sponsorblock(&ipc_socket, video_id, &options.sponsorblock, log_tx.clone());
+    let tracker = track_playback(&ipc_socket);
 
     send_log("Starting mpv player...");
     let status = mpv.wait().await?;
+    let _ = fs::remove_file(&ipc_socket);
 
     // Get stderr output if available
     let stderr_output = stderr_handle.await.unwrap_or_default();
@@ -378,9 +646,10 @@ pub async fn play_video(
             || error_msg.is_empty()
         {
             send_log("Retrying with fallback format (best available)...");
-            return play_video_fallback_format(video_id, log_tx).await;
+            return play_video_fallback_format(video_id, options, log_tx).await;
         }
 
+        let _ = fs::remove_dir_all(&watch_later);
         return Err(anyhow::anyhow!(
             "{}\nExit code: {:?}\nError details: {}",
             user_friendly_error,
@@ -394,14 +663,16 @@ pub async fn play_video(
     }
 
     send_log("Video playback completed.");
-    Ok(())
+    let report = finish_tracking(tracker).await;
+    Ok(playback_end(&watch_later, &report))
 }
 
 // Fallback function to try with simpler format selection
 async fn play_video_fallback_format(
     video_id: &str,
+    options: &PlayOptions,
     log_tx: Option<mpsc::UnboundedSender<String>>,
-) -> Result<()> {
+) -> Result<PlaybackEnd> {
     let send_log = |msg: &str| {
         if let Some(ref tx) = log_tx {
             let _ = tx.send(msg.to_string());
@@ -412,12 +683,22 @@ async fn play_video_fallback_format(
     let mpv_cmd = get_mpv_cmd().await;
 
     // Use simpler format selector as fallback
-    let fallback_format = "best[height<=1080]/best";
+    let fallback_format = if options.audio_only {
+        "bestaudio*/best"
+    } else {
+        "best[height<=1080]/best"
+    };
     send_log(&format!("Trying fallback format: {}", fallback_format));
 
     // Get hardware caps for basic args
     let caps = detect_hardware_capabilities(&mpv_cmd, log_tx.clone()).await;
     let mut mpv_args = build_mpv_args_with_ytdlp(&caps, Some(fallback_format)).await;
+    mpv_args.extend(audio_only_args(options.audio_only));
+    mpv_args.extend(subtitle_args(&options.subtitles));
+    let watch_later = watch_later_dir(video_id);
+    mpv_args.extend(resume_args(options.start_at, &watch_later));
+    let ipc_socket = ipc_socket_path();
+    mpv_args.push(format!("--input-ipc-server={}", ipc_socket.display()));
     mpv_args.push(url);
 
     let mut mpv = TokioCommand::new(&mpv_cmd)
@@ -429,24 +710,30 @@ async fn play_video_fallback_format(
     // Capture output streams
     capture_output(mpv.stdout.take(), log_tx.clone());
     capture_stderr(mpv.stderr.take(), log_tx.clone());
+    attach_controller(&ipc_socket, options.controller_tx.clone());
+    attach_sponsorblock(&ipc_socket, video_id, &options.sponsorblock, log_tx.clone());
+    let tracker = track_playback(&ipc_socket);
 
     let status = mpv.wait().await?;
+    let _ = fs::remove_file(&ipc_socket);
 
     if !status.success() {
         // Try final fallback with just 'best'
         send_log("Fallback format failed, trying basic 'best' format...");
-        return play_video_final_fallback(video_id, log_tx).await;
+        return play_video_final_fallback(video_id, options, log_tx).await;
     }
 
     send_log("Video playback completed (using fallback format).");
-    Ok(())
+    let report = finish_tracking(tracker).await;
+    Ok(playback_end(&watch_later, &report))
 }
 
 // Final fallback function using just 'best' format
 async fn play_video_final_fallback(
     video_id: &str,
+    options: &PlayOptions,
     log_tx: Option<mpsc::UnboundedSender<String>>,
-) -> Result<()> {
+) -> Result<PlaybackEnd> {
     let send_log = |msg: &str| {
         if let Some(ref tx) = log_tx {
             let _ = tx.send(msg.to_string());
@@ -462,6 +749,12 @@ async fn play_video_final_fallback(
     // Get hardware caps for basic args
     let caps = detect_hardware_capabilities(&mpv_cmd, log_tx.clone()).await;
     let mut mpv_args = build_mpv_args_with_ytdlp(&caps, None).await;
+    mpv_args.extend(audio_only_args(options.audio_only));
+    mpv_args.extend(subtitle_args(&options.subtitles));
+    let watch_later = watch_later_dir(video_id);
+    mpv_args.extend(resume_args(options.start_at, &watch_later));
+    let ipc_socket = ipc_socket_path();
+    mpv_args.push(format!("--input-ipc-server={}", ipc_socket.display()));
     mpv_args.push(url);
 
     let mut mpv = TokioCommand::new(&mpv_cmd)
@@ -473,8 +766,12 @@ async fn play_video_final_fallback(
     // Capture output streams
     capture_output(mpv.stdout.take(), log_tx.clone());
     capture_stderr(mpv.stderr.take(), log_tx.clone());
+    attach_controller(&ipc_socket, options.controller_tx.clone());
+    attach_sponsorblock(&ipc_socket, video_id, &options.sponsorblock, log_tx.clone());
+    let tracker = track_playback(&ipc_socket);
 
     let status = mpv.wait().await?;
+    let _ = fs::remove_file(&ipc_socket);
 
     if !status.success() {
         let exit_code = status.code();
@@ -482,6 +779,7 @@ async fn play_video_final_fallback(
             "Final fallback also failed with exit code: {:?}",
             exit_code
         ));
+        let _ = fs::remove_dir_all(&watch_later);
         return Err(anyhow::anyhow!(
             "Video playback failed with all format options.\nExit code: {:?}\nThe video might be unavailable, private, or your network connection is having issues.",
             exit_code
@@ -489,108 +787,345 @@ async fn play_video_final_fallback(
     }
 
     send_log("Video playback completed (using mpv default format).");
-    Ok(())
+    let report = finish_tracking(tracker).await;
+    Ok(playback_end(&watch_later, &report))
 }
 
-pub async fn download_video(
-    video_id: &str,
-    log_tx: Option<mpsc::UnboundedSender<String>>,
-    handle_storage: Option<Arc<std::sync::Mutex<Option<tokio::process::Child>>>>,
-) -> Result<()> {
-    // Ensure yt-dlp is available
-    if !deps::check_ytdlp().await {
-        deps::ensure_ytdlp().await?;
-    }
+// How long to wait for a freshly started mpv to open its IPC socket
+const IPC_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// How long to wait for mpv to answer a command
+const IPC_REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Something mpv reported without being asked
+#[derive(Debug, Clone, PartialEq)]
+pub enum MpvEvent {
+    // An observed property changed (see `observe`); `data` is null while unavailable
+    PropertyChange { name: String, data: Value },
+    // The file stopped playing, e.g. because it ended or mpv was told to quit
+    EndFile { reason: String },
+    // mpv is exiting or the connection was lost
+    Shutdown,
+    // Any other event, by name (e.g. "seek", "playback-restart")
+    Other(String),
+}
 
-    let url = format!("https://www.youtube.com/watch?v={}", video_id);
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value>>>>>;
 
-    // Use local yt-dlp if available
-    #[cfg(windows)]
-    let ytdlp_cmd = if let Some(local_ytdlp) = deps::get_ytdlp_path().await {
-        local_ytdlp.to_str().unwrap().to_string()
-    } else {
-        "yt-dlp.exe".to_string()
-    };
-    #[cfg(not(windows))]
-    let ytdlp_cmd = "yt-dlp";
+// Controls a running mpv over its JSON IPC socket (--input-ipc-server)
+pub struct MpvController {
+    writer: tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>,
+    pending: Pending,
+    next_request_id: AtomicU64,
+    events: broadcast::Sender<MpvEvent>,
+}
 
-    // Helper function to send log messages
-    let send_log = |msg: &str| {
-        if let Some(ref tx) = log_tx {
-            let _ = tx.send(msg.to_string());
+impl MpvController {
+    pub async fn connect(socket: &Path) -> Result<Self> {
+        #[cfg(windows)]
+        let stream = tokio::net::windows::named_pipe::ClientOptions::new()
+            .open(socket)
+            .with_context(|| format!("Failed to connect to mpv at {}", socket.display()))?;
+        #[cfg(not(windows))]
+        let stream = tokio::net::UnixStream::connect(socket)
+            .await
+            .with_context(|| format!("Failed to connect to mpv at {}", socket.display()))?;
+        Ok(Self::from_stream(stream))
+    }
+
+    // Keep trying while mpv starts up
+    pub async fn connect_when_ready(socket: &Path, timeout: Duration) -> Result<Self> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            match Self::connect(socket).await {
+                Ok(controller) => return Ok(controller),
+                Err(e) if tokio::time::Instant::now() >= deadline => return Err(e),
+                Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+            }
         }
-    };
+    }
 
-    send_log("Starting download with yt-dlp...");
-    let mut download = TokioCommand::new(&ytdlp_cmd)
-        .arg("--format")
-        .arg(FORMAT_SELECTOR)
-        .arg("--progress")
-        .arg("--newline")
-        .arg("--output")
-        .arg("%(title)s.%(ext)s")
-        .arg(&url)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()?;
+    // Helper: Split the stream and read replies and events in the background
+    fn from_stream<S>(stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let (events, _) = broadcast::channel(64);
 
-    // Take stdout and stderr before storing handle
-    let stdout = download.stdout.take();
-    let stderr = download.stderr.take();
+        let reader_pending = pending.clone();
+        let reader_events = events.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                if let Some(request_id) = message.get("request_id").and_then(Value::as_u64) {
+                    let reply = reader_pending.lock().unwrap().remove(&request_id);
+                    if let Some(reply) = reply {
+                        let _ = reply.send(parse_reply(&message));
+                    }
+                } else if let Some(event) = parse_event(&message) {
+                    let _ = reader_events.send(event);
+                }
+            }
+            // Connection closed: nobody will answer the outstanding requests
+            reader_pending.lock().unwrap().clear();
+            let _ = reader_events.send(MpvEvent::Shutdown);
+        });
 
-    // Store the handle for cancellation
-    if let Some(ref handle_storage) = &handle_storage {
-        let mut guard = handle_storage.lock().unwrap();
-        *guard = Some(download);
+        Self {
+            writer: tokio::sync::Mutex::new(Box::new(writer)),
+            pending,
+            next_request_id: AtomicU64::new(1),
+            events,
+        }
     }
 
-    // Capture and print output in real-time
-    capture_output(stdout, log_tx.clone());
-    capture_stderr_simple(stderr, log_tx.clone());
+    // Events from now on
+    pub fn events(&self) -> broadcast::Receiver<MpvEvent> {
+        self.events.subscribe()
+    }
 
-    // Wait for download to complete
-    let status = if let Some(ref handle_storage) = &handle_storage {
-        // Take the child out of the mutex before awaiting
-        let child = {
-            let mut child_guard = handle_storage.lock().unwrap();
-            child_guard.take()
+    // Run an mpv command, e.g. ["seek", 10, "relative"], and return its data
+    pub async fn command(&self, args: Vec<Value>) -> Result<Value> {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id, reply_tx);
+
+        let mut line = json!({ "command": args, "request_id": request_id }).to_string();
+        line.push('\n');
+        let written = {
+            let mut writer = self.writer.lock().await;
+            match writer.write_all(line.as_bytes()).await {
+                Ok(()) => writer.flush().await,
+                Err(e) => Err(e),
+            }
         };
+        if let Err(e) = written {
+            self.pending.lock().unwrap().remove(&request_id);
+            return Err(e).context("Failed to send command to mpv");
+        }
 
-        if let Some(mut child_process) = child {
-            let result = child_process.wait().await;
-            // Clear handle after completion
-            {
-                let mut child_guard = handle_storage.lock().unwrap();
-                *child_guard = None;
+        match tokio::time::timeout(IPC_REPLY_TIMEOUT, reply_rx).await {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(_)) => Err(anyhow::anyhow!("mpv closed the connection")),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&request_id);
+                Err(anyhow::anyhow!("mpv did not answer"))
             }
-            match result {
-                Ok(status) => status,
-                Err(e) => {
-                    send_log(&format!("Error waiting for download: {}", e));
-                    return Err(anyhow::anyhow!("Error waiting for download: {}", e));
-                }
-            }
-        } else {
-            // Handle was already taken (cancelled)
-            send_log("Download was cancelled");
-            return Err(anyhow::anyhow!("Download was cancelled"));
         }
-    } else {
-        return Err(anyhow::anyhow!(
-            "Handle storage required for cancellation support"
-        ));
-    };
+    }
 
-    if !status.success() {
-        send_log(&format!(
-            "Download failed with exit code: {:?}",
-            status.code()
-        ));
-        return Err(anyhow::anyhow!(
-            "Download failed with exit code: {:?}",
-            status.code()
-        ));
+    pub async fn get_property(&self, name: &str) -> Result<Value> {
+        self.command(vec![json!("get_property"), json!(name)]).await
+    }
+
+    pub async fn set_property(&self, name: &str, value: Value) -> Result<()> {
+        self.command(vec![json!("set_property"), json!(name), value])
+            .await
+            .map(|_| ())
+    }
+
+    // Helper: A numeric property, or None while mpv has nothing loaded
+    async fn number(&self, name: &str) -> Result<Option<f64>> {
+        match self.get_property(name).await {
+            Ok(value) => Ok(value.as_f64()),
+            Err(e) if e.to_string().contains("property unavailable") => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Current playback position in seconds
+    pub async fn time_pos(&self) -> Result<Option<f64>> {
+        self.number("time-pos").await
+    }
+
+    pub async fn duration(&self) -> Result<Option<f64>> {
+        self.number("duration").await
+    }
+
+    pub async fn is_paused(&self) -> Result<bool> {
+        Ok(self.get_property("pause").await?.as_bool().unwrap_or(false))
+    }
+
+    pub async fn pause(&self) -> Result<()> {
+        self.set_property("pause", json!(true)).await
+    }
+
+    pub async fn resume(&self) -> Result<()> {
+        self.set_property("pause", json!(false)).await
+    }
+
+    pub async fn toggle_pause(&self) -> Result<()> {
+        self.command(vec![json!("cycle"), json!("pause")])
+            .await
+            .map(|_| ())
+    }
+
+    // Seek by `seconds` (negative goes back)
+    pub async fn seek(&self, seconds: f64) -> Result<()> {
+        self.command(vec![json!("seek"), json!(seconds), json!("relative")])
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn seek_to(&self, seconds: f64) -> Result<()> {
+        self.command(vec![json!("seek"), json!(seconds), json!("absolute")])
+            .await
+            .map(|_| ())
+    }
+
+    // Volume in percent (100 is unchanged)
+    pub async fn set_volume(&self, volume: f64) -> Result<()> {
+        self.set_property("volume", json!(volume)).await
+    }
+
+    pub async fn add_volume(&self, delta: f64) -> Result<()> {
+        self.command(vec![json!("add"), json!("volume"), json!(delta)])
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn set_speed(&self, speed: f64) -> Result<()> {
+        self.set_property("speed", json!(speed)).await
+    }
+
+    pub async fn add_speed(&self, delta: f64) -> Result<()> {
+        self.command(vec![json!("add"), json!("speed"), json!(delta)])
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn quit(&self) -> Result<()> {
+        self.command(vec![json!("quit")]).await.map(|_| ())
+    }
+
+    // Report changes of a property as `MpvEvent::PropertyChange`
+    pub async fn observe(&self, name: &str) -> Result<()> {
+        let id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+        self.command(vec![json!("observe_property"), json!(id), json!(name)])
+            .await
+            .map(|_| ())
+    }
+
+    // Observe everything the now-playing bar shows
+    pub async fn observe_playback(&self) -> Result<()> {
+        for name in [
+            "media-title",
+            "time-pos",
+            "duration",
+            "pause",
+            "volume",
+            "speed",
+        ] {
+            self.observe(name).await?;
+        }
+        Ok(())
+    }
+}
+
+// Helper: mpv answers {"error": "success", "data": ...} or {"error": "<reason>"}
+fn parse_reply(message: &Value) -> Result<Value> {
+    match message.get("error").and_then(Value::as_str) {
+        Some("success") => Ok(message.get("data").cloned().unwrap_or(Value::Null)),
+        Some(error) => Err(anyhow::anyhow!("mpv: {}", error)),
+        None => Err(anyhow::anyhow!("mpv sent an unexpected reply")),
+    }
+}
+
+fn parse_event(message: &Value) -> Option<MpvEvent> {
+    let name = message.get("event")?.as_str()?;
+    Some(match name {
+        "property-change" => MpvEvent::PropertyChange {
+            name: message.get("name")?.as_str()?.to_string(),
+            data: message.get("data").cloned().unwrap_or(Value::Null),
+        },
+        "end-file" => MpvEvent::EndFile {
+            reason: message
+                .get("reason")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        },
+        "shutdown" => MpvEvent::Shutdown,
+        other => MpvEvent::Other(other.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watch_later_position_is_read() {
+        let content = "# https://www.youtube.com/watch?v=abc\nstart=754.320000\nvolume=80\n";
+        assert_eq!(parse_watch_later(content), Some(754));
+        assert_eq!(parse_watch_later("volume=80\n"), None);
+    }
+
+    #[test]
+    fn audio_only_playback_has_no_video() {
+        assert!(audio_only_args(false).is_empty());
+        assert!(audio_only_args(true).contains(&"--no-video".to_string()));
+    }
+
+    #[test]
+    fn subtitle_languages_reach_yt_dlp() {
+        let mut prefs = SubtitlePrefs {
+            languages: vec!["pt-BR".to_string(), "en".to_string()],
+            ..Default::default()
+        };
+        let args = subtitle_args(&prefs);
+        assert!(args.contains(&"--slang=pt-BR,en".to_string()));
+        assert!(args.contains(&"--ytdl-raw-options-append=sub-langs=pt-BR,en".to_string()));
+        assert!(args.contains(&"--ytdl-raw-options-append=write-auto-subs=".to_string()));
+
+        prefs.auto_captions = false;
+        assert!(!subtitle_args(&prefs)
+            .contains(&"--ytdl-raw-options-append=write-auto-subs=".to_string()));
+        prefs.languages.clear();
+        assert!(subtitle_args(&prefs).is_empty());
+    }
+
+    #[test]
+    fn playback_end_needs_a_saved_position_or_mpv_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let watch_later = dir.path().join("watch_later");
+        // Nothing saved and nothing reported: not counted as finished
+        let nothing = PlaybackReport::default();
+        assert_eq!(playback_end(&watch_later, &nothing), PlaybackEnd::Unknown);
+
+        let eof = PlaybackReport {
+            end_reason: Some("eof".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(playback_end(&watch_later, &eof), PlaybackEnd::Finished);
+
+        let near_end = PlaybackReport {
+            end_reason: Some("quit".to_string()),
+            position: Some(95.0),
+            duration: Some(100.0),
+        };
+        assert_eq!(playback_end(&watch_later, &near_end), PlaybackEnd::Finished);
+
+        let halfway = PlaybackReport {
+            position: Some(50.5),
+            ..near_end.clone()
+        };
+        assert_eq!(
+            playback_end(&watch_later, &halfway),
+            PlaybackEnd::StoppedAt(50)
+        );
+
+        // mpv's own saved position wins
+        fs::create_dir_all(&watch_later).unwrap();
+        fs::write(watch_later.join("0123ABCD"), "start=42.5\n").unwrap();
+        assert_eq!(
+            playback_end(&watch_later, &near_end),
+            PlaybackEnd::StoppedAt(42)
+        );
+        assert!(!watch_later.exists());
     }
-    send_log("Download completed successfully!");
-    Ok(())
 }
@@ -0,0 +1,309 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::youtube::Video;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum RepeatMode {
+    #[default]
+    Off,
+    // Start over when the end of the queue is reached
+    All,
+    // Play the current video again
+    One,
+}
+
+impl RepeatMode {
+    pub fn next(self) -> Self {
+        match self {
+            RepeatMode::Off => RepeatMode::All,
+            RepeatMode::All => RepeatMode::One,
+            RepeatMode::One => RepeatMode::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueItem {
+    pub video: Video,
+    // Already played in this round (shuffle picks from the rest)
+    pub played: bool,
+}
+
+// Videos to play one after another, saved as queue.json in the config directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayQueue {
+    pub items: Vec<QueueItem>,
+    // The item playing, or the last one played
+    pub current: Option<usize>,
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl PlayQueue {
+    // Load the queue at `path`; a missing or unreadable file starts empty
+    pub fn open(path: PathBuf) -> Self {
+        let mut queue: PlayQueue = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        if queue.current.is_some_and(|i| i >= queue.items.len()) {
+            queue.current = None;
+        }
+        queue.path = Some(path);
+        queue
+    }
+
+    pub fn open_default() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .or_else(|| dirs::home_dir().map(|d| d.join(".config")))
+            .context("Failed to find config directory")?
+            .join("rustyoutube");
+        Ok(Self::open(dir.join("queue.json")))
+    }
+
+    // Write the queue to disk (in-memory queues are not saved)
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?).context("Failed to save the queue")
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn current_video(&self) -> Option<&Video> {
+        self.items.get(self.current?).map(|item| &item.video)
+    }
+
+    // Add videos to the end, skipping ones already queued
+    pub fn enqueue(&mut self, videos: &[Video]) -> usize {
+        let mut added = 0;
+        for video in videos {
+            if !self.items.iter().any(|item| item.video.id == video.id) {
+                self.items.push(QueueItem {
+                    video: video.clone(),
+                    played: false,
+                });
+                added += 1;
+            }
+        }
+        added
+    }
+
+    // Replace the queue with these videos (e.g. a whole playlist) and start at `start`
+    pub fn replace(&mut self, videos: &[Video], start: usize) -> Option<Video> {
+        self.items.clear();
+        self.current = None;
+        self.enqueue(videos);
+        self.play_at(start)
+    }
+
+    // Jump to an item; it is returned for playing
+    pub fn play_at(&mut self, index: usize) -> Option<Video> {
+        let item = self.items.get_mut(index)?;
+        item.played = true;
+        self.current = Some(index);
+        Some(item.video.clone())
+    }
+
+    // The video to play after the current one finished, following the shuffle
+    // and repeat settings. None at the end of the queue.
+    pub fn advance(&mut self) -> Option<Video> {
+        if self.repeat == RepeatMode::One {
+            if let Some(index) = self.current {
+                return self.play_at(index);
+            }
+        }
+
+        let next = if self.shuffle {
+            self.random_unplayed()
+        } else {
+            self.current
+                .map_or(Some(0), |i| Some(i + 1))
+                .filter(|i| *i < self.items.len())
+        };
+        match next {
+            Some(index) => self.play_at(index),
+            None if self.repeat == RepeatMode::All && !self.items.is_empty() => {
+                // Start a new round
+                for item in self.items.iter_mut() {
+                    item.played = false;
+                }
+                let index = if self.shuffle {
+                    self.random_unplayed().unwrap_or(0)
+                } else {
+                    0
+                };
+                self.play_at(index)
+            }
+            None => None,
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index >= self.items.len() {
+            return;
+        }
+        self.items.remove(index);
+        self.current = match self.current {
+            // Step back so the item after the removed one plays next
+            Some(current) if current == index => index.checked_sub(1),
+            Some(current) if current > index => Some(current - 1),
+            other => other,
+        };
+    }
+
+    // Swap an item with its neighbour; returns its new index
+    pub fn move_up(&mut self, index: usize) -> usize {
+        if index == 0 || index >= self.items.len() {
+            return index;
+        }
+        self.swap(index, index - 1);
+        index - 1
+    }
+
+    pub fn move_down(&mut self, index: usize) -> usize {
+        if index + 1 >= self.items.len() {
+            return index;
+        }
+        self.swap(index, index + 1);
+        index + 1
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.items.swap(a, b);
+        self.current = match self.current {
+            Some(current) if current == a => Some(b),
+            Some(current) if current == b => Some(a),
+            other => other,
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.current = None;
+    }
+
+    // Helper: A random item not played yet in this round
+    fn random_unplayed(&self) -> Option<usize> {
+        let unplayed: Vec<usize> = (0..self.items.len())
+            .filter(|i| !self.items[*i].played)
+            .collect();
+        if unplayed.is_empty() {
+            return None;
+        }
+        // Good enough for picking a song; no need for a random number crate
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as usize ^ d.as_secs() as usize)
+            .unwrap_or(0);
+        Some(unplayed[seed % unplayed.len()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn videos(ids: &[&str]) -> Vec<Video> {
+        ids.iter()
+            .map(|id| Video {
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn ids(queue: &PlayQueue) -> Vec<&str> {
+        queue
+            .items
+            .iter()
+            .map(|item| item.video.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn advances_in_order_and_repeats() {
+        let mut queue = PlayQueue::default();
+        assert_eq!(queue.replace(&videos(&["a", "b"]), 0).unwrap().id, "a");
+        assert_eq!(queue.advance().unwrap().id, "b");
+        assert!(queue.advance().is_none());
+
+        queue.repeat = RepeatMode::All;
+        assert_eq!(queue.advance().unwrap().id, "a");
+
+        queue.repeat = RepeatMode::One;
+        assert_eq!(queue.advance().unwrap().id, "a");
+    }
+
+    #[test]
+    fn shuffle_plays_every_item_once_per_round() {
+        let mut queue = PlayQueue {
+            shuffle: true,
+            ..Default::default()
+        };
+        queue.replace(&videos(&["a", "b", "c", "d"]), 0);
+        let mut played = vec![queue.current_video().unwrap().id.clone()];
+        while let Some(video) = queue.advance() {
+            played.push(video.id);
+        }
+        played.sort();
+        assert_eq!(played, ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn reordering_and_removing_keep_the_current_item() {
+        let mut queue = PlayQueue::default();
+        queue.enqueue(&videos(&["a", "b", "c"]));
+        // Already queued
+        assert_eq!(queue.enqueue(&videos(&["b"])), 0);
+        queue.play_at(1);
+
+        assert_eq!(queue.move_up(1), 0);
+        assert_eq!(ids(&queue), ["b", "a", "c"]);
+        assert_eq!(queue.current_video().unwrap().id, "b");
+
+        queue.remove(1);
+        assert_eq!(ids(&queue), ["b", "c"]);
+        assert_eq!(queue.current, Some(0));
+        assert_eq!(queue.advance().unwrap().id, "c");
+
+        queue.enqueue(&videos(&["d"]));
+        queue.remove(1);
+        assert_eq!(ids(&queue), ["b", "d"]);
+        assert_eq!(queue.current, Some(0));
+        assert_eq!(queue.advance().unwrap().id, "d");
+    }
+
+    #[test]
+    fn queue_is_saved_and_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.json");
+        let mut queue = PlayQueue::open(path.clone());
+        queue.replace(&videos(&["a", "b"]), 1);
+        queue.repeat = RepeatMode::All;
+        queue.save().unwrap();
+
+        let reopened = PlayQueue::open(path);
+        assert_eq!(ids(&reopened), ["a", "b"]);
+        assert_eq!(reopened.current, Some(1));
+        assert_eq!(reopened.repeat, RepeatMode::All);
+    }
+}
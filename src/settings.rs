@@ -0,0 +1,404 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::sponsorblock::DEFAULT_API_URL;
+
+// User preferences, saved as settings.json in the config directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Play only the audio track unless asked otherwise
+    pub audio_only: bool,
+    // Which streams to play and download
+    pub quality: QualityPrefs,
+    pub downloads: DownloadPrefs,
+    pub subtitles: SubtitlePrefs,
+    pub sponsorblock: SponsorBlockPrefs,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+// Video quality preferences, turned into a yt-dlp format selector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualityPrefs {
+    // Tallest video to pick (None: no limit)
+    pub max_height: Option<u32>,
+    // Video codecs to try first, best first ("av01", "vp09", "avc1", ...)
+    pub codecs: Vec<String>,
+    // Allow HDR streams; SDR ones are picked otherwise
+    pub hdr: bool,
+    // Highest frame rate to pick (None: no limit)
+    pub max_fps: Option<u32>,
+    // Container downloads are merged into, e.g. "mkv" or "mp4" (None: yt-dlp decides)
+    pub container: Option<String>,
+}
+
+impl Default for QualityPrefs {
+    fn default() -> Self {
+        Self {
+            max_height: Some(1080),
+            codecs: vec!["av01".to_string(), "vp09".to_string()],
+            hdr: true,
+            max_fps: None,
+            container: None,
+        }
+    }
+}
+
+impl QualityPrefs {
+    // yt-dlp format selector: each preferred codec in turn (separate video and
+    // audio streams, then a combined file), then anything within the limits
+    pub fn format_selector(&self) -> String {
+        let mut filters = String::new();
+        if let Some(height) = self.max_height {
+            filters.push_str(&format!("[height<={}]", height));
+        }
+        if let Some(fps) = self.max_fps {
+            filters.push_str(&format!("[fps<=?{}]", fps));
+        }
+        if !self.hdr {
+            filters.push_str("[dynamic_range!^=?HDR]");
+        }
+
+        let mut choices: Vec<String> = self
+            .codecs
+            .iter()
+            .flat_map(|codec| {
+                [
+                    format!("bestvideo[vcodec^={}]{}+bestaudio", codec, filters),
+                    format!("best[vcodec^={}]{}", codec, filters),
+                ]
+            })
+            .collect();
+        if self.codecs.is_empty() {
+            choices.push(format!("bestvideo{}+bestaudio", filters));
+        }
+        choices.push(format!("best{}", filters));
+        choices.join("/")
+    }
+}
+
+// How downloads are run and where they are saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadPrefs {
+    // Downloads running at the same time
+    pub concurrency: usize,
+    // Folder downloads are saved under (None: the system's videos folder)
+    pub directory: Option<PathBuf>,
+    // File names inside that folder
+    pub templates: DownloadTemplates,
+    // Audio-only downloads
+    pub audio: AudioPrefs,
+}
+
+impl Default for DownloadPrefs {
+    fn default() -> Self {
+        Self {
+            concurrency: 2,
+            directory: None,
+            templates: DownloadTemplates::default(),
+            audio: AudioPrefs::default(),
+        }
+    }
+}
+
+impl DownloadPrefs {
+    // The download folder: the configured one, else the videos folder (XDG_VIDEOS_DIR
+    // on Linux), else ~/Videos
+    pub fn directory(&self) -> PathBuf {
+        self.directory
+            .clone()
+            .or_else(dirs::video_dir)
+            .or_else(|| dirs::home_dir().map(|d| d.join("Videos")))
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+// File name templates by where a download was started. Placeholders: {title},
+// {id}, {channel}, {playlist}, {index}, {upload_date} and {ext}; a "/" starts
+// a subfolder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadTemplates {
+    // A single video
+    pub video: String,
+    // A video from a playlist
+    pub playlist: String,
+    // A video from a channel's page
+    pub channel: String,
+}
+
+impl Default for DownloadTemplates {
+    fn default() -> Self {
+        Self {
+            video: "{channel}/{title} [{id}].{ext}".to_string(),
+            playlist: "{playlist}/{index} - {title} [{id}].{ext}".to_string(),
+            channel: "{channel}/{upload_date} - {title} [{id}].{ext}".to_string(),
+        }
+    }
+}
+
+// Audio files extracted from videos (needs ffmpeg)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioPrefs {
+    // "mp3", "opus" or "m4a" (anything yt-dlp's --audio-format takes works)
+    pub codec: String,
+    // kbit/s (None: the best variable bitrate)
+    pub bitrate: Option<u32>,
+}
+
+impl Default for AudioPrefs {
+    fn default() -> Self {
+        Self {
+            codec: "mp3".to_string(),
+            bitrate: Some(192),
+        }
+    }
+}
+
+impl AudioPrefs {
+    // File extension of the extracted audio
+    pub fn extension(&self) -> &str {
+        match self.codec.as_str() {
+            "vorbis" => "ogg",
+            "aac" | "alac" => "m4a",
+            codec => codec,
+        }
+    }
+}
+
+// Subtitles shown while playing and saved with downloads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubtitlePrefs {
+    // Language codes to pick, most wanted first ("en", "pt-BR", ...). Empty: let
+    // mpv choose.
+    pub languages: Vec<String>,
+    // Use YouTube's automatic captions when a language has no uploaded subtitles
+    pub auto_captions: bool,
+    // Subtitles in downloads
+    pub download: SubtitleMode,
+}
+
+impl Default for SubtitlePrefs {
+    fn default() -> Self {
+        Self {
+            languages: vec!["en".to_string()],
+            auto_captions: true,
+            download: SubtitleMode::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleMode {
+    #[default]
+    Off,
+    // Inside the video file
+    Embed,
+    // Separate files next to the video (.vtt)
+    Sidecar,
+}
+
+// SponsorBlock: community-submitted segments (sponsor reads, intros, ...) to
+// skip or mark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SponsorBlockPrefs {
+    pub enabled: bool,
+    // SponsorBlock server (the public one unless set)
+    pub api_url: String,
+    // What to do with each category; categories left out are ignored
+    pub categories: BTreeMap<String, SegmentAction>,
+    // Cut skipped segments out of downloads and add marked ones as chapters
+    pub apply_to_downloads: bool,
+}
+
+impl Default for SponsorBlockPrefs {
+    fn default() -> Self {
+        let categories = [
+            ("sponsor", SegmentAction::Skip),
+            ("selfpromo", SegmentAction::Skip),
+            ("intro", SegmentAction::Skip),
+            ("outro", SegmentAction::Skip),
+            ("interaction", SegmentAction::Mark),
+            ("preview", SegmentAction::Ignore),
+            ("music_offtopic", SegmentAction::Ignore),
+            ("filler", SegmentAction::Ignore),
+        ];
+        Self {
+            enabled: true,
+            api_url: DEFAULT_API_URL.to_string(),
+            categories: categories
+                .into_iter()
+                .map(|(category, action)| (category.to_string(), action))
+                .collect(),
+            apply_to_downloads: false,
+        }
+    }
+}
+
+impl SponsorBlockPrefs {
+    pub fn action(&self, category: &str) -> SegmentAction {
+        self.categories
+            .get(category)
+            .copied()
+            .unwrap_or(SegmentAction::Ignore)
+    }
+
+    // Categories with this action, in alphabetical order
+    pub fn categories_with(&self, action: SegmentAction) -> Vec<String> {
+        self.categories
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(category, _)| category.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SegmentAction {
+    // Jump over the segment
+    Skip,
+    // Show it as a chapter
+    Mark,
+    #[default]
+    Ignore,
+}
+
+impl Settings {
+    // Load the settings at `path`; a missing or unreadable file gives the defaults
+    pub fn open(path: PathBuf) -> Self {
+        let mut settings: Settings = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        settings.path = Some(path);
+        settings
+    }
+
+    pub fn open_default() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .or_else(|| dirs::home_dir().map(|d| d.join(".config")))
+            .context("Failed to find config directory")?
+            .join("rustyoutube");
+        Ok(Self::open(dir.join("settings.json")))
+    }
+
+    // Write the settings to disk (in-memory settings are not saved)
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?).context("Failed to save the settings")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_saved_and_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        assert!(!Settings::open(path.clone()).audio_only);
+
+        let mut settings = Settings::open(path.clone());
+        settings.audio_only = true;
+        settings.save().unwrap();
+        assert!(Settings::open(path).audio_only);
+    }
+
+    #[test]
+    fn unknown_and_missing_fields_use_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(
+            &path,
+            r#"{"from_a_newer_version": 1, "quality": {"max_height": 720}}"#,
+        )
+        .unwrap();
+        let settings = Settings::open(path);
+        assert!(!settings.audio_only);
+        assert_eq!(settings.quality.max_height, Some(720));
+        assert_eq!(settings.quality.codecs, ["av01", "vp09"]);
+        assert_eq!(settings.downloads.concurrency, 2);
+        assert_eq!(settings.downloads.templates, DownloadTemplates::default());
+        assert_eq!(settings.downloads.audio.codec, "mp3");
+        assert_eq!(settings.subtitles.download, SubtitleMode::Off);
+    }
+
+    #[test]
+    fn subtitle_modes_are_lowercase_in_json() {
+        let prefs: SubtitlePrefs =
+            serde_json::from_str(r#"{"languages": ["de", "en"], "download": "sidecar"}"#).unwrap();
+        assert_eq!(prefs.languages, ["de", "en"]);
+        assert!(prefs.auto_captions);
+        assert_eq!(prefs.download, SubtitleMode::Sidecar);
+    }
+
+    #[test]
+    fn sponsorblock_categories_are_configurable() {
+        let prefs: SponsorBlockPrefs = serde_json::from_str(
+            r#"{"categories": {"sponsor": "skip", "intro": "mark", "outro": "ignore"}}"#,
+        )
+        .unwrap();
+        assert!(prefs.enabled);
+        assert_eq!(prefs.action("intro"), SegmentAction::Mark);
+        assert_eq!(prefs.action("selfpromo"), SegmentAction::Ignore);
+        assert_eq!(prefs.categories_with(SegmentAction::Skip), ["sponsor"]);
+
+        let defaults = SponsorBlockPrefs::default();
+        assert_eq!(
+            defaults.categories_with(SegmentAction::Skip),
+            ["intro", "outro", "selfpromo", "sponsor"]
+        );
+    }
+
+    #[test]
+    fn configured_download_directory_wins() {
+        let prefs = DownloadPrefs {
+            directory: Some(PathBuf::from("/media/videos")),
+            ..Default::default()
+        };
+        assert_eq!(prefs.directory(), PathBuf::from("/media/videos"));
+        assert_ne!(DownloadPrefs::default().directory(), PathBuf::new());
+    }
+
+    #[test]
+    fn default_quality_prefers_av1_then_vp9_up_to_1080p() {
+        assert_eq!(
+            QualityPrefs::default().format_selector(),
+            "bestvideo[vcodec^=av01][height<=1080]+bestaudio/best[vcodec^=av01][height<=1080]/\
+             bestvideo[vcodec^=vp09][height<=1080]+bestaudio/best[vcodec^=vp09][height<=1080]/\
+             best[height<=1080]"
+        );
+    }
+
+    #[test]
+    fn quality_limits_become_filters() {
+        let prefs = QualityPrefs {
+            max_height: None,
+            codecs: Vec::new(),
+            hdr: false,
+            max_fps: Some(30),
+            container: Some("mkv".to_string()),
+        };
+        assert_eq!(
+            prefs.format_selector(),
+            "bestvideo[fps<=?30][dynamic_range!^=?HDR]+bestaudio/best[fps<=?30][dynamic_range!^=?HDR]"
+        );
+    }
+}
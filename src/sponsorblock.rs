@@ -0,0 +1,319 @@
+use anyhow::{Context, Result};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+use crate::player::{MpvController, MpvEvent};
+use crate::settings::{SegmentAction, SponsorBlockPrefs};
+
+pub const DEFAULT_API_URL: &str = "https://sponsor.ajay.app";
+
+// How long mpv shows "Skipped: ..." on screen (ms)
+const SKIP_MESSAGE_DURATION: u64 = 2000;
+
+// A part of a video submitted to SponsorBlock, e.g. a sponsor read
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    // "sponsor", "intro", "outro", "selfpromo", ...
+    pub category: String,
+    // Seconds
+    pub start: f64,
+    pub end: f64,
+    pub uuid: String,
+}
+
+// Client for the SponsorBlock API (https://wiki.sponsor.ajay.app/w/API_Docs)
+#[derive(Clone)]
+pub struct SponsorBlockClient {
+    client: Client,
+    base_url: String,
+}
+
+impl SponsorBlockClient {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            base_url: DEFAULT_API_URL.to_string(),
+        }
+    }
+
+    // Point the client at another server (a mirror, or a local test server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    // A video's segments in these categories, in playback order. A video
+    // nobody submitted segments for has none.
+    pub async fn segments(&self, video_id: &str, categories: &[String]) -> Result<Vec<Segment>> {
+        let response = self
+            .client
+            .get(format!("{}/api/skipSegments", self.base_url))
+            .query(&[
+                ("videoID", video_id.to_string()),
+                ("categories", json!(categories).to_string()),
+            ])
+            .send()
+            .await
+            .context("Failed to reach SponsorBlock")?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to load SponsorBlock segments for {} ({})",
+                video_id,
+                response.status()
+            ));
+        }
+
+        let body = response.text().await?;
+        parse_segments(&body)
+    }
+}
+
+// Parse a skipSegments answer; empty and backwards segments are left out
+pub fn parse_segments(body: &str) -> Result<Vec<Segment>> {
+    let items: Vec<ApiSegment> =
+        serde_json::from_str(body).context("Failed to parse SponsorBlock segments")?;
+    let mut segments: Vec<Segment> = items
+        .into_iter()
+        .filter(|item| item.segment[1] > item.segment[0])
+        .map(|item| Segment {
+            category: item.category,
+            start: item.segment[0],
+            end: item.segment[1],
+            uuid: item.uuid,
+        })
+        .collect();
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(segments)
+}
+
+// Name shown for a category
+pub fn category_name(category: &str) -> &str {
+    match category {
+        "sponsor" => "Sponsor",
+        "selfpromo" => "Self-promotion",
+        "intro" => "Intro",
+        "outro" => "Outro",
+        "interaction" => "Interaction reminder",
+        "preview" => "Preview",
+        "music_offtopic" => "Non-music section",
+        "filler" => "Filler",
+        other => other,
+    }
+}
+
+// A chapter as mpv's chapter-list property holds it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    #[serde(default)]
+    pub title: String,
+    pub time: f64,
+}
+
+// Skip and mark segments while mpv plays, until the file ends. Skip segments
+// are jumped over once each, so seeking back into one plays it; mark segments
+// are added to the video's chapters once it is loaded.
+pub async fn follow_playback(
+    controller: &MpvController,
+    segments: &[Segment],
+    prefs: &SponsorBlockPrefs,
+    log_tx: Option<mpsc::UnboundedSender<String>>,
+) -> Result<()> {
+    let send_log = |msg: String| {
+        if let Some(ref tx) = log_tx {
+            let _ = tx.send(msg);
+        }
+    };
+    let to_skip: Vec<&Segment> = segments
+        .iter()
+        .filter(|s| prefs.action(&s.category) == SegmentAction::Skip)
+        .collect();
+    let to_mark: Vec<&Segment> = segments
+        .iter()
+        .filter(|s| prefs.action(&s.category) == SegmentAction::Mark)
+        .collect();
+
+    let mut events = controller.events();
+    controller.observe("duration").await?;
+    if !to_skip.is_empty() {
+        controller.observe("time-pos").await?;
+    }
+
+    let mut skipped: HashSet<String> = HashSet::new();
+    let mut marked = to_mark.is_empty();
+    loop {
+        match events.recv().await {
+            Ok(MpvEvent::PropertyChange { name, data }) if name == "time-pos" => {
+                let Some(position) = data.as_f64() else {
+                    continue;
+                };
+                let Some(segment) = segment_to_skip(&to_skip, position, &skipped) else {
+                    continue;
+                };
+                skipped.insert(segment.uuid.clone());
+                controller.seek_to(segment.end).await?;
+                let message = format!("Skipped: {}", category_name(&segment.category));
+                let _ = controller
+                    .command(vec![
+                        json!("show-text"),
+                        json!(message),
+                        json!(SKIP_MESSAGE_DURATION),
+                    ])
+                    .await;
+                send_log(format!(
+                    "SponsorBlock: {} ({:.0}s)",
+                    message,
+                    segment.end - segment.start
+                ));
+            }
+            // Known once the file is loaded, along with its own chapters
+            Ok(MpvEvent::PropertyChange { name, data }) if name == "duration" => {
+                if marked || data.is_null() {
+                    continue;
+                }
+                marked = true;
+                let existing: Vec<Chapter> = controller
+                    .get_property("chapter-list")
+                    .await
+                    .ok()
+                    .and_then(|list| serde_json::from_value(list).ok())
+                    .unwrap_or_default();
+                let chapters = merge_chapters(existing, &to_mark);
+                controller
+                    .set_property("chapter-list", json!(chapters))
+                    .await?;
+                send_log(format!(
+                    "SponsorBlock: marked {} segment(s) as chapters",
+                    to_mark.len()
+                ));
+            }
+            Ok(MpvEvent::EndFile { .. }) | Ok(MpvEvent::Shutdown) | Err(RecvError::Closed) => {
+                return Ok(())
+            }
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+        }
+    }
+}
+
+// Helper: The skip segment playing at `position` that hasn't been skipped yet.
+// The last half second is left alone, so landing just short of the end after a
+// skip doesn't count.
+fn segment_to_skip<'a>(
+    segments: &[&'a Segment],
+    position: f64,
+    skipped: &HashSet<String>,
+) -> Option<&'a Segment> {
+    segments
+        .iter()
+        .find(|s| position >= s.start && position < s.end - 0.5 && !skipped.contains(&s.uuid))
+        .copied()
+}
+
+// Helper: The video's chapters with a chapter for each marked segment. Where a
+// segment ends, the chapter it interrupted picks up again.
+fn merge_chapters(existing: Vec<Chapter>, segments: &[&Segment]) -> Vec<Chapter> {
+    let mut chapters = existing.clone();
+    for segment in segments {
+        chapters.push(Chapter {
+            title: format!("[SponsorBlock] {}", category_name(&segment.category)),
+            time: segment.start,
+        });
+        let resumed = existing
+            .iter()
+            .rfind(|chapter| chapter.time <= segment.end)
+            .map(|chapter| chapter.title.clone())
+            .unwrap_or_default();
+        chapters.push(Chapter {
+            title: resumed,
+            time: segment.end,
+        });
+    }
+    chapters.sort_by(|a, b| a.time.total_cmp(&b.time));
+    chapters
+}
+
+#[derive(Deserialize)]
+struct ApiSegment {
+    category: String,
+    // [start, end] in seconds
+    segment: [f64; 2],
+    #[serde(rename = "UUID", default)]
+    uuid: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(category: &str, start: f64, end: f64) -> Segment {
+        Segment {
+            category: category.to_string(),
+            start,
+            end,
+            uuid: format!("{}-{}", category, start),
+        }
+    }
+
+    #[test]
+    fn segments_are_parsed_in_order() {
+        let body = r#"[
+            {"category": "outro", "actionType": "skip", "segment": [590.0, 612.5], "UUID": "b"},
+            {"category": "sponsor", "actionType": "skip", "segment": [30.2, 75.9], "UUID": "a"},
+            {"category": "intro", "actionType": "skip", "segment": [10.0, 10.0], "UUID": "c"}
+        ]"#;
+        let segments = parse_segments(body).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].category, "sponsor");
+        assert_eq!((segments[0].start, segments[0].end), (30.2, 75.9));
+        assert_eq!(segments[1].uuid, "b");
+    }
+
+    #[test]
+    fn segments_are_skipped_once() {
+        let sponsor = segment("sponsor", 30.0, 60.0);
+        let segments = [&sponsor];
+        let mut skipped = HashSet::new();
+        assert_eq!(segment_to_skip(&segments, 29.9, &skipped), None);
+        assert_eq!(segment_to_skip(&segments, 30.0, &skipped), Some(&sponsor));
+        // Just short of the end after seeking there
+        assert_eq!(segment_to_skip(&segments, 59.8, &skipped), None);
+
+        skipped.insert(sponsor.uuid.clone());
+        assert_eq!(segment_to_skip(&segments, 45.0, &skipped), None);
+    }
+
+    #[test]
+    fn marked_segments_become_chapters() {
+        let existing = vec![
+            Chapter {
+                title: "Intro".to_string(),
+                time: 0.0,
+            },
+            Chapter {
+                title: "Review".to_string(),
+                time: 60.0,
+            },
+        ];
+        let reminder = segment("interaction", 90.0, 100.0);
+        let chapters = merge_chapters(existing, &[&reminder]);
+        let titles: Vec<(&str, f64)> = chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.time))
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                ("Intro", 0.0),
+                ("Review", 60.0),
+                ("[SponsorBlock] Interaction reminder", 90.0),
+                ("Review", 100.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::auth::{account_token_path, TokenData};
+
+// Keyring service name all account entries are stored under
+const KEYRING_SERVICE: &str = "rustyoutube";
+
+// Set to "file" to keep tokens out of the system keyring
+pub const STORE_ENV: &str = "RUSTYOUTUBE_TOKEN_STORE";
+// When set, tokens are kept in a file encrypted with this passphrase
+pub const PASSPHRASE_ENV: &str = "RUSTYOUTUBE_TOKEN_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+
+// The token file (or its keyring entry) can't be read back as a token. Unlike
+// a missing passphrase or a locked keyring, retrying won't help.
+#[derive(Debug)]
+pub struct CorruptToken;
+
+impl std::fmt::Display for CorruptToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The saved token is corrupt")
+    }
+}
+
+impl std::error::Error for CorruptToken {}
+
+// The token file points at a keyring entry that no longer exists
+#[derive(Debug)]
+pub struct MissingToken;
+
+impl std::fmt::Display for MissingToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The saved token is no longer in the system keyring")
+    }
+}
+
+impl std::error::Error for MissingToken {}
+
+// What the token file holds. A signed-in account always has one, even when
+// the token itself lives in the keyring, so accounts can still be listed.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum TokenFile {
+    Keyring {
+        keyring_user: String,
+    },
+    Encrypted {
+        salt: String,
+        nonce: String,
+        ciphertext: String,
+    },
+    // Written by older versions as plain pretty JSON; migrated on load
+    Plain(TokenData),
+}
+
+// Where an account's token is kept: the system keyring when one is available,
+// otherwise a private file (optionally encrypted with a passphrase)
+pub struct TokenStore {
+    path: PathBuf,
+    keyring_user: Option<String>,
+    passphrase: Option<String>,
+}
+
+impl TokenStore {
+    // Plain file only; used by tests and as the base for the other options
+    pub fn file(path: PathBuf) -> Self {
+        Self {
+            path,
+            keyring_user: None,
+            passphrase: None,
+        }
+    }
+
+    // Store for a named account, configured from the environment
+    pub fn for_account(account: &str) -> Result<Self> {
+        let mut store = Self::file(account_token_path(account)?);
+
+        let use_keyring = std::env::var(STORE_ENV)
+            .map(|value| !value.eq_ignore_ascii_case("file"))
+            .unwrap_or(true);
+        if use_keyring {
+            store = store.with_keyring(account);
+        }
+
+        if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+            if !passphrase.is_empty() {
+                store = store.with_passphrase(passphrase);
+            }
+        }
+
+        Ok(store)
+    }
+
+    // Prefer the system keyring, under this user name
+    pub fn with_keyring(mut self, user: impl Into<String>) -> Self {
+        self.keyring_user = Some(user.into());
+        self
+    }
+
+    // Encrypt the token file; takes precedence over the keyring
+    pub fn with_passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub async fn load(&self) -> Result<TokenData> {
+        let content = fs::read_to_string(&self.path).context("Failed to read token file")?;
+        let file: TokenFile = serde_json::from_str(&content).context(CorruptToken)?;
+
+        match file {
+            TokenFile::Keyring { keyring_user } => {
+                let secret = keyring_get(keyring_user)
+                    .await
+                    .context("Failed to read the saved token from the system keyring")?
+                    .ok_or(MissingToken)?;
+                serde_json::from_str(&secret).context(CorruptToken)
+            }
+            TokenFile::Encrypted {
+                salt,
+                nonce,
+                ciphertext,
+            } => {
+                let passphrase = self.passphrase.as_deref().with_context(|| {
+                    format!(
+                        "The saved token is encrypted. Set {} to unlock it.",
+                        PASSPHRASE_ENV
+                    )
+                })?;
+                let plaintext = decrypt(passphrase, &salt, &nonce, &ciphertext)?;
+                serde_json::from_slice(&plaintext).context(CorruptToken)
+            }
+            TokenFile::Plain(token) => {
+                // Move plaintext tokens to the preferred storage
+                if self.keyring_user.is_some() || self.passphrase.is_some() {
+                    self.save(&token).await?;
+                } else {
+                    restrict_permissions(&self.path)?;
+                }
+                Ok(token)
+            }
+        }
+    }
+
+    pub async fn save(&self, token: &TokenData) -> Result<()> {
+        let secret = serde_json::to_string_pretty(token)?;
+        let previous_keyring_user = self.keyring_user_on_disk();
+
+        let file = if let Some(passphrase) = &self.passphrase {
+            encrypt(passphrase, secret.as_bytes())?
+        } else if let Some(user) = &self.keyring_user {
+            match keyring_set(user.clone(), secret).await {
+                Ok(()) => TokenFile::Keyring {
+                    keyring_user: user.clone(),
+                },
+                // No usable keyring (e.g. no Secret Service running): keep a private file
+                Err(_) => TokenFile::Plain(token.clone()),
+            }
+        } else {
+            TokenFile::Plain(token.clone())
+        };
+
+        write_private(&self.path, serde_json::to_string_pretty(&file)?.as_bytes())?;
+
+        // Don't leave a stale copy behind when the token moved out of the keyring
+        if let Some(previous) = previous_keyring_user {
+            if !matches!(&file, TokenFile::Keyring { keyring_user } if *keyring_user == previous) {
+                keyring_delete(previous).await.ok();
+            }
+        }
+
+        Ok(())
+    }
+
+    // Forget the token everywhere it is stored
+    pub async fn delete(&self) -> Result<()> {
+        if let Some(user) = self.keyring_user_on_disk() {
+            keyring_delete(user)
+                .await
+                .context("Failed to delete the saved token from the system keyring")?;
+        }
+
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).context("Failed to delete saved token"),
+        }
+    }
+
+    // Helper: keyring entry the token file currently points at, if any
+    fn keyring_user_on_disk(&self) -> Option<String> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        match serde_json::from_str(&content).ok()? {
+            TokenFile::Keyring { keyring_user } => Some(keyring_user),
+            _ => None,
+        }
+    }
+}
+
+// Helper: the keyring crate blocks, so keep it off the async runtime threads
+async fn keyring_get(user: String) -> Result<Option<String>> {
+    tokio::task::spawn_blocking(move || {
+        match keyring::Entry::new(KEYRING_SERVICE, &user)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    })
+    .await?
+}
+
+async fn keyring_set(user: String, secret: String) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        keyring::Entry::new(KEYRING_SERVICE, &user)?
+            .set_password(&secret)
+            .map_err(anyhow::Error::from)
+    })
+    .await?
+}
+
+async fn keyring_delete(user: String) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        match keyring::Entry::new(KEYRING_SERVICE, &user)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    })
+    .await?
+}
+
+// Helper: derive the file key from the passphrase (Argon2id)
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to derive encryption key: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<TokenFile> {
+    let mut salt = [0u8; SALT_LEN];
+    chacha20poly1305::aead::rand_core::RngCore::fill_bytes(&mut OsRng, &mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt token"))?;
+
+    Ok(TokenFile::Encrypted {
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt(passphrase: &str, salt: &str, nonce: &str, ciphertext: &str) -> Result<Vec<u8>> {
+    let salt = BASE64.decode(salt).context(CorruptToken)?;
+    let nonce = BASE64.decode(nonce).context(CorruptToken)?;
+    let ciphertext = BASE64.decode(ciphertext).context(CorruptToken)?;
+    if nonce.len() != 12 {
+        return Err(CorruptToken.into());
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt the saved token. Is the passphrase right?"))
+}
+
+// Helper: write a file only the current user can read (0600 on Unix)
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    file.write_all(contents)?;
+
+    // The mode above only applies to new files
+    restrict_permissions(path)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("Failed to restrict permissions on {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
@@ -1,7 +1,21 @@
+use crate::auth::{is_corrupt_token, list_accounts, AuthClient, RevokeOutcome};
+use crate::cache::{MetadataCache, ANONYMOUS_CACHE};
+use crate::deps;
+use crate::downloads::{
+    output_template, CompletedDownload, DownloadJob, DownloadManager, DownloadProgress,
+    DownloadTarget, ItemState, JobState, TemplateValues,
+};
+use crate::history::HistoryEntry;
 use crate::i18n::{t, t_with_args};
-use crate::player::{download_video, play_video};
-use crate::youtube::{Playlist, Subscription, Video, YouTubeClient};
+use crate::player::{
+    play_video, DownloadOptions, MpvController, MpvEvent, PlayOptions, PlaybackEnd,
+};
+use crate::queue::{PlayQueue, RepeatMode};
+use crate::settings::{Settings, SubtitlePrefs};
+use crate::youtube::{Playlist, Rating, Subscription, Video, YouTubeClient};
+use crate::ytdlp::{list_captions, list_formats, CaptionTrack, VideoFormat};
 use anyhow::Result;
+use chrono::NaiveDate;
 use crossterm::event::{
     self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
 };
@@ -10,24 +24,27 @@ use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
 use ratatui::Frame;
+use std::collections::HashMap;
 use std::io;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::process::Child;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
 #[derive(Clone, Copy, PartialEq)]
 enum ViewMode {
     MainMenu,
     Recommendations,
+    Feed,
     Search,
     History,
     Subscriptions,
+    // Replaced by the channel tabs; nothing switches to it any more
+    #[allow(dead_code)]
     ChannelMenu,
     SubscriptionVideos,
     SubscriptionShorts,
@@ -37,9 +54,248 @@ enum ViewMode {
     ChannelInput,
     ChannelVideos,
     SearchResults,
+    Accounts,
+    PlaylistPicker,
+    Queue,
+    FormatPicker,
+    Captions,
+    Downloads,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MenuItem {
+    Recommendations,
+    Search,
+    History,
+    Queue,
+    Downloads,
+    Subscriptions,
+    Feed,
+    Playlists,
+    Channel,
+    Accounts,
+    SignOut,
+    Quit,
+}
+
+// Actions that wait for a y/n answer in the status bar
+#[derive(Clone, PartialEq)]
+enum PendingAction {
+    SignOut,
+    // The token couldn't be read back to revoke it; delete it locally anyway
+    ForgetCorruptToken,
+    Subscribe {
+        channel_id: String,
+        channel_title: String,
+    },
+    Unsubscribe {
+        channel_id: String,
+        channel_title: String,
+    },
+    Like {
+        video_id: String,
+        title: String,
+    },
+    AddToPlaylist {
+        playlist_id: String,
+        playlist_title: String,
+        video_id: String,
+        title: String,
+    },
+    RemoveFromPlaylist {
+        playlist_item_id: String,
+        title: String,
+    },
+    ClearHistoryDays {
+        from: NaiveDate,
+        to: NaiveDate,
+    },
+    ClearHistory,
+    ClearQueue,
+}
+
+// What mpv reports about the video it is playing
+struct NowPlaying {
+    controller: Arc<MpvController>,
+    events: broadcast::Receiver<MpvEvent>,
+    title: String,
+    position: Option<f64>,
+    duration: Option<f64>,
+    paused: bool,
+    volume: Option<f64>,
+    speed: Option<f64>,
+}
+
+impl NowPlaying {
+    fn new(controller: Arc<MpvController>) -> Self {
+        Self {
+            events: controller.events(),
+            controller,
+            title: String::new(),
+            position: None,
+            duration: None,
+            paused: false,
+            volume: None,
+            speed: None,
+        }
+    }
+
+    // Helper: Apply an observed property change
+    fn update(&mut self, name: &str, data: &serde_json::Value) {
+        match name {
+            "media-title" => self.title = data.as_str().unwrap_or_default().to_string(),
+            "time-pos" => self.position = data.as_f64(),
+            "duration" => self.duration = data.as_f64(),
+            "pause" => self.paused = data.as_bool().unwrap_or(false),
+            "volume" => self.volume = data.as_f64(),
+            "speed" => self.speed = data.as_f64(),
+            _ => {}
+        }
+    }
+}
+
+// Helper: Record the video in the history and play it in the background. Where
+// playback ended (None on errors) is sent on `done_tx` with the video ID.
+fn spawn_playback(
+    youtube_client: YouTubeClient,
+    video: Video,
+    options: PlayOptions,
+    log_tx: mpsc::UnboundedSender<String>,
+    done_tx: mpsc::UnboundedSender<(String, Option<PlaybackEnd>)>,
+) {
+    tokio::spawn(async move {
+        // Add to history (ignore errors)
+        let _ = youtube_client.add_to_history(&video).await;
+        // Play video, then remember where it stopped
+        let end = match play_video(&video.id, &options, Some(log_tx.clone())).await {
+            Ok(end) => Some(end),
+            Err(e) => {
+                let _ = log_tx.send(format!("Error: {}", e));
+                None
+            }
+        };
+        match end {
+            Some(PlaybackEnd::StoppedAt(position)) => {
+                let _ = youtube_client.set_watch_position(&video.id, position);
+            }
+            Some(PlaybackEnd::Finished) => {
+                let _ = youtube_client.mark_watched(&video.id);
+            }
+            Some(PlaybackEnd::Unknown) | None => {}
+        }
+        let _ = done_tx.send((video.id, end));
+    });
+}
+
+// Helper: Download options for a video, named by the template for where it was
+// picked: a playlist, a channel's videos or anywhere else
+fn download_options(
+    settings: &Settings,
+    view: ViewMode,
+    video: &Video,
+    list: &[Video],
+    channel_title: Option<&str>,
+    playlist_title: Option<&str>,
+) -> DownloadOptions {
+    let prefs = &settings.downloads;
+    let mut values = TemplateValues::for_video(video);
+    let template = match view {
+        ViewMode::PlaylistVideos => {
+            values.playlist = playlist_title.map(str::to_string);
+            values.index = list.iter().position(|v| v.id == video.id).map(|i| i + 1);
+            &prefs.templates.playlist
+        }
+        ViewMode::ChannelVideos | ViewMode::SubscriptionVideos | ViewMode::SubscriptionShorts => {
+            if values.channel.is_none() {
+                values.channel = channel_title.map(str::to_string);
+            }
+            &prefs.templates.channel
+        }
+        _ => &prefs.templates.video,
+    };
+    DownloadOptions {
+        quality: settings.quality.clone(),
+        format: None,
+        directory: Some(prefs.directory()),
+        output: Some(output_template(template, &values)),
+        force: false,
+        audio: None,
+        subtitles: settings.subtitles.clone(),
+        sponsorblock: settings.sponsorblock.clone(),
+    }
+}
+
+// Helper: Download options for a whole playlist or channel; yt-dlp fills in
+// each video's values
+fn collection_download_options(settings: &Settings, target: &DownloadTarget) -> DownloadOptions {
+    let prefs = &settings.downloads;
+    let mut values = TemplateValues::default();
+    let template = match target {
+        DownloadTarget::Playlist { title, .. } => {
+            values.playlist = Some(title.clone()).filter(|title| !title.is_empty());
+            &prefs.templates.playlist
+        }
+        DownloadTarget::Channel { title, .. } => {
+            values.channel = Some(title.clone()).filter(|title| !title.is_empty());
+            &prefs.templates.channel
+        }
+        DownloadTarget::Video(_) => &prefs.templates.video,
+    };
+    DownloadOptions {
+        quality: settings.quality.clone(),
+        format: None,
+        directory: Some(prefs.directory()),
+        output: Some(output_template(template, &values)),
+        force: false,
+        audio: None,
+        subtitles: settings.subtitles.clone(),
+        sponsorblock: settings.sponsorblock.clone(),
+    }
+}
+
+// Helper: Main menu entries, in display order. Account-only entries are left out
+// when browsing anonymously.
+fn main_menu_items(authenticated: bool, can_switch_accounts: bool) -> Vec<MenuItem> {
+    let mut items = vec![
+        MenuItem::Recommendations,
+        MenuItem::Search,
+        MenuItem::History,
+        MenuItem::Queue,
+        MenuItem::Downloads,
+    ];
+    if authenticated {
+        items.push(MenuItem::Subscriptions);
+        items.push(MenuItem::Feed);
+        items.push(MenuItem::Playlists);
+    }
+    items.push(MenuItem::Channel);
+    if can_switch_accounts {
+        items.push(MenuItem::Accounts);
+    }
+    if authenticated && can_switch_accounts {
+        items.push(MenuItem::SignOut);
+    }
+    items.push(MenuItem::Quit);
+    items
 }
 
-pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
+// Builds a client for the named account
+pub type ConnectAccount = Box<dyn Fn(&str) -> Result<YouTubeClient>>;
+
+// Lets the UI swap to another signed-in account without restarting
+pub struct AccountSwitcher {
+    // None once the user has signed out from the UI
+    pub current: Option<String>,
+    pub connect: ConnectAccount,
+}
+
+// Key handling nests `if`s inside match arms on purpose: a guard would let the
+// key fall through to the next arm that matches it.
+#[allow(clippy::collapsible_match)]
+pub async fn run(
+    mut youtube_client: YouTubeClient,
+    mut account_switcher: Option<AccountSwitcher>,
+) -> Result<()> {
     // Clear any pending input and prepare terminal
     use std::io::Write;
     std::io::stdout().flush()?;
@@ -60,6 +316,17 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
     // Channel for yt-dlp output messages
     let (log_tx, mut log_rx) = mpsc::unbounded_channel::<String>();
     let log_tx_arc = Arc::new(log_tx);
+    // Playbacks that ended, once their position was saved
+    let (playback_done_tx, mut playback_done_rx) =
+        mpsc::unbounded_channel::<(String, Option<PlaybackEnd>)>();
+    // Controllers for mpv instances as they start
+    let (controller_tx, mut controller_rx) = mpsc::unbounded_channel::<Arc<MpvController>>();
+    let mut now_playing: Option<NowPlaying> = None;
+    let mut queue = PlayQueue::open_default().unwrap_or_default();
+    let mut queue_list_state = ListState::default();
+    let mut queue_playing: Option<String> = None; // Video the queue started
+    let mut settings = Settings::open_default().unwrap_or_default();
+    let mut queue_audio_only = settings.audio_only; // How the queue was started
 
     let mut view_mode = ViewMode::MainMenu;
     let mut all_videos: Vec<Video> = Vec::new(); // Store all videos
@@ -69,24 +336,41 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
     let mut channel_playlists = Vec::new(); // Store channel playlists
     let mut selected_channel_id: Option<String> = None; // Store selected channel ID
     let mut selected_channel_title: Option<String> = None; // Store selected channel title
+    let mut selected_playlist: Option<Playlist> = None; // Playlist being viewed, for downloads
     let mut video_list_state = ListState::default();
     let mut subscription_list_state = ListState::default();
     let mut playlist_list_state = ListState::default();
-    let mut main_menu_selection = 0; // Track main menu selection (0=recommendations, 1=search, 2=history, 3=subscriptions, 4=playlists, 5=channel, 6=quit)
+    let mut main_menu_selection = 0; // Index into menu_items
     let mut search_query = String::new();
     let mut search_input_mode = false;
     let mut history: Vec<Video> = Vec::new(); // Store watched videos history
+    let mut history_entries: Vec<HistoryEntry> = Vec::new(); // Same order as `history`
+    let mut history_range_start: Option<NaiveDate> = None; // Day marked with 'v'
+    let mut watch_progress = youtube_client.watch_progress().unwrap_or_default(); // By video ID
     let mut channel_tab = 0; // Track channel tab selection (0=Videos, 1=Shorts, 2=Playlists)
     let mut channel_url = String::new();
     let mut status_message = t("status_welcome");
     let mut log_message = String::new(); // Store yt-dlp output messages
     let mut should_quit = false;
+    let mut menu_items = main_menu_items(
+        youtube_client.is_authenticated(),
+        account_switcher.is_some(),
+    );
+    let mut accounts: Vec<String> = Vec::new(); // Accounts with a saved token
+    let mut account_list_state = ListState::default();
+    let mut pending_action: Option<PendingAction> = None;
+    let mut picker_video: Option<Video> = None; // Video waiting for a playlist, format or subtitles to be picked
+    let mut picker_return_view = ViewMode::MainMenu;
+    let mut formats: Vec<VideoFormat> = Vec::new(); // Formats of picker_video
+    let mut format_list_state = ListState::default();
+    let mut captions: Vec<CaptionTrack> = Vec::new(); // Subtitle tracks of picker_video
+    let mut caption_list_state = ListState::default();
 
-    // Store handles for cancellation (download and playback)
-    let download_handle: Arc<std::sync::Mutex<Option<Child>>> =
-        Arc::new(std::sync::Mutex::new(None));
-    let playback_handle: Arc<std::sync::Mutex<Option<Child>>> =
-        Arc::new(std::sync::Mutex::new(None));
+    // Downloads run in the background; finished files are recorded in downloads.json
+    let downloads = DownloadManager::open_default(settings.downloads.concurrency)
+        .unwrap_or_else(|_| DownloadManager::new(settings.downloads.concurrency))
+        .with_log((*log_tx_arc).clone());
+    let mut download_list_state = ListState::default();
 
     // Pagination state
     const VIDEOS_PER_PAGE: usize = 9;
@@ -103,6 +387,15 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
         }
     };
 
+    // Helper function to get the video under the cursor on the current page
+    let selected_video = |all: &[Video], page: usize, state: &ListState| -> Option<Video> {
+        let selected = state.selected()?;
+        if selected >= VIDEOS_PER_PAGE {
+            return None;
+        }
+        all.get(page * VIDEOS_PER_PAGE + selected).cloned()
+    };
+
     // Helper function to calculate total pages
     let calculate_total_pages =
         |count: usize| -> usize { (count + VIDEOS_PER_PAGE - 1) / VIDEOS_PER_PAGE.max(1) };
@@ -112,10 +405,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
         let mut regular_videos: Vec<Video> = Vec::new();
         let mut shorts: Vec<Video> = Vec::new();
         for video in videos {
-            // Check if it's a short (title contains #shorts or #short)
-            let is_short = video.title.to_lowercase().contains("#shorts")
-                || video.title.to_lowercase().contains("#short");
-            if is_short {
+            if video.is_short {
                 shorts.push(video);
             } else {
                 regular_videos.push(video);
@@ -126,7 +416,13 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
 
     // Initial render
     terminal.draw(|f| {
-        ui_main_menu(f, main_menu_selection, &status_message, &log_message);
+        ui_main_menu(
+            f,
+            &menu_items,
+            main_menu_selection,
+            &status_message,
+            &log_message,
+        );
     })?;
 
     loop {
@@ -136,13 +432,93 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
         }
         // If multiple messages came in, keep the latest one
 
+        // The most recently started player is the one the bar shows and controls
+        while let Ok(controller) = controller_rx.try_recv() {
+            now_playing = Some(NowPlaying::new(controller));
+        }
+        if let Some(playing) = now_playing.as_mut() {
+            let mut stopped = false;
+            loop {
+                match playing.events.try_recv() {
+                    Ok(MpvEvent::PropertyChange { name, data }) => playing.update(&name, &data),
+                    Ok(MpvEvent::Shutdown) | Err(broadcast::error::TryRecvError::Closed) => {
+                        stopped = true;
+                        break;
+                    }
+                    Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+                    Err(broadcast::error::TryRecvError::Empty) => break,
+                }
+            }
+            if stopped {
+                now_playing = None;
+            }
+        }
+
+        // A playback ended; show where it stopped and move the queue along
+        let mut ended = Vec::new();
+        while let Ok(done) = playback_done_rx.try_recv() {
+            ended.push(done);
+        }
+        if !ended.is_empty() {
+            watch_progress = youtube_client.watch_progress().unwrap_or_default();
+            for entry in history_entries.iter_mut() {
+                if let Some(saved) = watch_progress.get(&entry.video_id) {
+                    entry.position = saved.position;
+                    entry.watched = saved.watched;
+                }
+            }
+            for (video_id, end) in ended {
+                if queue_playing.as_deref() != Some(video_id.as_str()) {
+                    continue;
+                }
+                queue_playing = None;
+                // Closing the player stops the queue; only finished videos advance it
+                if end != Some(PlaybackEnd::Finished) {
+                    continue;
+                }
+                match queue.advance() {
+                    Some(next) => {
+                        status_message =
+                            t_with_args("status_queue_next", &[("title", &next.title)]);
+                        queue_playing = Some(next.id.clone());
+                        let options = PlayOptions {
+                            start_at: watch_progress
+                                .get(&next.id)
+                                .and_then(HistoryEntry::resume_position),
+                            audio_only: queue_audio_only,
+                            quality: settings.quality.clone(),
+                            format: None,
+                            subtitles: settings.subtitles.clone(),
+                            sponsorblock: settings.sponsorblock.clone(),
+                            controller_tx: Some(controller_tx.clone()),
+                        };
+                        spawn_playback(
+                            youtube_client.clone(),
+                            next,
+                            options,
+                            (*log_tx_arc).clone(),
+                            playback_done_tx.clone(),
+                        );
+                    }
+                    None => status_message = t("status_queue_finished"),
+                }
+                let _ = queue.save();
+            }
+        }
+
         // Always redraw UI to show updated log messages
         terminal.draw(|f| {
             match view_mode {
                 ViewMode::MainMenu => {
-                    ui_main_menu(f, main_menu_selection, &status_message, &log_message);
+                    ui_main_menu(
+                        f,
+                        &menu_items,
+                        main_menu_selection,
+                        &status_message,
+                        &log_message,
+                    );
                 }
-                ViewMode::Recommendations => {
+                ViewMode::Recommendations | ViewMode::Feed => {
                     let page_videos = get_current_page_videos(&all_videos, current_page);
                     let total_pages = calculate_total_pages(all_videos.len());
                     ui_videos(
@@ -153,6 +529,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         current_page + 1,
                         total_pages,
                         &log_message,
+                        &watch_progress,
                     );
                 }
                 ViewMode::Search => {
@@ -165,11 +542,12 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                     );
                 }
                 ViewMode::History => {
-                    let page_videos = get_current_page_videos(&history, current_page);
-                    let total_pages = calculate_total_pages(history.len());
-                    ui_videos(
+                    let start = (current_page * VIDEOS_PER_PAGE).min(history_entries.len());
+                    let end = (start + VIDEOS_PER_PAGE).min(history_entries.len());
+                    let total_pages = calculate_total_pages(history_entries.len());
+                    ui_history(
                         f,
-                        &page_videos,
+                        &history_entries[start..end],
                         &mut video_list_state,
                         &status_message,
                         current_page + 1,
@@ -190,6 +568,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         current_page + 1,
                         total_pages,
                         &log_message,
+                        &watch_progress,
                     );
                 }
                 ViewMode::Subscriptions => {
@@ -216,6 +595,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         current_page + 1,
                         total_pages,
                         &log_message,
+                        &watch_progress,
                     );
                 }
                 ViewMode::SubscriptionVideos | ViewMode::SubscriptionShorts => {
@@ -236,6 +616,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         current_page + 1,
                         total_pages,
                         &log_message,
+                        &watch_progress,
                     );
                 }
                 ViewMode::SubscriptionPlaylists => {
@@ -250,7 +631,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         &log_message,
                     );
                 }
-                ViewMode::Playlists => {
+                ViewMode::Playlists | ViewMode::PlaylistPicker => {
                     ui_playlists(
                         f,
                         &playlists,
@@ -270,11 +651,61 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         current_page + 1,
                         total_pages,
                         &log_message,
+                        &watch_progress,
                     );
                 }
                 ViewMode::ChannelInput => {
                     ui_input(f, &channel_url, &status_message, &log_message);
                 }
+                ViewMode::Downloads => {
+                    ui_downloads(
+                        f,
+                        &downloads.jobs(),
+                        &downloads.completed(),
+                        &mut download_list_state,
+                        &status_message,
+                        &log_message,
+                    );
+                }
+                ViewMode::FormatPicker => {
+                    ui_formats(
+                        f,
+                        &formats,
+                        &mut format_list_state,
+                        picker_video.as_ref().map_or("", |v| v.title.as_str()),
+                        &status_message,
+                        &log_message,
+                    );
+                }
+                ViewMode::Captions => {
+                    ui_captions(
+                        f,
+                        &captions,
+                        &mut caption_list_state,
+                        picker_video.as_ref().map_or("", |v| v.title.as_str()),
+                        &status_message,
+                        &log_message,
+                    );
+                }
+                ViewMode::Queue => {
+                    ui_queue(
+                        f,
+                        &queue,
+                        &mut queue_list_state,
+                        &status_message,
+                        &log_message,
+                    );
+                }
+                ViewMode::Accounts => {
+                    ui_accounts(
+                        f,
+                        &accounts,
+                        account_switcher.as_ref().and_then(|a| a.current.as_deref()),
+                        &mut account_list_state,
+                        &status_message,
+                        &log_message,
+                    );
+                }
                 ViewMode::ChannelVideos => {
                     let page_videos = get_current_page_videos(&all_videos, current_page);
                     let total_pages = calculate_total_pages(all_videos.len());
@@ -286,9 +717,13 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         current_page + 1,
                         total_pages,
                         &log_message,
+                        &watch_progress,
                     );
                 }
             }
+            if let Some(playing) = &now_playing {
+                ui_now_playing(f, playing);
+            }
         })?;
 
         // Use shorter poll timeout to update UI more frequently
@@ -307,6 +742,275 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                         should_quit = true;
                     }
 
+                    // A pending action takes the next key as its answer, before any hotkey
+                    if let Some(action) = pending_action.take() {
+                        if view_mode == ViewMode::PlaylistPicker {
+                            view_mode = picker_return_view;
+                            picker_video = None;
+                        }
+                        if !matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                            status_message = t("status_cancelled");
+                            continue;
+                        }
+                        match action {
+                            PendingAction::SignOut | PendingAction::ForgetCorruptToken => {
+                                let forget = action == PendingAction::ForgetCorruptToken;
+                                let account =
+                                    account_switcher.as_ref().and_then(|a| a.current.clone());
+                                let result = match &account {
+                                    Some(account) => match AuthClient::for_account(account) {
+                                        Ok(auth_client) => match if forget {
+                                            auth_client.forget_corrupt_token().await
+                                        } else {
+                                            auth_client.revoke().await
+                                        } {
+                                            // Cached subscriptions and playlists were theirs;
+                                            // the client's own cache, so nothing writes them back
+                                            Ok(outcome) => {
+                                                youtube_client.clear_cache().map(|()| outcome)
+                                            }
+                                            Err(e) => Err(e),
+                                        },
+                                        Err(e) => Err(e),
+                                    },
+                                    None => Ok(RevokeOutcome::NotSignedIn),
+                                };
+                                match result {
+                                    Ok(outcome) => {
+                                        // Keep browsing without an account; loaded data was theirs
+                                        let mut builder = YouTubeClient::builder()
+                                            .http_client(reqwest::Client::new())
+                                            .api_key_from_env();
+                                        if let Ok(cache) =
+                                            MetadataCache::for_account(ANONYMOUS_CACHE)
+                                        {
+                                            builder = builder.cache(cache);
+                                        }
+                                        youtube_client = builder.build();
+                                        if let Some(switcher) = account_switcher.as_mut() {
+                                            switcher.current = None;
+                                        }
+                                        menu_items = main_menu_items(false, true);
+                                        main_menu_selection = 0;
+                                        all_videos.clear();
+                                        all_shorts.clear();
+                                        subscriptions.clear();
+                                        playlists.clear();
+                                        channel_playlists.clear();
+                                        history.clear();
+                                        history_entries.clear();
+                                        selected_channel_id = None;
+                                        selected_channel_title = None;
+                                        current_page = 0;
+                                        let account = account.unwrap_or_default();
+                                        status_message = match outcome {
+                                            RevokeOutcome::Revoked => t_with_args(
+                                                "status_signed_out",
+                                                &[("account", &account)],
+                                            ),
+                                            RevokeOutcome::AlreadyRevoked => t_with_args(
+                                                "status_already_revoked",
+                                                &[("account", &account)],
+                                            ),
+                                            RevokeOutcome::NotSignedIn => t("status_not_signed_in"),
+                                        };
+                                    }
+                                    // Only a confirmed answer drops a token that was never revoked
+                                    Err(e) if !forget && is_corrupt_token(&e) => {
+                                        pending_action = Some(PendingAction::ForgetCorruptToken);
+                                        status_message = t("status_confirm_forget_corrupt_token");
+                                    }
+                                    Err(e) => {
+                                        status_message = format!("Error: {}", e);
+                                    }
+                                }
+                            }
+                            PendingAction::Subscribe {
+                                channel_id,
+                                channel_title,
+                            } => match youtube_client.subscribe(&channel_id).await {
+                                Ok(()) => {
+                                    status_message = t_with_args(
+                                        "status_subscribed",
+                                        &[("channel", &channel_title)],
+                                    );
+                                }
+                                Err(e) => {
+                                    status_message = format!("Error: {:#}", e);
+                                }
+                            },
+                            PendingAction::Unsubscribe {
+                                channel_id,
+                                channel_title,
+                            } => match youtube_client.unsubscribe(&channel_id).await {
+                                Ok(()) => {
+                                    subscriptions
+                                        .retain(|s: &Subscription| s.channel_id != channel_id);
+                                    if subscription_list_state.selected().unwrap_or(0)
+                                        >= subscriptions.len()
+                                    {
+                                        subscription_list_state
+                                            .select(Some(subscriptions.len().saturating_sub(1)));
+                                    }
+                                    status_message = t_with_args(
+                                        "status_unsubscribed",
+                                        &[("channel", &channel_title)],
+                                    );
+                                }
+                                Err(e) => {
+                                    status_message = format!("Error: {:#}", e);
+                                }
+                            },
+                            PendingAction::Like { video_id, title } => {
+                                match youtube_client.rate_video(&video_id, Rating::Like).await {
+                                    Ok(()) => {
+                                        status_message =
+                                            t_with_args("status_liked", &[("title", &title)]);
+                                    }
+                                    Err(e) => {
+                                        status_message = format!("Error: {:#}", e);
+                                    }
+                                }
+                            }
+                            PendingAction::AddToPlaylist {
+                                playlist_id,
+                                playlist_title,
+                                video_id,
+                                title,
+                            } => match youtube_client
+                                .add_to_playlist(&playlist_id, &video_id)
+                                .await
+                            {
+                                Ok(()) => {
+                                    if let Some(playlist) =
+                                        playlists.iter_mut().find(|p| p.id == playlist_id)
+                                    {
+                                        playlist.item_count += 1;
+                                    }
+                                    status_message = t_with_args(
+                                        "status_added_to_playlist",
+                                        &[("title", &title), ("playlist", &playlist_title)],
+                                    );
+                                }
+                                Err(e) => {
+                                    status_message = format!("Error: {:#}", e);
+                                }
+                            },
+                            PendingAction::RemoveFromPlaylist {
+                                playlist_item_id,
+                                title,
+                            } => match youtube_client.remove_from_playlist(&playlist_item_id).await
+                            {
+                                Ok(()) => {
+                                    all_videos.retain(|v| {
+                                        v.playlist_item_id.as_deref()
+                                            != Some(playlist_item_id.as_str())
+                                    });
+                                    let total_pages = calculate_total_pages(all_videos.len());
+                                    if current_page >= total_pages {
+                                        current_page = total_pages.saturating_sub(1);
+                                    }
+                                    video_list_state.select(Some(0));
+                                    status_message = t_with_args(
+                                        "status_removed_from_playlist",
+                                        &[("title", &title)],
+                                    );
+                                }
+                                Err(e) => {
+                                    status_message = format!("Error: {:#}", e);
+                                }
+                            },
+                            PendingAction::ClearHistoryDays { from, to } => {
+                                match youtube_client.clear_history_between(from, to) {
+                                    Ok(count) => {
+                                        history_entries.retain(|entry| {
+                                            let day = entry.watched_on();
+                                            day < from || day > to
+                                        });
+                                        history = history_entries
+                                            .iter()
+                                            .map(HistoryEntry::to_video)
+                                            .collect();
+                                        history_range_start = None;
+                                        let total_pages = calculate_total_pages(history.len());
+                                        if current_page >= total_pages {
+                                            current_page = total_pages.saturating_sub(1);
+                                        }
+                                        video_list_state.select(Some(0));
+                                        status_message = t_with_args(
+                                            "status_cleared_history_entries",
+                                            &[("count", &count.to_string())],
+                                        );
+                                    }
+                                    Err(e) => {
+                                        status_message = format!("Error: {:#}", e);
+                                    }
+                                }
+                            }
+                            PendingAction::ClearQueue => {
+                                queue.clear();
+                                queue_playing = None;
+                                queue_list_state.select(None);
+                                status_message = match queue.save() {
+                                    Ok(()) => t("status_cleared_queue"),
+                                    Err(e) => format!("Error: {:#}", e),
+                                };
+                            }
+                            PendingAction::ClearHistory => match youtube_client.clear_history() {
+                                Ok(()) => {
+                                    history.clear();
+                                    history_entries.clear();
+                                    history_range_start = None;
+                                    current_page = 0;
+                                    video_list_state.select(None);
+                                    status_message = t("status_cleared_history");
+                                }
+                                Err(e) => {
+                                    status_message = format!("Error: {:#}", e);
+                                }
+                            },
+                        }
+                        continue;
+                    }
+
+                    // Player controls work everywhere except while typing
+                    let typing = view_mode == ViewMode::ChannelInput
+                        || (view_mode == ViewMode::Search && search_input_mode);
+                    if key.code == KeyCode::Char('M') && !typing {
+                        // Audio-only as the default for everything played from now on
+                        settings.audio_only = !settings.audio_only;
+                        status_message = if settings.audio_only {
+                            t("status_audio_only_on")
+                        } else {
+                            t("status_audio_only_off")
+                        };
+                        if let Err(e) = settings.save() {
+                            status_message = format!("Error: {:#}", e);
+                        }
+                        continue;
+                    }
+                    if let Some(playing) = now_playing.as_ref().filter(|_| !typing) {
+                        let controller = playing.controller.clone();
+                        let result = match key.code {
+                            KeyCode::Char('K') => Some(controller.toggle_pause().await),
+                            KeyCode::Char('<') => Some(controller.seek(-10.0).await),
+                            KeyCode::Char('>') => Some(controller.seek(10.0).await),
+                            KeyCode::Char('-') => Some(controller.add_volume(-5.0).await),
+                            KeyCode::Char('+') | KeyCode::Char('=') => {
+                                Some(controller.add_volume(5.0).await)
+                            }
+                            KeyCode::Char('{') => Some(controller.add_speed(-0.25).await),
+                            KeyCode::Char('}') => Some(controller.add_speed(0.25).await),
+                            _ => None,
+                        };
+                        if let Some(result) = result {
+                            if let Err(e) = result {
+                                status_message = format!("Error: {:#}", e);
+                            }
+                            continue;
+                        }
+                    }
+
                     match view_mode {
                         ViewMode::MainMenu => {
                             match key.code {
@@ -314,18 +1018,16 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                     should_quit = true;
                                 }
                                 KeyCode::Up => {
-                                    if main_menu_selection > 0 {
-                                        main_menu_selection -= 1;
-                                    }
+                                    main_menu_selection = main_menu_selection.saturating_sub(1);
                                 }
                                 KeyCode::Down => {
-                                    if main_menu_selection < 6 {
+                                    if main_menu_selection + 1 < menu_items.len() {
                                         main_menu_selection += 1;
                                     }
                                 }
                                 KeyCode::Enter | KeyCode::Char(' ') => {
-                                    match main_menu_selection {
-                                        0 => {
+                                    match menu_items[main_menu_selection] {
+                                        MenuItem::Recommendations => {
                                             // Recommendations
                                             view_mode = ViewMode::Recommendations;
                                             status_message = t("status_loading_recommendations");
@@ -344,6 +1046,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                     current_page + 1,
                                                     total_pages,
                                                     &log_message,
+                                                    &watch_progress,
                                                 );
                                             })?;
 
@@ -371,26 +1074,27 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 }
                                             }
                                         }
-                                        1 => {
+                                        MenuItem::Search => {
                                             // Search
                                             view_mode = ViewMode::Search;
                                             search_query.clear();
                                             search_input_mode = true;
                                             status_message = t("status_search_prompt");
                                         }
-                                        2 => {
+                                        MenuItem::History => {
                                             // History - fetch from YouTube API/yt-dlp
                                             view_mode = ViewMode::History;
                                             current_page = 0;
+                                            history_range_start = None;
                                             status_message = t("status_loading_history");
                                             terminal.draw(|f| {
-                                                let page_videos =
-                                                    get_current_page_videos(&history, current_page);
+                                                let end =
+                                                    VIDEOS_PER_PAGE.min(history_entries.len());
                                                 let total_pages =
-                                                    calculate_total_pages(history.len());
-                                                ui_videos(
+                                                    calculate_total_pages(history_entries.len());
+                                                ui_history(
                                                     f,
-                                                    &page_videos,
+                                                    &history_entries[..end],
                                                     &mut video_list_state,
                                                     &status_message,
                                                     current_page + 1,
@@ -400,8 +1104,12 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                             })?;
 
                                             match youtube_client.get_watch_history().await {
-                                                Ok(new_videos) => {
-                                                    history = new_videos;
+                                                Ok(entries) => {
+                                                    history = entries
+                                                        .iter()
+                                                        .map(HistoryEntry::to_video)
+                                                        .collect();
+                                                    history_entries = entries;
                                                     current_page = 0;
                                                     if history.is_empty() {
                                                         status_message = t("status_no_history");
@@ -420,10 +1128,43 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                     status_message = format!("Error: {}", e);
                                                     // Don't go back to menu, show error in history view
                                                     history.clear();
+                                                    history_entries.clear();
                                                 }
                                             }
                                         }
-                                        3 => {
+                                        MenuItem::Downloads => {
+                                            // Downloads
+                                            view_mode = ViewMode::Downloads;
+                                            let jobs = downloads.jobs();
+                                            download_list_state.select(if jobs.is_empty() {
+                                                None
+                                            } else {
+                                                Some(0)
+                                            });
+                                            status_message = t_with_args(
+                                                "status_downloads",
+                                                &[("count", &jobs.len().to_string())],
+                                            );
+                                        }
+                                        MenuItem::Queue => {
+                                            // Play queue
+                                            view_mode = ViewMode::Queue;
+                                            queue_list_state.select(
+                                                queue
+                                                    .current
+                                                    .or(Some(0))
+                                                    .filter(|_| !queue.is_empty()),
+                                            );
+                                            status_message = if queue.is_empty() {
+                                                t("status_queue_empty")
+                                            } else {
+                                                t_with_args(
+                                                    "status_queue_loaded",
+                                                    &[("count", &queue.len().to_string())],
+                                                )
+                                            };
+                                        }
+                                        MenuItem::Subscriptions => {
                                             // Subscriptions
                                             if youtube_client.is_authenticated() {
                                                 view_mode = ViewMode::Subscriptions;
@@ -466,7 +1207,54 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 status_message = "Not authenticated. Please check your credentials.".to_string();
                                             }
                                         }
-                                        4 => {
+                                        MenuItem::Feed => {
+                                            // Subscription feed, newest first
+                                            view_mode = ViewMode::Feed;
+                                            status_message = t("status_loading_feed");
+                                            terminal.draw(|f| {
+                                                let page_videos = get_current_page_videos(
+                                                    &all_videos,
+                                                    current_page,
+                                                );
+                                                let total_pages =
+                                                    calculate_total_pages(all_videos.len());
+                                                ui_videos(
+                                                    f,
+                                                    &page_videos,
+                                                    &mut video_list_state,
+                                                    &status_message,
+                                                    current_page + 1,
+                                                    total_pages,
+                                                    &log_message,
+                                                    &watch_progress,
+                                                );
+                                            })?;
+
+                                            match youtube_client.get_subscription_feed(false).await
+                                            {
+                                                Ok(new_videos) => {
+                                                    all_videos = new_videos;
+                                                    current_page = 0;
+                                                    if all_videos.is_empty() {
+                                                        status_message = t("status_no_feed");
+                                                    } else {
+                                                        video_list_state.select(Some(0));
+                                                        status_message = t_with_args(
+                                                            "status_loaded_feed",
+                                                            &[(
+                                                                "count",
+                                                                &all_videos.len().to_string(),
+                                                            )],
+                                                        );
+                                                    }
+                                                }
+                                                Err(e) => {
+                                                    status_message = format!("Error: {}", e);
+                                                    view_mode = ViewMode::MainMenu;
+                                                }
+                                            }
+                                        }
+                                        MenuItem::Playlists => {
                                             // Playlists
                                             if youtube_client.is_authenticated() {
                                                 view_mode = ViewMode::Playlists;
@@ -499,17 +1287,49 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 status_message = "Not authenticated. Please check your credentials.".to_string();
                                             }
                                         }
-                                        5 => {
+                                        MenuItem::Channel => {
                                             // Channel Input
                                             view_mode = ViewMode::ChannelInput;
                                             channel_url.clear();
                                             status_message = t("channel_input_title");
                                         }
-                                        6 => {
+                                        MenuItem::Accounts => {
+                                            // Accounts
+                                            match list_accounts() {
+                                                Ok(names) => {
+                                                    accounts = names;
+                                                    let current = account_switcher
+                                                        .as_ref()
+                                                        .and_then(|a| a.current.as_deref());
+                                                    account_list_state.select(Some(
+                                                        accounts
+                                                            .iter()
+                                                            .position(|name| {
+                                                                Some(name.as_str()) == current
+                                                            })
+                                                            .unwrap_or(0),
+                                                    ));
+                                                    view_mode = ViewMode::Accounts;
+                                                    status_message = if accounts.is_empty() {
+                                                        t("status_no_accounts")
+                                                    } else {
+                                                        t("accounts_title")
+                                                    };
+                                                }
+                                                Err(e) => {
+                                                    status_message = format!("Error: {}", e);
+                                                }
+                                            }
+                                        }
+                                        MenuItem::SignOut => {
+                                            // Sign out (asks first)
+                                            pending_action = Some(PendingAction::SignOut);
+                                            status_message = t("status_confirm_sign_out");
+                                        }
+                                        MenuItem::Quit => {
                                             // Quit
                                             should_quit = true;
                                         }
-                                        _ => {}
                                     }
                                 }
                                 KeyCode::Char('s') | KeyCode::Char('S') => {
@@ -585,35 +1405,360 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 .to_string();
                                     }
                                 }
-                                KeyCode::Char('c') | KeyCode::Char('C') => {
-                                    view_mode = ViewMode::ChannelInput;
-                                    channel_url.clear();
-                                    status_message = t("channel_input_title");
-                                }
-                                _ => {}
-                            }
-                        }
-                        ViewMode::Subscriptions => {
-                            match key.code {
-                                KeyCode::Char('m') | KeyCode::Esc => {
-                                    view_mode = ViewMode::MainMenu;
-                                    status_message = "Main menu".to_string();
-                                }
-                                KeyCode::Up => {
-                                    if let Some(selected) = subscription_list_state.selected() {
-                                        if selected > 0 {
-                                            subscription_list_state.select(Some(selected - 1));
-                                        }
-                                    }
-                                }
-                                KeyCode::Down => {
-                                    if let Some(selected) = subscription_list_state.selected() {
-                                        if selected < subscriptions.len().saturating_sub(1) {
-                                            subscription_list_state.select(Some(selected + 1));
-                                        }
-                                    }
-                                }
-                                KeyCode::Enter | KeyCode::Char(' ') => {
+                                KeyCode::Char('f') | KeyCode::Char('F') => {
+                                    if menu_items.contains(&MenuItem::Feed) {
+                                        view_mode = ViewMode::Feed;
+                                        status_message = t("status_loading_feed");
+                                        terminal.draw(|f| {
+                                            let page_videos =
+                                                get_current_page_videos(&all_videos, current_page);
+                                            let total_pages =
+                                                calculate_total_pages(all_videos.len());
+                                            ui_videos(
+                                                f,
+                                                &page_videos,
+                                                &mut video_list_state,
+                                                &status_message,
+                                                current_page + 1,
+                                                total_pages,
+                                                &log_message,
+                                                &watch_progress,
+                                            );
+                                        })?;
+
+                                        match youtube_client.get_subscription_feed(false).await {
+                                            Ok(new_videos) => {
+                                                all_videos = new_videos;
+                                                current_page = 0;
+                                                if all_videos.is_empty() {
+                                                    status_message = t("status_no_feed");
+                                                } else {
+                                                    video_list_state.select(Some(0));
+                                                    status_message = t_with_args(
+                                                        "status_loaded_feed",
+                                                        &[("count", &all_videos.len().to_string())],
+                                                    );
+                                                }
+                                            }
+                                            Err(e) => {
+                                                status_message = format!("Error: {}", e);
+                                                view_mode = ViewMode::MainMenu;
+                                            }
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('d') | KeyCode::Char('D') => {
+                                    // Downloads
+                                    view_mode = ViewMode::Downloads;
+                                    let jobs = downloads.jobs();
+                                    download_list_state.select(if jobs.is_empty() {
+                                        None
+                                    } else {
+                                        Some(0)
+                                    });
+                                    status_message = t_with_args(
+                                        "status_downloads",
+                                        &[("count", &jobs.len().to_string())],
+                                    );
+                                }
+                                KeyCode::Char('e') | KeyCode::Char('E') => {
+                                    // Play queue
+                                    view_mode = ViewMode::Queue;
+                                    queue_list_state.select(
+                                        queue.current.or(Some(0)).filter(|_| !queue.is_empty()),
+                                    );
+                                    status_message = if queue.is_empty() {
+                                        t("status_queue_empty")
+                                    } else {
+                                        t_with_args(
+                                            "status_queue_loaded",
+                                            &[("count", &queue.len().to_string())],
+                                        )
+                                    };
+                                }
+                                KeyCode::Char('c') | KeyCode::Char('C') => {
+                                    view_mode = ViewMode::ChannelInput;
+                                    channel_url.clear();
+                                    status_message = t("channel_input_title");
+                                }
+                                KeyCode::Char('a') | KeyCode::Char('A') => {
+                                    if menu_items.contains(&MenuItem::Accounts) {
+                                        // Accounts
+                                        match list_accounts() {
+                                            Ok(names) => {
+                                                accounts = names;
+                                                let current = account_switcher
+                                                    .as_ref()
+                                                    .and_then(|a| a.current.as_deref());
+                                                account_list_state.select(Some(
+                                                    accounts
+                                                        .iter()
+                                                        .position(|name| {
+                                                            Some(name.as_str()) == current
+                                                        })
+                                                        .unwrap_or(0),
+                                                ));
+                                                view_mode = ViewMode::Accounts;
+                                                status_message = if accounts.is_empty() {
+                                                    t("status_no_accounts")
+                                                } else {
+                                                    t("accounts_title")
+                                                };
+                                            }
+                                            Err(e) => {
+                                                status_message = format!("Error: {}", e);
+                                            }
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('o') | KeyCode::Char('O') => {
+                                    if menu_items.contains(&MenuItem::SignOut) {
+                                        // Sign out (asks first)
+                                        pending_action = Some(PendingAction::SignOut);
+                                        status_message = t("status_confirm_sign_out");
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        ViewMode::Queue => match key.code {
+                            KeyCode::Char('q') | KeyCode::Char('Q') => {
+                                should_quit = true;
+                            }
+                            KeyCode::Char('m') | KeyCode::Esc => {
+                                view_mode = ViewMode::MainMenu;
+                                status_message = "Main menu".to_string();
+                            }
+                            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                                if let Some(selected) = queue_list_state.selected() {
+                                    queue_list_state.select(Some(queue.move_up(selected)));
+                                    let _ = queue.save();
+                                }
+                            }
+                            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                                if let Some(selected) = queue_list_state.selected() {
+                                    queue_list_state.select(Some(queue.move_down(selected)));
+                                    let _ = queue.save();
+                                }
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                if let Some(selected) = queue_list_state.selected() {
+                                    if selected > 0 {
+                                        queue_list_state.select(Some(selected - 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                if let Some(selected) = queue_list_state.selected() {
+                                    if selected + 1 < queue.len() {
+                                        queue_list_state.select(Some(selected + 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Enter
+                            | KeyCode::Char(' ')
+                            | KeyCode::Char('p')
+                            | KeyCode::Char('a') => {
+                                // Play from the selected item on; 'a' flips audio-only for this run
+                                let selected = queue_list_state.selected().unwrap_or(0);
+                                if let Some(video) = queue.play_at(selected) {
+                                    let _ = queue.save();
+                                    queue_audio_only =
+                                        settings.audio_only != (key.code == KeyCode::Char('a'));
+                                    status_message = if queue_audio_only {
+                                        t_with_args("status_listening", &[("title", &video.title)])
+                                    } else {
+                                        t_with_args("status_playing", &[("title", &video.title)])
+                                    };
+                                    queue_playing = Some(video.id.clone());
+                                    let options = PlayOptions {
+                                        start_at: watch_progress
+                                            .get(&video.id)
+                                            .and_then(HistoryEntry::resume_position),
+                                        audio_only: queue_audio_only,
+                                        quality: settings.quality.clone(),
+                                        format: None,
+                                        subtitles: settings.subtitles.clone(),
+                                        sponsorblock: settings.sponsorblock.clone(),
+                                        controller_tx: Some(controller_tx.clone()),
+                                    };
+                                    spawn_playback(
+                                        youtube_client.clone(),
+                                        video,
+                                        options,
+                                        (*log_tx_arc).clone(),
+                                        playback_done_tx.clone(),
+                                    );
+                                }
+                            }
+                            KeyCode::Char('n') => {
+                                // Skip: finishing the video makes the queue move on
+                                match now_playing.as_ref().filter(|_| queue_playing.is_some()) {
+                                    Some(playing) => {
+                                        if let Err(e) = playing.controller.quit().await {
+                                            status_message = format!("Error: {:#}", e);
+                                        } else {
+                                            // Quitting mpv reads as stopping, not finishing
+                                            queue_playing = None;
+                                            match queue.advance() {
+                                                Some(video) => {
+                                                    let _ = queue.save();
+                                                    status_message = t_with_args(
+                                                        "status_queue_next",
+                                                        &[("title", &video.title)],
+                                                    );
+                                                    queue_playing = Some(video.id.clone());
+                                                    let options = PlayOptions {
+                                                        start_at: watch_progress
+                                                            .get(&video.id)
+                                                            .and_then(
+                                                                HistoryEntry::resume_position,
+                                                            ),
+                                                        audio_only: queue_audio_only,
+                                                        quality: settings.quality.clone(),
+                                                        format: None,
+                                                        subtitles: settings.subtitles.clone(),
+                                                        sponsorblock: settings.sponsorblock.clone(),
+                                                        controller_tx: Some(controller_tx.clone()),
+                                                    };
+                                                    spawn_playback(
+                                                        youtube_client.clone(),
+                                                        video,
+                                                        options,
+                                                        (*log_tx_arc).clone(),
+                                                        playback_done_tx.clone(),
+                                                    );
+                                                }
+                                                None => status_message = t("status_queue_finished"),
+                                            }
+                                        }
+                                    }
+                                    None => status_message = t("status_queue_not_playing"),
+                                }
+                            }
+                            KeyCode::Char('x') => {
+                                if let Some(selected) = queue_list_state.selected() {
+                                    if let Some(item) = queue.items.get(selected) {
+                                        status_message = t_with_args(
+                                            "status_removed_from_queue",
+                                            &[("title", &item.video.title)],
+                                        );
+                                        queue.remove(selected);
+                                        let _ = queue.save();
+                                        queue_list_state.select(if queue.is_empty() {
+                                            None
+                                        } else {
+                                            Some(selected.min(queue.len() - 1))
+                                        });
+                                    }
+                                }
+                            }
+                            KeyCode::Char('z') => {
+                                queue.shuffle = !queue.shuffle;
+                                let _ = queue.save();
+                                status_message = if queue.shuffle {
+                                    t("status_shuffle_on")
+                                } else {
+                                    t("status_shuffle_off")
+                                };
+                            }
+                            KeyCode::Char('t') => {
+                                queue.repeat = queue.repeat.next();
+                                let _ = queue.save();
+                                status_message = match queue.repeat {
+                                    RepeatMode::Off => t("status_repeat_off"),
+                                    RepeatMode::All => t("status_repeat_all"),
+                                    RepeatMode::One => t("status_repeat_one"),
+                                };
+                            }
+                            KeyCode::Char('C') => {
+                                if !queue.is_empty() {
+                                    pending_action = Some(PendingAction::ClearQueue);
+                                    status_message = t("status_confirm_clear_queue");
+                                }
+                            }
+                            _ => {}
+                        },
+                        ViewMode::Accounts => match key.code {
+                            KeyCode::Char('m') | KeyCode::Esc => {
+                                view_mode = ViewMode::MainMenu;
+                                status_message = "Main menu".to_string();
+                            }
+                            KeyCode::Up => {
+                                if let Some(selected) = account_list_state.selected() {
+                                    if selected > 0 {
+                                        account_list_state.select(Some(selected - 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Down => {
+                                if let Some(selected) = account_list_state.selected() {
+                                    if selected < accounts.len().saturating_sub(1) {
+                                        account_list_state.select(Some(selected + 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') => {
+                                if let (Some(selected), Some(switcher)) =
+                                    (account_list_state.selected(), account_switcher.as_mut())
+                                {
+                                    if let Some(name) = accounts.get(selected) {
+                                        match (switcher.connect)(name) {
+                                            Ok(new_client) => {
+                                                // Nothing loaded for the old account carries over
+                                                youtube_client = new_client;
+                                                switcher.current = Some(name.clone());
+                                                menu_items = main_menu_items(
+                                                    youtube_client.is_authenticated(),
+                                                    true,
+                                                );
+                                                main_menu_selection = 0;
+                                                all_videos.clear();
+                                                all_shorts.clear();
+                                                subscriptions.clear();
+                                                playlists.clear();
+                                                channel_playlists.clear();
+                                                history.clear();
+                                                history_entries.clear();
+                                                selected_channel_id = None;
+                                                selected_channel_title = None;
+                                                current_page = 0;
+                                                view_mode = ViewMode::MainMenu;
+                                                status_message = t_with_args(
+                                                    "status_switched_account",
+                                                    &[("account", name)],
+                                                );
+                                            }
+                                            Err(e) => {
+                                                status_message = format!("Error: {}", e);
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            _ => {}
+                        },
+                        ViewMode::Subscriptions => {
+                            match key.code {
+                                KeyCode::Char('m') | KeyCode::Esc => {
+                                    view_mode = ViewMode::MainMenu;
+                                    status_message = "Main menu".to_string();
+                                }
+                                KeyCode::Up => {
+                                    if let Some(selected) = subscription_list_state.selected() {
+                                        if selected > 0 {
+                                            subscription_list_state.select(Some(selected - 1));
+                                        }
+                                    }
+                                }
+                                KeyCode::Down => {
+                                    if let Some(selected) = subscription_list_state.selected() {
+                                        if selected < subscriptions.len().saturating_sub(1) {
+                                            subscription_list_state.select(Some(selected + 1));
+                                        }
+                                    }
+                                }
+                                KeyCode::Enter | KeyCode::Char(' ') => {
                                     if let Some(selected) = subscription_list_state.selected() {
                                         if selected < subscriptions.len() {
                                             let sub = &subscriptions[selected];
@@ -640,6 +1785,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                     1,
                                                     1,
                                                     &log_message,
+                                                    &watch_progress,
                                                 )
                                             })?;
 
@@ -684,7 +1830,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                         )
                                     })?;
 
-                                    match youtube_client.get_subscriptions().await {
+                                    match youtube_client.fresh().get_subscriptions().await {
                                         Ok(new_subs) => {
                                             subscriptions = new_subs;
                                             if subscription_list_state.selected().unwrap_or(0)
@@ -702,15 +1848,306 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                         }
                                     }
                                 }
+                                KeyCode::Char('X') => {
+                                    // Unsubscribe (asks first)
+                                    if let Some(sub) = subscription_list_state
+                                        .selected()
+                                        .and_then(|selected| subscriptions.get(selected))
+                                    {
+                                        status_message = t_with_args(
+                                            "status_confirm_unsubscribe",
+                                            &[("channel", &sub.channel_title)],
+                                        );
+                                        pending_action = Some(PendingAction::Unsubscribe {
+                                            channel_id: sub.channel_id.clone(),
+                                            channel_title: sub.channel_title.clone(),
+                                        });
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        ViewMode::Downloads => {
+                            let jobs = downloads.jobs();
+                            let selected_job = download_list_state
+                                .selected()
+                                .and_then(|selected| jobs.get(selected));
+                            match key.code {
+                                KeyCode::Char('q') | KeyCode::Char('Q') => {
+                                    should_quit = true;
+                                }
+                                KeyCode::Char('m') | KeyCode::Esc => {
+                                    view_mode = ViewMode::MainMenu;
+                                    status_message = "Main menu".to_string();
+                                }
+                                KeyCode::Up | KeyCode::Char('k') => {
+                                    if let Some(selected) = download_list_state.selected() {
+                                        if selected > 0 {
+                                            download_list_state.select(Some(selected - 1));
+                                        }
+                                    }
+                                }
+                                KeyCode::Down | KeyCode::Char('j') => {
+                                    if let Some(selected) = download_list_state.selected() {
+                                        if selected + 1 < jobs.len() {
+                                            download_list_state.select(Some(selected + 1));
+                                        }
+                                    } else if !jobs.is_empty() {
+                                        download_list_state.select(Some(0));
+                                    }
+                                }
+                                KeyCode::Char('p') | KeyCode::Char(' ') => {
+                                    // Pause or resume
+                                    if let Some(job) = selected_job {
+                                        if downloads.pause(job.id) {
+                                            status_message = t_with_args(
+                                                "status_download_paused",
+                                                &[("title", job.target.title())],
+                                            );
+                                        } else if downloads.resume(job.id) {
+                                            status_message = t_with_args(
+                                                "status_download_resumed",
+                                                &[("title", job.target.title())],
+                                            );
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('c') => {
+                                    if let Some(job) = selected_job {
+                                        if downloads.cancel(job.id) {
+                                            status_message = t_with_args(
+                                                "status_download_cancelled",
+                                                &[("title", job.target.title())],
+                                            );
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('r') => {
+                                    if let Some(job) = selected_job {
+                                        if downloads.retry(job.id) {
+                                            status_message = t_with_args(
+                                                "status_download_retrying",
+                                                &[("title", job.target.title())],
+                                            );
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('x') => {
+                                    // Remove from the list (not while it downloads)
+                                    if let Some(job) = selected_job {
+                                        if downloads.remove(job.id) {
+                                            let left = jobs.len() - 1;
+                                            download_list_state.select(if left == 0 {
+                                                None
+                                            } else {
+                                                download_list_state
+                                                    .selected()
+                                                    .map(|selected| selected.min(left - 1))
+                                            });
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('C') => {
+                                    // Clear finished, skipped and cancelled downloads
+                                    downloads.clear_finished();
+                                    let left = downloads.jobs().len();
+                                    download_list_state.select(if left == 0 {
+                                        None
+                                    } else {
+                                        Some(0)
+                                    });
+                                }
                                 _ => {}
                             }
                         }
+                        ViewMode::FormatPicker => match key.code {
+                            KeyCode::Char('m') | KeyCode::Esc => {
+                                view_mode = picker_return_view;
+                                picker_video = None;
+                                status_message = t("status_cancelled");
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                if let Some(selected) = format_list_state.selected() {
+                                    if selected > 0 {
+                                        format_list_state.select(Some(selected - 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                if let Some(selected) = format_list_state.selected() {
+                                    if selected + 1 < formats.len() {
+                                        format_list_state.select(Some(selected + 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('p') => {
+                                // Play the video in the picked format
+                                if let (Some(format), Some(video)) = (
+                                    format_list_state
+                                        .selected()
+                                        .and_then(|selected| formats.get(selected)),
+                                    picker_video.take(),
+                                ) {
+                                    status_message = t_with_args(
+                                        "status_playing_format",
+                                        &[("title", &video.title), ("format", &format.format_id)],
+                                    );
+                                    let options = PlayOptions {
+                                        start_at: watch_progress
+                                            .get(&video.id)
+                                            .and_then(HistoryEntry::resume_position),
+                                        audio_only: !format.has_video(),
+                                        quality: settings.quality.clone(),
+                                        format: Some(format.selector()),
+                                        subtitles: settings.subtitles.clone(),
+                                        sponsorblock: settings.sponsorblock.clone(),
+                                        controller_tx: Some(controller_tx.clone()),
+                                    };
+                                    spawn_playback(
+                                        youtube_client.clone(),
+                                        video,
+                                        options,
+                                        (*log_tx_arc).clone(),
+                                        playback_done_tx.clone(),
+                                    );
+                                    view_mode = picker_return_view;
+                                }
+                            }
+                            KeyCode::Char('d') => {
+                                // Download the video in the picked format
+                                if let (Some(format), Some(video)) = (
+                                    format_list_state
+                                        .selected()
+                                        .and_then(|selected| formats.get(selected)),
+                                    picker_video.take(),
+                                ) {
+                                    status_message = t_with_args(
+                                        "status_downloading_format",
+                                        &[("title", &video.title), ("format", &format.format_id)],
+                                    );
+                                    let options = DownloadOptions {
+                                        format: Some(format.selector()),
+                                        ..download_options(
+                                            &settings,
+                                            picker_return_view,
+                                            &video,
+                                            &all_videos,
+                                            selected_channel_title.as_deref(),
+                                            selected_playlist.as_ref().map(|p| p.title.as_str()),
+                                        )
+                                    };
+                                    downloads.enqueue(video, options);
+                                    view_mode = picker_return_view;
+                                }
+                            }
+                            _ => {}
+                        },
+                        ViewMode::Captions => match key.code {
+                            KeyCode::Char('m') | KeyCode::Esc => {
+                                view_mode = picker_return_view;
+                                picker_video = None;
+                                status_message = t("status_cancelled");
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                if let Some(selected) = caption_list_state.selected() {
+                                    if selected > 0 {
+                                        caption_list_state.select(Some(selected - 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                if let Some(selected) = caption_list_state.selected() {
+                                    if selected + 1 < captions.len() {
+                                        caption_list_state.select(Some(selected + 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('p') => {
+                                // Play the video with the picked subtitles
+                                if let (Some(track), Some(video)) = (
+                                    caption_list_state
+                                        .selected()
+                                        .and_then(|selected| captions.get(selected)),
+                                    picker_video.take(),
+                                ) {
+                                    status_message = t_with_args(
+                                        "status_playing_with_captions",
+                                        &[("title", &video.title), ("language", &track.name)],
+                                    );
+                                    let options = PlayOptions {
+                                        start_at: watch_progress
+                                            .get(&video.id)
+                                            .and_then(HistoryEntry::resume_position),
+                                        audio_only: false,
+                                        quality: settings.quality.clone(),
+                                        format: None,
+                                        subtitles: SubtitlePrefs {
+                                            languages: vec![track.language.clone()],
+                                            auto_captions: track.automatic,
+                                            ..settings.subtitles.clone()
+                                        },
+                                        sponsorblock: settings.sponsorblock.clone(),
+                                        controller_tx: Some(controller_tx.clone()),
+                                    };
+                                    spawn_playback(
+                                        youtube_client.clone(),
+                                        video,
+                                        options,
+                                        (*log_tx_arc).clone(),
+                                        playback_done_tx.clone(),
+                                    );
+                                    view_mode = picker_return_view;
+                                }
+                            }
+                            _ => {}
+                        },
                         ViewMode::ChannelMenu => {
                             // This mode is now deprecated, but kept for compatibility
                             // Should redirect to SubscriptionVideos with tabs
                             view_mode = ViewMode::SubscriptionVideos;
                             channel_tab = 0;
                         }
+                        ViewMode::PlaylistPicker => match key.code {
+                            KeyCode::Char('m') | KeyCode::Esc => {
+                                view_mode = picker_return_view;
+                                picker_video = None;
+                                status_message = t("status_cancelled");
+                            }
+                            KeyCode::Up => {
+                                if let Some(selected) = playlist_list_state.selected() {
+                                    if selected > 0 {
+                                        playlist_list_state.select(Some(selected - 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Down => {
+                                if let Some(selected) = playlist_list_state.selected() {
+                                    if selected < playlists.len().saturating_sub(1) {
+                                        playlist_list_state.select(Some(selected + 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') => {
+                                if let (Some(playlist), Some(video)) = (
+                                    playlist_list_state
+                                        .selected()
+                                        .and_then(|selected| playlists.get(selected)),
+                                    &picker_video,
+                                ) {
+                                    status_message = t_with_args(
+                                        "status_confirm_add_to_playlist",
+                                        &[("title", &video.title), ("playlist", &playlist.title)],
+                                    );
+                                    pending_action = Some(PendingAction::AddToPlaylist {
+                                        playlist_id: playlist.id.clone(),
+                                        playlist_title: playlist.title.clone(),
+                                        video_id: video.id.clone(),
+                                        title: video.title.clone(),
+                                    });
+                                }
+                            }
+                            _ => {}
+                        },
                         ViewMode::Playlists => match key.code {
                             KeyCode::Char('m') | KeyCode::Esc => {
                                 view_mode = ViewMode::MainMenu;
@@ -730,10 +2167,29 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                     }
                                 }
                             }
-                            KeyCode::Enter | KeyCode::Char(' ') => {
-                                if let Some(selected) = playlist_list_state.selected() {
-                                    if selected < playlists.len() {
+                            KeyCode::Char('d') => {
+                                // Download the whole playlist
+                                if let Some(playlist) = playlist_list_state
+                                    .selected()
+                                    .and_then(|selected| playlists.get(selected))
+                                {
+                                    let target = DownloadTarget::Playlist {
+                                        id: playlist.id.clone(),
+                                        title: playlist.title.clone(),
+                                    };
+                                    status_message = t_with_args(
+                                        "status_downloading_playlist",
+                                        &[("title", &playlist.title)],
+                                    );
+                                    let options = collection_download_options(&settings, &target);
+                                    downloads.enqueue_target(target, options);
+                                }
+                            }
+                            KeyCode::Enter | KeyCode::Char(' ') => {
+                                if let Some(selected) = playlist_list_state.selected() {
+                                    if selected < playlists.len() {
                                         let playlist = &playlists[selected];
+                                        selected_playlist = Some(playlist.clone());
                                         view_mode = ViewMode::PlaylistVideos;
                                         current_page = 0;
                                         status_message =
@@ -748,6 +2204,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 1,
                                                 1,
                                                 &log_message,
+                                                &watch_progress,
                                             )
                                         })?;
 
@@ -788,7 +2245,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                     )
                                 })?;
 
-                                match youtube_client.get_playlists().await {
+                                match youtube_client.fresh().get_playlists().await {
                                     Ok(new_playlists) => {
                                         playlists = new_playlists;
                                         if playlist_list_state.selected().unwrap_or(0)
@@ -828,10 +2285,30 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                         }
                                     }
                                 }
+                                KeyCode::Char('d') => {
+                                    // Download the whole playlist
+                                    if let Some(playlist) = playlist_list_state
+                                        .selected()
+                                        .and_then(|selected| channel_playlists.get(selected))
+                                    {
+                                        let target = DownloadTarget::Playlist {
+                                            id: playlist.id.clone(),
+                                            title: playlist.title.clone(),
+                                        };
+                                        status_message = t_with_args(
+                                            "status_downloading_playlist",
+                                            &[("title", &playlist.title)],
+                                        );
+                                        let options =
+                                            collection_download_options(&settings, &target);
+                                        downloads.enqueue_target(target, options);
+                                    }
+                                }
                                 KeyCode::Enter | KeyCode::Char(' ') => {
                                     if let Some(selected) = playlist_list_state.selected() {
                                         if selected < channel_playlists.len() {
                                             let playlist = &channel_playlists[selected];
+                                            selected_playlist = Some(playlist.clone());
                                             view_mode = ViewMode::PlaylistVideos;
                                             current_page = 0;
                                             status_message = format!(
@@ -848,6 +2325,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                     1,
                                                     1,
                                                     &log_message,
+                                                    &watch_progress,
                                                 )
                                             })?;
 
@@ -1006,6 +2484,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                             }
                         }
                         ViewMode::Recommendations
+                        | ViewMode::Feed
                         | ViewMode::History
                         | ViewMode::SearchResults
                         | ViewMode::SubscriptionVideos
@@ -1016,6 +2495,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                 KeyCode::Esc => {
                                     // Go back to previous view
                                     if view_mode == ViewMode::Recommendations
+                                        || view_mode == ViewMode::Feed
                                         || view_mode == ViewMode::History
                                         || view_mode == ViewMode::SearchResults
                                     {
@@ -1082,6 +2562,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                             1,
                                                             1,
                                                             &log_message,
+                                                            &watch_progress,
                                                         )
                                                     })?;
 
@@ -1146,6 +2627,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                             1,
                                                             1,
                                                             &log_message,
+                                                            &watch_progress,
                                                         )
                                                     })?;
 
@@ -1221,6 +2703,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                             1,
                                                             1,
                                                             &log_message,
+                                                            &watch_progress,
                                                         )
                                                     })?;
 
@@ -1310,6 +2793,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                             1,
                                                             1,
                                                             &log_message,
+                                                            &watch_progress,
                                                         )
                                                     })?;
 
@@ -1384,6 +2868,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                             1,
                                                             1,
                                                             &log_message,
+                                                            &watch_progress,
                                                         )
                                                     })?;
 
@@ -1505,6 +2990,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                     1,
                                                     1,
                                                     &log_message,
+                                                    &watch_progress,
                                                 )
                                             })?;
 
@@ -1582,7 +3068,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                     };
                                     let page_videos =
                                         get_current_page_videos(current_list, current_page);
-                                    if page_videos.len() > 0 {
+                                    if !page_videos.is_empty() {
                                         video_list_state.select(Some(0));
                                     }
                                 }
@@ -1844,7 +3330,13 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                         );
                                     }
                                 }
-                                KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('p') => {
+                                KeyCode::Enter
+                                | KeyCode::Char(' ')
+                                | KeyCode::Char('p')
+                                | KeyCode::Char('a') => {
+                                    // 'a' plays the other way round from the audio-only default
+                                    let audio_only =
+                                        settings.audio_only != (key.code == KeyCode::Char('a'));
                                     // Determine which list to use based on view mode and get video info
                                     let video_info = {
                                         let current_list: &[Video] =
@@ -1881,12 +3373,28 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                         }
                                     };
 
-                                    if let Some((vid_id, vid_title, _vid_clone)) = video_info {
-                                        // Note: History is now fetched from YouTube via API/yt-dlp, not tracked locally
-                                        // The history will be synced when you open the History view
-
-                                        status_message =
-                                            t_with_args("status_playing", &[("title", &vid_title)]);
+                                    if let Some((vid_id, vid_title, video)) = video_info {
+                                        // Pick up where a partly watched video was left
+                                        let start_at = watch_progress
+                                            .get(&vid_id)
+                                            .and_then(HistoryEntry::resume_position);
+                                        status_message = match start_at {
+                                            Some(position) => t_with_args(
+                                                "status_resuming",
+                                                &[
+                                                    ("title", &vid_title),
+                                                    ("position", &format_duration(position)),
+                                                ],
+                                            ),
+                                            None if audio_only => t_with_args(
+                                                "status_listening",
+                                                &[("title", &vid_title)],
+                                            ),
+                                            None => t_with_args(
+                                                "status_playing",
+                                                &[("title", &vid_title)],
+                                            ),
+                                        };
 
                                         // Determine which list to use for display
                                         let current_list: &[Video] =
@@ -1909,26 +3417,27 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 current_page + 1,
                                                 total_pages,
                                                 &log_message,
+                                                &watch_progress,
                                             )
                                         })?;
 
                                         // Add to history and play video in background
-                                        let log_tx = log_tx_arc.clone();
-                                        let youtube_client_clone = youtube_client.clone();
-                                        let vid_id_clone = vid_id.clone();
-                                        tokio::spawn(async move {
-                                            // Add to history (ignore errors)
-                                            let _ = youtube_client_clone
-                                                .add_to_history(&vid_id_clone)
-                                                .await;
-                                            // Play video
-                                            if let Err(e) =
-                                                play_video(&vid_id_clone, Some((*log_tx).clone()))
-                                                    .await
-                                            {
-                                                let _ = (*log_tx).send(format!("Error: {}", e));
-                                            }
-                                        });
+                                        let options = PlayOptions {
+                                            start_at,
+                                            audio_only,
+                                            quality: settings.quality.clone(),
+                                            format: None,
+                                            subtitles: settings.subtitles.clone(),
+                                            sponsorblock: settings.sponsorblock.clone(),
+                                            controller_tx: Some(controller_tx.clone()),
+                                        };
+                                        spawn_playback(
+                                            youtube_client.clone(),
+                                            video,
+                                            options,
+                                            (*log_tx_arc).clone(),
+                                            playback_done_tx.clone(),
+                                        );
                                     }
                                 }
                                 KeyCode::Char('d') => {
@@ -1969,41 +3478,130 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                         current_page + 1,
                                                         total_pages,
                                                         &log_message,
+                                                        &watch_progress,
                                                     )
                                                 })?;
 
                                                 // Download video in background
-                                                let video_id = video.id.clone();
-                                                let log_tx = log_tx_arc.clone();
-                                                let download_handle_clone = download_handle.clone();
-                                                tokio::spawn(async move {
-                                                    if let Err(e) = download_video(
-                                                        &video_id,
-                                                        Some((*log_tx).clone()),
-                                                        Some(download_handle_clone),
-                                                    )
-                                                    .await
-                                                    {
-                                                        let _ =
-                                                            (*log_tx).send(format!("Error: {}", e));
-                                                    }
-                                                });
+                                                let options = download_options(
+                                                    &settings,
+                                                    view_mode,
+                                                    video,
+                                                    current_list,
+                                                    selected_channel_title.as_deref(),
+                                                    selected_playlist
+                                                        .as_ref()
+                                                        .map(|p| p.title.as_str()),
+                                                );
+                                                downloads.enqueue(video.clone(), options);
                                             }
                                         }
                                     }
                                 }
-                                KeyCode::Char('c') => {
-                                    // Cancel ongoing download
-                                    let mut handle_guard = download_handle.lock().unwrap();
-                                    if let Some(mut child) = handle_guard.take() {
-                                        // Kill the process asynchronously
-                                        let log_tx_cancel = log_tx_arc.clone();
-                                        tokio::spawn(async move {
-                                            if let Err(e) = child.kill().await {
-                                                // Process might have already finished, ignore error
-                                                let _ = e;
+                                KeyCode::Char('s') => {
+                                    // Download only the audio, in the codec from the settings
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    if let Some(video) = selected_video(
+                                        current_list,
+                                        current_page,
+                                        &video_list_state,
+                                    ) {
+                                        if deps::check_ffmpeg().await {
+                                            let audio = settings.downloads.audio.clone();
+                                            status_message = t_with_args(
+                                                "status_downloading_audio",
+                                                &[("title", &video.title), ("codec", &audio.codec)],
+                                            );
+                                            let options = DownloadOptions {
+                                                audio: Some(audio),
+                                                ..download_options(
+                                                    &settings,
+                                                    view_mode,
+                                                    &video,
+                                                    current_list,
+                                                    selected_channel_title.as_deref(),
+                                                    selected_playlist
+                                                        .as_ref()
+                                                        .map(|p| p.title.as_str()),
+                                                )
+                                            };
+                                            downloads.enqueue(video, options);
+                                        } else {
+                                            status_message = t("status_ffmpeg_missing");
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('D')
+                                    if view_mode == ViewMode::PlaylistVideos
+                                        || view_mode == ViewMode::ChannelVideos
+                                        || view_mode == ViewMode::SubscriptionVideos
+                                        || view_mode == ViewMode::SubscriptionShorts =>
+                                {
+                                    // Download the whole playlist, or all of the channel's videos
+                                    let target = if view_mode == ViewMode::PlaylistVideos {
+                                        selected_playlist.as_ref().map(|playlist| {
+                                            DownloadTarget::Playlist {
+                                                id: playlist.id.clone(),
+                                                title: playlist.title.clone(),
                                             }
-                                        });
+                                        })
+                                    } else {
+                                        let first = all_videos.first();
+                                        selected_channel_id
+                                            .clone()
+                                            .or_else(|| {
+                                                first
+                                                    .map(|video| video.channel_id.clone())
+                                                    .filter(|id| !id.is_empty())
+                                            })
+                                            .or_else(|| {
+                                                Some(channel_url.trim().to_string())
+                                                    .filter(|url| !url.is_empty())
+                                            })
+                                            .map(|id| DownloadTarget::Channel {
+                                                title: selected_channel_title
+                                                    .clone()
+                                                    .or_else(|| {
+                                                        first.map(|video| {
+                                                            video.channel_title.clone()
+                                                        })
+                                                    })
+                                                    .filter(|title| !title.is_empty())
+                                                    .unwrap_or_else(|| id.clone()),
+                                                id,
+                                            })
+                                    };
+                                    if let Some(target) = target {
+                                        status_message = match &target {
+                                            DownloadTarget::Playlist { title, .. } => t_with_args(
+                                                "status_downloading_playlist",
+                                                &[("title", title)],
+                                            ),
+                                            _ => t_with_args(
+                                                "status_downloading_channel",
+                                                &[("channel", target.title())],
+                                            ),
+                                        };
+                                        let options =
+                                            collection_download_options(&settings, &target);
+                                        downloads.enqueue_target(target, options);
+                                    }
+                                }
+                                KeyCode::Char('c') => {
+                                    // Cancel the newest running download
+                                    let running = downloads
+                                        .jobs()
+                                        .into_iter()
+                                        .rev()
+                                        .find(|job| job.state == JobState::Running);
+                                    if let Some(job) = running {
+                                        downloads.cancel(job.id);
                                         status_message = "Download cancelled".to_string();
                                         let _ = log_tx_arc
                                             .send("Download cancelled by user".to_string());
@@ -2011,6 +3609,474 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                         status_message = "No active download to cancel".to_string();
                                     }
                                 }
+                                KeyCode::Char('L') => {
+                                    // Like (asks first)
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    if !youtube_client.is_authenticated() {
+                                        status_message = t("error_not_authenticated");
+                                    } else if let Some(video) = selected_video(
+                                        current_list,
+                                        current_page,
+                                        &video_list_state,
+                                    ) {
+                                        status_message = t_with_args(
+                                            "status_confirm_like",
+                                            &[("title", &video.title)],
+                                        );
+                                        pending_action = Some(PendingAction::Like {
+                                            video_id: video.id,
+                                            title: video.title,
+                                        });
+                                    }
+                                }
+                                KeyCode::Char('S') => {
+                                    // Subscribe to the video's channel (asks first)
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    if !youtube_client.is_authenticated() {
+                                        status_message = t("error_not_authenticated");
+                                    } else if let Some(video) = selected_video(
+                                        current_list,
+                                        current_page,
+                                        &video_list_state,
+                                    ) {
+                                        // Channel views know their channel even when videos don't
+                                        let channel_id = if video.channel_id.is_empty() {
+                                            selected_channel_id.clone().unwrap_or_default()
+                                        } else {
+                                            video.channel_id
+                                        };
+                                        if channel_id.is_empty() {
+                                            status_message = t("status_unknown_channel");
+                                        } else {
+                                            status_message = t_with_args(
+                                                "status_confirm_subscribe",
+                                                &[("channel", &video.channel_title)],
+                                            );
+                                            pending_action = Some(PendingAction::Subscribe {
+                                                channel_id,
+                                                channel_title: video.channel_title,
+                                            });
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('A') => {
+                                    // Add to one of the user's playlists
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    if !youtube_client.is_authenticated() {
+                                        status_message = t("error_not_authenticated");
+                                    } else if let Some(video) = selected_video(
+                                        current_list,
+                                        current_page,
+                                        &video_list_state,
+                                    ) {
+                                        if playlists.is_empty() {
+                                            match youtube_client.get_playlists().await {
+                                                Ok(new_playlists) => playlists = new_playlists,
+                                                Err(e) => {
+                                                    status_message = format!("Error: {}", e);
+                                                    continue;
+                                                }
+                                            }
+                                        }
+                                        if playlists.is_empty() {
+                                            status_message = t("status_no_playlists");
+                                        } else {
+                                            status_message = t_with_args(
+                                                "status_pick_playlist",
+                                                &[("title", &video.title)],
+                                            );
+                                            picker_video = Some(video);
+                                            picker_return_view = view_mode;
+                                            view_mode = ViewMode::PlaylistPicker;
+                                            playlist_list_state.select(Some(0));
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('F') => {
+                                    // Choose a format to play or download the video in
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    if let Some(video) = selected_video(
+                                        current_list,
+                                        current_page,
+                                        &video_list_state,
+                                    ) {
+                                        status_message = t_with_args(
+                                            "status_loading_formats",
+                                            &[("title", &video.title)],
+                                        );
+                                        let page_videos =
+                                            get_current_page_videos(current_list, current_page);
+                                        let total_pages = calculate_total_pages(current_list.len());
+                                        terminal.draw(|f| {
+                                            ui_videos(
+                                                f,
+                                                &page_videos,
+                                                &mut video_list_state,
+                                                &status_message,
+                                                current_page + 1,
+                                                total_pages,
+                                                &log_message,
+                                                &watch_progress,
+                                            )
+                                        })?;
+
+                                        match list_formats(&video.id).await {
+                                            Ok(list) if list.is_empty() => {
+                                                status_message = t("status_no_formats");
+                                            }
+                                            Ok(list) => {
+                                                formats = list;
+                                                format_list_state.select(Some(0));
+                                                status_message = t_with_args(
+                                                    "status_pick_format",
+                                                    &[("count", &formats.len().to_string())],
+                                                );
+                                                picker_video = Some(video);
+                                                picker_return_view = view_mode;
+                                                view_mode = ViewMode::FormatPicker;
+                                            }
+                                            Err(e) => {
+                                                status_message = format!("Error: {:#}", e);
+                                            }
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('T') => {
+                                    // List the video's subtitle tracks and play it with one of them
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    if let Some(video) = selected_video(
+                                        current_list,
+                                        current_page,
+                                        &video_list_state,
+                                    ) {
+                                        status_message = t_with_args(
+                                            "status_loading_captions",
+                                            &[("title", &video.title)],
+                                        );
+                                        let page_videos =
+                                            get_current_page_videos(current_list, current_page);
+                                        let total_pages = calculate_total_pages(current_list.len());
+                                        terminal.draw(|f| {
+                                            ui_videos(
+                                                f,
+                                                &page_videos,
+                                                &mut video_list_state,
+                                                &status_message,
+                                                current_page + 1,
+                                                total_pages,
+                                                &log_message,
+                                                &watch_progress,
+                                            )
+                                        })?;
+
+                                        match list_captions(&video.id).await {
+                                            Ok(list) if list.is_empty() => {
+                                                status_message = t("status_no_captions");
+                                            }
+                                            Ok(list) => {
+                                                captions = list;
+                                                caption_list_state.select(Some(0));
+                                                status_message = t_with_args(
+                                                    "status_pick_caption",
+                                                    &[("count", &captions.len().to_string())],
+                                                );
+                                                picker_video = Some(video);
+                                                picker_return_view = view_mode;
+                                                view_mode = ViewMode::Captions;
+                                            }
+                                            Err(e) => {
+                                                status_message = format!("Error: {:#}", e);
+                                            }
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('e') => {
+                                    // Add the selected video to the play queue
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    if let Some(video) = selected_video(
+                                        current_list,
+                                        current_page,
+                                        &video_list_state,
+                                    ) {
+                                        status_message = if queue
+                                            .enqueue(std::slice::from_ref(&video))
+                                            > 0
+                                        {
+                                            t_with_args("status_queued", &[("title", &video.title)])
+                                        } else {
+                                            t_with_args(
+                                                "status_already_queued",
+                                                &[("title", &video.title)],
+                                            )
+                                        };
+                                        if let Err(e) = queue.save() {
+                                            status_message = format!("Error: {:#}", e);
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('E') => {
+                                    // Add every video in the list to the play queue
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    let added = queue.enqueue(current_list);
+                                    status_message = t_with_args(
+                                        "status_queued_count",
+                                        &[("count", &added.to_string())],
+                                    );
+                                    if let Err(e) = queue.save() {
+                                        status_message = format!("Error: {:#}", e);
+                                    }
+                                }
+                                KeyCode::Char('P') => {
+                                    // Play the whole list, starting at the selected video
+                                    let current_list: &[Video] = if view_mode == ViewMode::History {
+                                        &history
+                                    } else if view_mode == ViewMode::SubscriptionShorts {
+                                        &all_shorts
+                                    } else {
+                                        &all_videos
+                                    };
+                                    let start = current_page * VIDEOS_PER_PAGE
+                                        + video_list_state.selected().unwrap_or(0);
+                                    if let Some(video) = queue.replace(current_list, start) {
+                                        let _ = queue.save();
+                                        status_message = t_with_args(
+                                            "status_playing_all",
+                                            &[
+                                                ("title", &video.title),
+                                                ("count", &queue.len().to_string()),
+                                            ],
+                                        );
+                                        queue_playing = Some(video.id.clone());
+                                        queue_audio_only = settings.audio_only;
+                                        let options = PlayOptions {
+                                            start_at: watch_progress
+                                                .get(&video.id)
+                                                .and_then(HistoryEntry::resume_position),
+                                            audio_only: queue_audio_only,
+                                            quality: settings.quality.clone(),
+                                            format: None,
+                                            subtitles: settings.subtitles.clone(),
+                                            sponsorblock: settings.sponsorblock.clone(),
+                                            controller_tx: Some(controller_tx.clone()),
+                                        };
+                                        spawn_playback(
+                                            youtube_client.clone(),
+                                            video,
+                                            options,
+                                            (*log_tx_arc).clone(),
+                                            playback_done_tx.clone(),
+                                        );
+                                    }
+                                }
+                                KeyCode::Char('x') if view_mode == ViewMode::History => {
+                                    // Remove the selected video from the history
+                                    let index = current_page * VIDEOS_PER_PAGE
+                                        + video_list_state.selected().unwrap_or(0);
+                                    if let Some(entry) = history_entries.get(index).cloned() {
+                                        match youtube_client.remove_from_history(&entry.video_id) {
+                                            Ok(()) => {
+                                                history_entries.remove(index);
+                                                history.remove(index);
+                                                let total_pages =
+                                                    calculate_total_pages(history.len());
+                                                if current_page >= total_pages {
+                                                    current_page = total_pages.saturating_sub(1);
+                                                }
+                                                let on_page = history
+                                                    .len()
+                                                    .saturating_sub(current_page * VIDEOS_PER_PAGE)
+                                                    .min(VIDEOS_PER_PAGE);
+                                                if on_page == 0 {
+                                                    video_list_state.select(None);
+                                                } else if video_list_state.selected().unwrap_or(0)
+                                                    >= on_page
+                                                {
+                                                    video_list_state.select(Some(on_page - 1));
+                                                }
+                                                let title = history_title(&entry).to_string();
+                                                status_message = t_with_args(
+                                                    "status_removed_from_history",
+                                                    &[("title", &title)],
+                                                );
+                                            }
+                                            Err(e) => {
+                                                status_message = format!("Error: {:#}", e);
+                                            }
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('v') if view_mode == ViewMode::History => {
+                                    // Mark the selected day as the start of a range to clear
+                                    let index = current_page * VIDEOS_PER_PAGE
+                                        + video_list_state.selected().unwrap_or(0);
+                                    if let Some(entry) = history_entries.get(index) {
+                                        let day = entry.watched_on();
+                                        history_range_start = Some(day);
+                                        status_message = t_with_args(
+                                            "status_history_range_start",
+                                            &[("day", &day.format("%Y-%m-%d").to_string())],
+                                        );
+                                    }
+                                }
+                                KeyCode::Char('D') if view_mode == ViewMode::History => {
+                                    // Clear the selected day, or the marked range up to it
+                                    let index = current_page * VIDEOS_PER_PAGE
+                                        + video_list_state.selected().unwrap_or(0);
+                                    if let Some(entry) = history_entries.get(index) {
+                                        let day = entry.watched_on();
+                                        let start = history_range_start.unwrap_or(day);
+                                        let (from, to) = (start.min(day), start.max(day));
+                                        status_message = if from == to {
+                                            t_with_args(
+                                                "status_confirm_clear_history_day",
+                                                &[("day", &from.format("%Y-%m-%d").to_string())],
+                                            )
+                                        } else {
+                                            t_with_args(
+                                                "status_confirm_clear_history_range",
+                                                &[
+                                                    ("from", &from.format("%Y-%m-%d").to_string()),
+                                                    ("to", &to.format("%Y-%m-%d").to_string()),
+                                                ],
+                                            )
+                                        };
+                                        pending_action =
+                                            Some(PendingAction::ClearHistoryDays { from, to });
+                                    }
+                                }
+                                KeyCode::Char('C') if view_mode == ViewMode::History => {
+                                    if !history_entries.is_empty() {
+                                        status_message = t("status_confirm_clear_history");
+                                        pending_action = Some(PendingAction::ClearHistory);
+                                    }
+                                }
+                                KeyCode::Char('[') | KeyCode::Char(']')
+                                    if view_mode == ViewMode::History =>
+                                {
+                                    // Jump to the first video of the newer ('[') or older (']') day
+                                    let index = current_page * VIDEOS_PER_PAGE
+                                        + video_list_state.selected().unwrap_or(0);
+                                    if let Some(entry) = history_entries.get(index) {
+                                        let day = entry.watched_on();
+                                        let target = if key.code == KeyCode::Char(']') {
+                                            history_entries[index..]
+                                                .iter()
+                                                .position(|e| e.watched_on() != day)
+                                                .map(|offset| index + offset)
+                                        } else {
+                                            history_entries[..index]
+                                                .iter()
+                                                .rposition(|e| e.watched_on() != day)
+                                                .map(|newer| {
+                                                    let newer_day =
+                                                        history_entries[newer].watched_on();
+                                                    history_entries[..newer]
+                                                        .iter()
+                                                        .rposition(|e| e.watched_on() != newer_day)
+                                                        .map_or(0, |i| i + 1)
+                                                })
+                                        };
+                                        if let Some(target) = target {
+                                            current_page = target / VIDEOS_PER_PAGE;
+                                            video_list_state.select(Some(target % VIDEOS_PER_PAGE));
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('r') | KeyCode::Char('R')
+                                    if view_mode == ViewMode::History =>
+                                {
+                                    // History lives on disk; just read it again
+                                    match youtube_client.get_watch_history().await {
+                                        Ok(entries) => {
+                                            history = entries
+                                                .iter()
+                                                .map(HistoryEntry::to_video)
+                                                .collect();
+                                            history_entries = entries;
+                                            current_page = 0;
+                                            if history.is_empty() {
+                                                video_list_state.select(None);
+                                                status_message = t("status_no_history");
+                                            } else {
+                                                video_list_state.select(Some(0));
+                                                status_message = t_with_args(
+                                                    "status_loaded_history",
+                                                    &[("count", &history.len().to_string())],
+                                                );
+                                            }
+                                        }
+                                        Err(e) => {
+                                            status_message = format!("Error: {}", e);
+                                        }
+                                    }
+                                }
+                                KeyCode::Char('X') if view_mode == ViewMode::PlaylistVideos => {
+                                    // Remove from the playlist (only the user's own playlists)
+                                    if let Some(video) =
+                                        selected_video(&all_videos, current_page, &video_list_state)
+                                    {
+                                        match video.playlist_item_id {
+                                            Some(playlist_item_id)
+                                                if selected_channel_id.is_none() =>
+                                            {
+                                                status_message = t_with_args(
+                                                    "status_confirm_remove_from_playlist",
+                                                    &[("title", &video.title)],
+                                                );
+                                                pending_action =
+                                                    Some(PendingAction::RemoveFromPlaylist {
+                                                        playlist_item_id,
+                                                        title: video.title,
+                                                    });
+                                            }
+                                            _ => {
+                                                status_message = t("status_not_own_playlist");
+                                            }
+                                        }
+                                    }
+                                }
                                 KeyCode::Char('r') | KeyCode::Char('R') => {
                                     status_message = t("status_refreshing");
                                     // Determine which list to use based on view mode
@@ -2041,6 +4107,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 current_page + 1,
                                                 total_pages,
                                                 &log_message,
+                                                &watch_progress,
                                             )
                                         })?;
                                     } else {
@@ -2053,6 +4120,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 current_page + 1,
                                                 total_pages,
                                                 &log_message,
+                                                &watch_progress,
                                             )
                                         })?;
                                     }
@@ -2063,6 +4131,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                         | ViewMode::SubscriptionShorts => {
                                             if let Some(channel_id) = &selected_channel_id {
                                                 youtube_client
+                                                    .fresh()
                                                     .get_channel_videos_by_id(channel_id)
                                                     .await
                                             } else {
@@ -2073,6 +4142,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                             if let Some(channel_id) = &selected_channel_id {
                                                 // Refresh playlists
                                                 match youtube_client
+                                                    .fresh()
                                                     .get_channel_playlists(channel_id)
                                                     .await
                                                 {
@@ -2113,11 +4183,13 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 {
                                                     let playlist = &channel_playlists[selected];
                                                     youtube_client
+                                                        .fresh()
                                                         .get_playlist_videos(&playlist.id)
                                                         .await
                                                 } else if selected < playlists.len() {
                                                     let playlist = &playlists[selected];
                                                     youtube_client
+                                                        .fresh()
                                                         .get_playlist_videos(&playlist.id)
                                                         .await
                                                 } else {
@@ -2128,7 +4200,13 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                             }
                                         }
                                         ViewMode::ChannelVideos => {
-                                            youtube_client.get_channel_videos(&channel_url).await
+                                            youtube_client
+                                                .fresh()
+                                                .get_channel_videos(&channel_url)
+                                                .await
+                                        }
+                                        ViewMode::Feed => {
+                                            youtube_client.get_subscription_feed(true).await
                                         }
                                         _ => continue,
                                     };
@@ -2231,6 +4309,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                                 current_page + 1,
                                                 total_pages,
                                                 &log_message,
+                                                &watch_progress,
                                             );
                                         })?;
 
@@ -2238,8 +4317,6 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                             Ok(new_videos) => {
                                                 all_videos = new_videos;
                                                 video_list_state.select(Some(0));
-                                                let total_pages =
-                                                    calculate_total_pages(all_videos.len());
                                                 status_message = t_with_args(
                                                     "status_search_results",
                                                     &[
@@ -2286,6 +4363,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
                                             1,
                                             1,
                                             &log_message,
+                                            &watch_progress,
                                         )
                                     })?;
 
@@ -2348,6 +4426,7 @@ pub async fn run(youtube_client: YouTubeClient) -> Result<()> {
     Ok(())
 }
 
+#[allow(dead_code)]
 fn ui_channel_menu(f: &mut Frame, channel_name: &str, status: &str, log: &str) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -2431,15 +4510,621 @@ fn ui_channel_menu(f: &mut Frame, channel_name: &str, status: &str, log: &str) {
         ]),
     ];
 
-    let menu = Paragraph::new(menu_items)
-        .style(Style::default().fg(Color::White))
+    let menu = Paragraph::new(menu_items)
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Select Option"),
+        )
+        .alignment(Alignment::Left);
+    f.render_widget(menu, chunks[1]);
+
+    // Log output (pink box)
+    let log_text = if log.is_empty() { "Ready" } else { log };
+    let log_widget = Paragraph::new(log_text)
+        .style(Style::default().fg(Color::Magenta))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("yt-dlp Output"),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(log_widget, chunks[2]);
+
+    // Status
+    let help_text = "v/1: Videos | s/2: Shorts | p/3: Playlists | Esc: Back";
+    let status_text = format!("{} | {}", status, help_text);
+    let status_widget = Paragraph::new(status_text)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(status_widget, chunks[3]);
+}
+
+fn ui_main_menu(f: &mut Frame, items: &[MenuItem], selection: usize, status: &str, log: &str) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    // Title
+    let title = Paragraph::new(t("app_title"))
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Menu options with selection highlighting
+    let menu_items: Vec<Line> = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let text = match item {
+                MenuItem::Recommendations => t("menu_recommendations"),
+                MenuItem::Search => t("menu_search"),
+                MenuItem::History => t("menu_history"),
+                MenuItem::Queue => t("menu_queue"),
+                MenuItem::Downloads => t("menu_downloads"),
+                MenuItem::Subscriptions => t("menu_subscriptions"),
+                MenuItem::Feed => t("menu_feed"),
+                MenuItem::Playlists => t("menu_playlists"),
+                MenuItem::Channel => t("menu_channel"),
+                MenuItem::Accounts => t("menu_accounts"),
+                MenuItem::SignOut => t("menu_sign_out"),
+                MenuItem::Quit => t("menu_quit"),
+            };
+            let style = if selection == i {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(vec![
+                Span::styled(
+                    if selection == i { "▶ " } else { "  " },
+                    Style::default().fg(if selection == i {
+                        Color::Yellow
+                    } else {
+                        Color::White
+                    }),
+                ),
+                Span::styled(text, style),
+            ])
+        })
+        .collect();
+
+    let menu = Paragraph::new(menu_items)
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t("main_menu_title")),
+        )
+        .alignment(Alignment::Left);
+    f.render_widget(menu, chunks[1]);
+
+    // Log output (pink box)
+    let log_text = if log.is_empty() { "Ready" } else { log };
+    let log_widget = Paragraph::new(log_text)
+        .style(Style::default().fg(Color::Magenta))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("yt-dlp Output"),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(log_widget, chunks[2]);
+
+    // Status - simplified, only show navigation on main menu
+    let help_text = t("help_navigate");
+    let status_text = format!("{} | {}", status, help_text);
+    let status_widget = Paragraph::new(status_text)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(status_widget, chunks[3]);
+}
+
+fn ui_subscriptions(
+    f: &mut Frame,
+    subscriptions: &[Subscription],
+    list_state: &mut ListState,
+    status: &str,
+    log: &str,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    // Title
+    let title = Paragraph::new("Subscriptions")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Subscription list
+    let items: Vec<ListItem> = subscriptions
+        .iter()
+        .enumerate()
+        .map(|(i, sub)| {
+            let content = vec![Line::from(vec![
+                Span::styled(format!("{}. ", i + 1), Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    &sub.channel_title,
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ])];
+            ListItem::new(content)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Subscriptions"),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, chunks[1], list_state);
+
+    // Log output (pink box)
+    let log_text = if log.is_empty() { "Ready" } else { log };
+    let log_widget = Paragraph::new(log_text)
+        .style(Style::default().fg(Color::Magenta))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("yt-dlp Output"),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(log_widget, chunks[2]);
+
+    // Status bar
+    let help_text = "↑/↓: Navigate | 1-9: Select item | PageUp/PageDown: Prev/Next Page | Enter/Space: View Videos | r: Refresh | X: Unsubscribe | Esc/m: Back | Ctrl+Q/Ctrl+C: Quit";
+    let status_text = format!("{} | {}", status, help_text);
+    let status_widget = Paragraph::new(status_text)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(status_widget, chunks[3]);
+}
+
+fn ui_accounts(
+    f: &mut Frame,
+    accounts: &[String],
+    current: Option<&str>,
+    list_state: &mut ListState,
+    status: &str,
+    log: &str,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    // Title
+    let title = Paragraph::new(t("accounts_title"))
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Account list, current account marked
+    let items: Vec<ListItem> = accounts
+        .iter()
+        .map(|name| {
+            let mut spans = vec![Span::styled(
+                name.as_str(),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            )];
+            if Some(name.as_str()) == current {
+                spans.push(Span::styled(
+                    format!(" {}", t("account_current")),
+                    Style::default().fg(Color::Green),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t("accounts_title")),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, chunks[1], list_state);
+
+    // Log output (pink box)
+    let log_text = if log.is_empty() { "Ready" } else { log };
+    let log_widget = Paragraph::new(log_text)
+        .style(Style::default().fg(Color::Magenta))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("yt-dlp Output"),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(log_widget, chunks[2]);
+
+    // Status bar
+    let help_text =
+        "↑/↓: Navigate | Enter/Space: Switch account | Esc/m: Back | Ctrl+Q/Ctrl+C: Quit";
+    let status_text = format!("{} | {}", status, help_text);
+    let status_widget = Paragraph::new(status_text)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(status_widget, chunks[3]);
+}
+
+fn ui_downloads(
+    f: &mut Frame,
+    jobs: &[DownloadJob],
+    completed: &[CompletedDownload],
+    list_state: &mut ListState,
+    status: &str,
+    log: &str,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(7),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    // Title with a summary
+    let running = jobs
+        .iter()
+        .filter(|job| job.state == JobState::Running)
+        .count();
+    let title = Paragraph::new(format!(
+        "{} ({} / {})",
+        t("downloads_title"),
+        running,
+        jobs.len()
+    ))
+    .style(
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )
+    .alignment(Alignment::Center)
+    .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Jobs with their state and progress
+    let items: Vec<ListItem> = jobs
+        .iter()
+        .map(|job| {
+            let (state, color) = match &job.state {
+                JobState::Queued => (t("download_queued"), Color::Gray),
+                JobState::Running => (t("download_running"), Color::Cyan),
+                JobState::Paused => (t("download_paused"), Color::Yellow),
+                JobState::Finished => (t("download_finished"), Color::Green),
+                JobState::Failed(_) => (t("download_failed"), Color::Red),
+                JobState::Cancelled => (t("download_cancelled"), Color::DarkGray),
+                JobState::Skipped => (t("download_skipped"), Color::Green),
+            };
+            let title = match (&job.target, &job.options.audio) {
+                (DownloadTarget::Video(video), Some(audio)) => {
+                    format!("♪ {} ({})", video.title, audio.codec)
+                }
+                (DownloadTarget::Video(video), None) => video.title.clone(),
+                (DownloadTarget::Playlist { title, .. }, _) => {
+                    format!("{}: {}", t("download_playlist"), title)
+                }
+                (DownloadTarget::Channel { title, .. }, _) => {
+                    format!("{}: {}", t("download_channel"), title)
+                }
+            };
+            let mut spans = vec![
+                Span::styled(format!("{:<12}", state), Style::default().fg(color)),
+                Span::styled(
+                    title,
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ];
+            if job.target.is_collection() && job.state != JobState::Queued {
+                spans.push(Span::styled(
+                    format!(" [{}]", download_items_text(job)),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            match &job.state {
+                JobState::Running | JobState::Paused => {
+                    if let Some(progress) = &job.progress {
+                        spans.push(Span::styled(
+                            format!(" {}", download_progress_text(progress)),
+                            Style::default().fg(Color::Gray),
+                        ));
+                    }
+                }
+                JobState::Finished | JobState::Skipped if !job.target.is_collection() => {
+                    if let Some(file) = &job.file {
+                        spans.push(Span::styled(
+                            format!(" → {}", file.display()),
+                            Style::default().fg(Color::Gray),
+                        ));
+                    }
+                }
+                JobState::Failed(error) => {
+                    spans.push(Span::styled(
+                        format!(" - {}", error),
+                        Style::default().fg(Color::Red),
+                    ));
+                }
+                _ => {}
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(t("downloads_title")),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, chunks[1], list_state);
+
+    // The videos of a selected playlist or channel download, newest first
+    let selected = list_state
+        .selected()
+        .and_then(|selected| jobs.get(selected));
+    if let Some(job) = selected.filter(|job| job.target.is_collection()) {
+        let video_items: Vec<ListItem> = job
+            .items
+            .iter()
+            .rev()
+            .take(5)
+            .map(|item| {
+                let (state, color) = match &item.state {
+                    ItemState::Running => (t("download_running"), Color::Cyan),
+                    ItemState::Finished => (t("download_finished"), Color::Green),
+                    ItemState::Archived => (t("download_archived"), Color::DarkGray),
+                    ItemState::Failed(_) => (t("download_failed"), Color::Red),
+                };
+                let mut spans = vec![
+                    Span::styled(format!("{:<12}", state), Style::default().fg(color)),
+                    Span::styled(item.title.clone(), Style::default().fg(Color::White)),
+                ];
+                if let ItemState::Failed(error) = &item.state {
+                    spans.push(Span::styled(
+                        format!(" - {}", error),
+                        Style::default().fg(Color::Red),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let video_list =
+            List::new(video_items).block(Block::default().borders(Borders::ALL).title(
+                t_with_args("downloads_items_title", &[("title", job.target.title())]),
+            ));
+        f.render_widget(video_list, chunks[2]);
+    } else {
+        render_completed_downloads(f, completed, chunks[2]);
+    }
+
+    // Log output (pink box)
+    let log_text = if log.is_empty() { "Ready" } else { log };
+    let log_widget = Paragraph::new(log_text)
+        .style(Style::default().fg(Color::Magenta))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("yt-dlp Output"),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(log_widget, chunks[3]);
+
+    // Status bar
+    let help_text = "↑/↓: Navigate | p/Space: Pause/Resume | c: Cancel | r: Retry | x: Remove | C: Clear Finished | Esc/m: Back";
+    let status_text = format!("{} | {}", status, help_text);
+    let status_widget = Paragraph::new(status_text)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(status_widget, chunks[4]);
+}
+
+// Helper: Recently completed files (recorded across runs)
+fn render_completed_downloads(f: &mut Frame, completed: &[CompletedDownload], area: Rect) {
+    let completed_items: Vec<ListItem> = completed
+        .iter()
+        .take(5)
+        .map(|done| {
+            let when = chrono::DateTime::from_timestamp(done.finished_at, 0)
+                .map(|dt| {
+                    dt.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}  ", when), Style::default().fg(Color::Gray)),
+                Span::styled(
+                    done.path.display().to_string(),
+                    Style::default().fg(Color::Green),
+                ),
+            ]))
+        })
+        .collect();
+    let completed_list = List::new(completed_items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(t("downloads_completed_title")),
+    );
+    f.render_widget(completed_list, area);
+}
+
+// Helper: "12/40 done, 3 already downloaded, 1 failed" for a playlist or channel download
+fn download_items_text(job: &DownloadJob) -> String {
+    let count = |state: fn(&ItemState) -> bool| {
+        job.items
+            .iter()
+            .filter(|item| state(&item.state))
+            .count()
+            .to_string()
+    };
+    let total = job
+        .item_count
+        .map_or_else(|| "?".to_string(), |count| count.to_string());
+    t_with_args(
+        "download_items",
+        &[
+            ("done", &count(|state| *state == ItemState::Finished)),
+            ("total", &total),
+            ("archived", &count(|state| *state == ItemState::Archived)),
+            (
+                "failed",
+                &count(|state| matches!(state, ItemState::Failed(_))),
+            ),
+        ],
+    )
+}
+
+// Helper: "[#####-----]  45.3%  2.1 MiB/s  ETA 0:35" for a download
+fn download_progress_text(progress: &DownloadProgress) -> String {
+    let mut parts = Vec::new();
+    if let Some(percent) = progress.percent() {
+        const WIDTH: usize = 20;
+        let filled = ((percent / 100.0) * WIDTH as f64).round() as usize;
+        parts.push(format!(
+            "[{}{}] {:>5.1}%",
+            "#".repeat(filled),
+            "-".repeat(WIDTH - filled),
+            percent
+        ));
+    } else {
+        parts.push(format_size(progress.downloaded));
+    }
+    if let Some(speed) = progress.speed {
+        parts.push(format!("{}/s", format_size(speed as u64)));
+    }
+    if let Some(eta) = progress.eta {
+        parts.push(format!("ETA {}", format_duration(eta)));
+    }
+    parts.join("  ")
+}
+
+fn ui_formats(
+    f: &mut Frame,
+    formats: &[VideoFormat],
+    list_state: &mut ListState,
+    video_title: &str,
+    status: &str,
+    log: &str,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    // Title
+    let title = Paragraph::new(format!("{}: {}", t("formats_title"), video_title))
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Formats, best first
+    let items: Vec<ListItem> = formats
+        .iter()
+        .map(|format| {
+            let color = match (format.has_video(), format.has_audio()) {
+                (true, true) => Color::White,
+                (true, false) => Color::Cyan,
+                _ => Color::Yellow,
+            };
+            ListItem::new(Line::from(Span::styled(
+                format_label(format),
+                Style::default().fg(color),
+            )))
+        })
+        .collect();
+
+    let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Select Option"),
+                .title(t("formats_title")),
         )
-        .alignment(Alignment::Left);
-    f.render_widget(menu, chunks[1]);
+        .highlight_style(
+            Style::default()
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, chunks[1], list_state);
 
     // Log output (pink box)
     let log_text = if log.is_empty() { "Ready" } else { log };
@@ -2453,8 +5138,9 @@ fn ui_channel_menu(f: &mut Frame, channel_name: &str, status: &str, log: &str) {
         .wrap(Wrap { trim: true });
     f.render_widget(log_widget, chunks[2]);
 
-    // Status
-    let help_text = "v/1: Videos | s/2: Shorts | p/3: Playlists | Esc: Back";
+    // Status bar
+    let help_text =
+        "↑/↓: Navigate | Enter/Space: Play in this format | d: Download in this format | Esc/m: Back";
     let status_text = format!("{} | {}", status, help_text);
     let status_widget = Paragraph::new(status_text)
         .style(Style::default().fg(Color::Green))
@@ -2463,7 +5149,14 @@ fn ui_channel_menu(f: &mut Frame, channel_name: &str, status: &str, log: &str) {
     f.render_widget(status_widget, chunks[3]);
 }
 
-fn ui_main_menu(f: &mut Frame, selection: usize, status: &str, log: &str) {
+fn ui_captions(
+    f: &mut Frame,
+    captions: &[CaptionTrack],
+    list_state: &mut ListState,
+    video_title: &str,
+    status: &str,
+    log: &str,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -2475,7 +5168,7 @@ fn ui_main_menu(f: &mut Frame, selection: usize, status: &str, log: &str) {
         .split(f.size());
 
     // Title
-    let title = Paragraph::new(t("app_title"))
+    let title = Paragraph::new(format!("{}: {}", t("captions_title"), video_title))
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -2485,105 +5178,43 @@ fn ui_main_menu(f: &mut Frame, selection: usize, status: &str, log: &str) {
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    // Menu options with selection highlighting
-    let menu_items: Vec<Line> = (0..7)
-        .map(|i| {
-            let (text, style) = match i {
-                0 => (
-                    t("menu_recommendations"),
-                    if selection == i {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    },
-                ),
-                1 => (
-                    t("menu_search"),
-                    if selection == i {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    },
-                ),
-                2 => (
-                    t("menu_history"),
-                    if selection == i {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    },
-                ),
-                3 => (
-                    t("menu_subscriptions"),
-                    if selection == i {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    },
-                ),
-                4 => (
-                    t("menu_playlists"),
-                    if selection == i {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    },
-                ),
-                5 => (
-                    t("menu_channel"),
-                    if selection == i {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    },
-                ),
-                6 => (
-                    t("menu_quit"),
-                    if selection == i {
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default().fg(Color::White)
-                    },
-                ),
-                _ => (String::new(), Style::default()),
+    // Uploaded subtitles, then automatic captions
+    let items: Vec<ListItem> = captions
+        .iter()
+        .map(|track| {
+            let (label, color) = if track.automatic {
+                (
+                    format!(
+                        "{:<10} {} ({})",
+                        track.language,
+                        track.name,
+                        t("caption_automatic")
+                    ),
+                    Color::Yellow,
+                )
+            } else {
+                (
+                    format!("{:<10} {}", track.language, track.name),
+                    Color::White,
+                )
             };
-            Line::from(vec![
-                Span::styled(
-                    if selection == i { "▶ " } else { "  " },
-                    Style::default().fg(if selection == i {
-                        Color::Yellow
-                    } else {
-                        Color::White
-                    }),
-                ),
-                Span::styled(text, style),
-            ])
+            ListItem::new(Line::from(Span::styled(label, Style::default().fg(color))))
         })
         .collect();
 
-    let menu = Paragraph::new(menu_items)
-        .style(Style::default().fg(Color::White))
+    let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(t("main_menu_title")),
+                .title(t("captions_title")),
         )
-        .alignment(Alignment::Left);
-    f.render_widget(menu, chunks[1]);
+        .highlight_style(
+            Style::default()
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, chunks[1], list_state);
 
     // Log output (pink box)
     let log_text = if log.is_empty() { "Ready" } else { log };
@@ -2597,8 +5228,8 @@ fn ui_main_menu(f: &mut Frame, selection: usize, status: &str, log: &str) {
         .wrap(Wrap { trim: true });
     f.render_widget(log_widget, chunks[2]);
 
-    // Status - simplified, only show navigation on main menu
-    let help_text = format!("{}", t("help_navigate"));
+    // Status bar
+    let help_text = "↑/↓: Navigate | Enter/Space: Play with these subtitles | Esc/m: Back";
     let status_text = format!("{} | {}", status, help_text);
     let status_widget = Paragraph::new(status_text)
         .style(Style::default().fg(Color::Green))
@@ -2607,13 +5238,43 @@ fn ui_main_menu(f: &mut Frame, selection: usize, status: &str, log: &str) {
     f.render_widget(status_widget, chunks[3]);
 }
 
-fn ui_subscriptions(
-    f: &mut Frame,
-    subscriptions: &[Subscription],
-    list_state: &mut ListState,
-    status: &str,
-    log: &str,
-) {
+// Helper: One line describing a format, like yt-dlp -F
+fn format_label(format: &VideoFormat) -> String {
+    let resolution = match (format.width, format.height) {
+        (Some(width), Some(height)) => format!("{}x{}", width, height),
+        (None, Some(height)) => format!("{}p", height),
+        _ => "audio only".to_string(),
+    };
+    let mut parts = vec![
+        format!("{:<6}", format.format_id),
+        format!("{:<5}", format.ext),
+        format!("{:<10}", resolution),
+    ];
+    if let Some(fps) = format.fps.filter(|_| format.has_video()) {
+        parts.push(format!("{}fps", fps.round()));
+    }
+    if let Some(range) = format.dynamic_range.as_deref().filter(|r| *r != "SDR") {
+        parts.push(range.to_string());
+    }
+    match (&format.vcodec, &format.acodec) {
+        (Some(vcodec), Some(acodec)) => parts.push(format!("{} + {}", vcodec, acodec)),
+        (Some(vcodec), None) => parts.push(format!("{} (video only)", vcodec)),
+        (None, Some(acodec)) => parts.push(acodec.clone()),
+        (None, None) => {}
+    }
+    if let Some(size) = format.filesize {
+        parts.push(format_size(size));
+    }
+    if let Some(bitrate) = format.bitrate {
+        parts.push(format!("{}k", bitrate.round()));
+    }
+    if !format.note.is_empty() {
+        parts.push(format.note.clone());
+    }
+    parts.join("  ")
+}
+
+fn ui_queue(f: &mut Frame, queue: &PlayQueue, list_state: &mut ListState, status: &str, log: &str) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -2624,32 +5285,70 @@ fn ui_subscriptions(
         ])
         .split(f.size());
 
-    // Title
-    let title = Paragraph::new("Subscriptions")
-        .style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+    // Title with the playback settings
+    let repeat = match queue.repeat {
+        RepeatMode::Off => t("queue_repeat_off"),
+        RepeatMode::All => t("queue_repeat_all"),
+        RepeatMode::One => t("queue_repeat_one"),
+    };
+    let shuffle = if queue.shuffle {
+        t("queue_shuffle_on")
+    } else {
+        t("queue_shuffle_off")
+    };
+    let title = Paragraph::new(format!(
+        "{} ({}) | {} | {}",
+        t("queue_title"),
+        queue.len(),
+        shuffle,
+        repeat
+    ))
+    .style(
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )
+    .alignment(Alignment::Center)
+    .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    // Subscription list
-    let items: Vec<ListItem> = subscriptions
+    // Queued videos; the current one is marked, played ones are dimmed
+    let items: Vec<ListItem> = queue
+        .items
         .iter()
         .enumerate()
-        .map(|(i, sub)| {
-            let content = vec![Line::from(vec![
-                Span::styled(format!("{}. ", i + 1), Style::default().fg(Color::Yellow)),
+        .map(|(i, item)| {
+            let color = if item.played {
+                Color::DarkGray
+            } else {
+                Color::White
+            };
+            let mut spans = vec![
+                Span::styled(format!("{:>3}. ", i + 1), Style::default().fg(color)),
                 Span::styled(
-                    &sub.channel_title,
-                    Style::default()
-                        .fg(Color::White)
-                        .add_modifier(Modifier::BOLD),
+                    item.video.title.as_str(),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
                 ),
-            ])];
-            ListItem::new(content)
+            ];
+            if !item.video.channel_title.is_empty() {
+                spans.push(Span::styled(
+                    format!(" - {}", item.video.channel_title),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            if let Some(duration) = item.video.duration {
+                spans.push(Span::styled(
+                    format!(" [{}]", format_duration(duration)),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            if queue.current == Some(i) {
+                spans.push(Span::styled(
+                    format!(" {}", t("queue_current")),
+                    Style::default().fg(Color::Green),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -2657,7 +5356,7 @@ fn ui_subscriptions(
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Subscriptions"),
+                .title(t("queue_title")),
         )
         .highlight_style(
             Style::default()
@@ -2680,7 +5379,7 @@ fn ui_subscriptions(
     f.render_widget(log_widget, chunks[2]);
 
     // Status bar
-    let help_text = "↑/↓: Navigate | 1-9: Select item | PageUp/PageDown: Prev/Next Page | Enter/Space: View Videos | r: Refresh | Esc/m: Back | Ctrl+Q/Ctrl+C: Quit";
+    let help_text = "↑/↓: Navigate | Shift+↑/↓: Move | Enter/Space: Play from here | a: Audio Only | n: Next | x: Remove | z: Shuffle | t: Repeat | C: Clear | Esc/m: Back";
     let status_text = format!("{} | {}", status, help_text);
     let status_widget = Paragraph::new(status_text)
         .style(Style::default().fg(Color::Green))
@@ -2836,7 +5535,7 @@ fn ui_playlists(
     f.render_widget(log_widget, chunks[2]);
 
     // Status bar
-    let help_text = "↑/↓: Navigate | 1-9: Select item | PageUp/PageDown: Prev/Next Page | Enter/Space: View Videos | r: Refresh | Esc/m: Back | Ctrl+Q/Ctrl+C: Quit";
+    let help_text = "↑/↓: Navigate | 1-9: Select item | PageUp/PageDown: Prev/Next Page | Enter/Space: View Videos | d: Download Playlist | r: Refresh | Esc/m: Back | Ctrl+Q/Ctrl+C: Quit";
     let status_text = format!("{} | {}", status, help_text);
     let status_widget = Paragraph::new(status_text)
         .style(Style::default().fg(Color::Green))
@@ -2901,6 +5600,7 @@ fn ui_input(f: &mut Frame, channel_url: &str, status: &str, log: &str) {
     f.render_widget(status_widget, chunks[4]);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn ui_videos(
     f: &mut Frame,
     videos: &[Video],
@@ -2909,6 +5609,7 @@ fn ui_videos(
     current_page: usize,
     total_pages: usize,
     log: &str,
+    progress: &HashMap<String, HistoryEntry>,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -2953,12 +5654,7 @@ fn ui_videos(
                             .add_modifier(Modifier::BOLD),
                     ),
                 ]),
-                Line::from(vec![
-                    Span::raw("  "),
-                    Span::styled(&video.channel_title, Style::default().fg(Color::Blue)),
-                    Span::raw(" • "),
-                    Span::styled(date, Style::default().fg(Color::Gray)),
-                ]),
+                video_details_line(video, date, progress.get(&video.id)),
             ];
             ListItem::new(content)
         })
@@ -2988,10 +5684,203 @@ fn ui_videos(
 
     // Status bar
     let help_text = if total_pages > 1 {
-        "↑/↓: Navigate | 1-9: Select item | p: Play | d: Download | c: Cancel | PageUp/PageDown: Prev/Next Page | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
+        "↑/↓: Navigate | 1-9: Select item | p: Play | a: Play Audio Only | d: Download | s: Download Audio | D: Download All | F: Formats | T: Subtitles | c: Cancel | L: Like | S: Subscribe | A: Add to Playlist | e/E: Queue Video/All | P: Play All | PageUp/PageDown: Prev/Next Page | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
     } else {
-        "↑/↓: Navigate | 1-9: Select item | p: Play | d: Download | c: Cancel | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
+        "↑/↓: Navigate | 1-9: Select item | p: Play | a: Play Audio Only | d: Download | s: Download Audio | D: Download All | F: Formats | T: Subtitles | c: Cancel | L: Like | S: Subscribe | A: Add to Playlist | e/E: Queue Video/All | P: Play All | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
+    };
+    let status_text = format!("{} | {}", status, help_text);
+    let status_widget = Paragraph::new(status_text)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+    f.render_widget(status_widget, chunks[3]);
+}
+
+// Helper: Drawn over the log box while mpv is playing
+fn ui_now_playing(f: &mut Frame, playing: &NowPlaying) {
+    let size = f.size();
+    if size.height < 6 {
+        return;
+    }
+    let area = ratatui::layout::Rect {
+        x: size.x,
+        y: size.y + size.height - 6,
+        width: size.width,
+        height: 3,
     };
+
+    let position = playing.position.unwrap_or(0.0) as u64;
+    let mut spans = vec![
+        Span::styled(
+            if playing.paused { "⏸ " } else { "▶ " },
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled(
+            playing.title.clone(),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw("  "),
+    ];
+    match playing.duration {
+        Some(duration) if duration > 0.0 => {
+            let filled = ((position as f64 / duration).min(1.0) * 20.0).round() as usize;
+            spans.push(Span::styled(
+                format!(
+                    "{} / {} {}{}",
+                    format_duration(position),
+                    format_duration(duration as u64),
+                    "▓".repeat(filled),
+                    "░".repeat(20 - filled)
+                ),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        _ => spans.push(Span::styled(
+            format_duration(position),
+            Style::default().fg(Color::Red),
+        )),
+    }
+    if let Some(volume) = playing.volume {
+        spans.push(Span::raw(" • "));
+        spans.push(Span::styled(
+            format!("Vol {:.0}%", volume),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    if let Some(speed) = playing.speed.filter(|s| (*s - 1.0).abs() > f64::EPSILON) {
+        spans.push(Span::raw(" • "));
+        spans.push(Span::styled(
+            format!("{:.2}x", speed),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+
+    let bar = Paragraph::new(Line::from(spans)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Now Playing (K: Pause | </>: Seek | -/+: Volume | {/}: Speed)"),
+    );
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(bar, area);
+}
+
+fn ui_history(
+    f: &mut Frame,
+    entries: &[HistoryEntry],
+    list_state: &mut ListState,
+    status: &str,
+    current_page: usize,
+    total_pages: usize,
+    log: &str,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    // Title with the day of the selected video, so paging shows where you are
+    let selected_day = list_state
+        .selected()
+        .and_then(|i| entries.get(i))
+        .map(|entry| entry.watched_at_local().format("%A, %d %B %Y").to_string());
+    let mut title_text = "Watch History".to_string();
+    if let Some(day) = selected_day {
+        title_text.push_str(&format!(" - {}", day));
+    }
+    if total_pages > 1 {
+        title_text.push_str(&format!(" (Page {}/{})", current_page, total_pages));
+    }
+    let title = Paragraph::new(title_text)
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let items: Vec<ListItem> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let mut details = vec![
+                Span::raw("  "),
+                Span::styled(&entry.channel_title, Style::default().fg(Color::Blue)),
+                Span::raw(" • "),
+                Span::styled(
+                    entry
+                        .watched_at_local()
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string(),
+                    Style::default().fg(Color::Gray),
+                ),
+            ];
+            let progress = match (entry.position, entry.duration) {
+                (Some(position), Some(duration)) => Some(format!(
+                    "{} / {}",
+                    format_duration(position),
+                    format_duration(duration)
+                )),
+                (Some(position), None) => Some(format_duration(position)),
+                (None, Some(duration)) => Some(format_duration(duration)),
+                (None, None) => None,
+            };
+            if let Some(progress) = progress {
+                details.push(Span::raw(" • "));
+                details.push(Span::styled(progress, Style::default().fg(Color::Gray)));
+            }
+            if let Some(bar) = progress_bar(entry, None) {
+                details.push(Span::raw(" • "));
+                details.push(bar);
+            }
+            let content = vec![
+                Line::from(vec![
+                    Span::styled(format!("{}. ", i + 1), Style::default().fg(Color::Yellow)),
+                    Span::styled(
+                        history_title(entry),
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ]),
+                Line::from(details),
+            ];
+            ListItem::new(content)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Videos"))
+        .highlight_style(
+            Style::default()
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, chunks[1], list_state);
+
+    // Log output (pink box)
+    let log_text = if log.is_empty() { "Ready" } else { log };
+    let log_widget = Paragraph::new(log_text)
+        .style(Style::default().fg(Color::Magenta))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("yt-dlp Output"),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(log_widget, chunks[2]);
+
+    // Status bar
+    let help_text = "↑/↓: Navigate | 1-9: Select item | p: Play | a: Play Audio Only | d: Download | s: Download Audio | F: Formats | T: Subtitles | e/E: Queue Video/All | P: Play All | [/]: Newer/Older Day | x: Remove | v: Mark Day | D: Clear Day/Range | C: Clear All | PageUp/PageDown: Prev/Next Page | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit";
     let status_text = format!("{} | {}", status, help_text);
     let status_widget = Paragraph::new(status_text)
         .style(Style::default().fg(Color::Green))
@@ -3000,6 +5889,16 @@ fn ui_videos(
     f.render_widget(status_widget, chunks[3]);
 }
 
+// Helper: Entries whose video couldn't be looked up only have an ID
+fn history_title(entry: &HistoryEntry) -> &str {
+    if entry.title.is_empty() {
+        &entry.video_id
+    } else {
+        &entry.title
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ui_channel_with_tabs(
     f: &mut Frame,
     videos: &[Video],
@@ -3010,6 +5909,7 @@ fn ui_channel_with_tabs(
     current_page: usize,
     total_pages: usize,
     log: &str,
+    progress: &HashMap<String, HistoryEntry>,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -3022,7 +5922,7 @@ fn ui_channel_with_tabs(
         .split(f.size());
 
     // Title with tabs
-    let tabs = vec!["Videos", "Shorts", "Playlists"];
+    let tabs = ["Videos", "Shorts", "Playlists"];
     let mut tab_spans = Vec::new();
     for (i, tab) in tabs.iter().enumerate() {
         if i > 0 {
@@ -3049,7 +5949,7 @@ fn ui_channel_with_tabs(
         format!("{} - {}", channel_name, tabs[active_tab])
     };
 
-    let title = Paragraph::new(vec![Line::from(tab_line), Line::from(title_text)])
+    let title = Paragraph::new(vec![tab_line, Line::from(title_text)])
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -3076,12 +5976,7 @@ fn ui_channel_with_tabs(
                             .add_modifier(Modifier::BOLD),
                     ),
                 ]),
-                Line::from(vec![
-                    Span::raw("  "),
-                    Span::styled(&video.channel_title, Style::default().fg(Color::Blue)),
-                    Span::raw(" • "),
-                    Span::styled(date, Style::default().fg(Color::Gray)),
-                ]),
+                video_details_line(video, date, progress.get(&video.id)),
             ];
             ListItem::new(content)
         })
@@ -3115,11 +6010,11 @@ fn ui_channel_with_tabs(
 
     // Status bar
     let help_text = if active_tab == 2 {
-        "↑/↓: Navigate | 1-9: Select item | Enter/Space: View Playlist | ←/→/1/2/3: Switch Tab | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
+        "↑/↓: Navigate | 1-9: Select item | Enter/Space: View Playlist | d: Download Playlist | ←/→/1/2/3: Switch Tab | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
     } else if total_pages > 1 {
-        "↑/↓: Navigate | 1-9: Select item | p: Play | d: Download | c: Cancel | ←/→/1/2/3: Switch Tab | PageUp/PageDown: Prev/Next Page | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
+        "↑/↓: Navigate | 1-9: Select item | p: Play | a: Play Audio Only | d: Download | s: Download Audio | D: Download All | F: Formats | T: Subtitles | c: Cancel | L: Like | S: Subscribe | A: Add to Playlist | e/E: Queue Video/All | P: Play All | ←/→/1/2/3: Switch Tab | PageUp/PageDown: Prev/Next Page | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
     } else {
-        "↑/↓: Navigate | 1-9: Select item | p: Play | d: Download | c: Cancel | ←/→/1/2/3: Switch Tab | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
+        "↑/↓: Navigate | 1-9: Select item | p: Play | a: Play Audio Only | d: Download | s: Download Audio | D: Download All | F: Formats | T: Subtitles | c: Cancel | L: Like | S: Subscribe | A: Add to Playlist | e/E: Queue Video/All | P: Play All | ←/→/1/2/3: Switch Tab | r: Refresh | Esc: Back | Ctrl+Q/Ctrl+C: Quit"
     };
     let status_text = format!("{} | {}", status, help_text);
     let status_widget = Paragraph::new(status_text)
@@ -3149,7 +6044,7 @@ fn ui_channel_with_tabs_playlists(
         .split(f.size());
 
     // Title with tabs
-    let tabs = vec!["Videos", "Shorts", "Playlists"];
+    let tabs = ["Videos", "Shorts", "Playlists"];
     let mut tab_spans = Vec::new();
     for (i, tab) in tabs.iter().enumerate() {
         if i > 0 {
@@ -3169,7 +6064,7 @@ fn ui_channel_with_tabs_playlists(
 
     let title_text = format!("{} - {}", channel_name, tabs[active_tab]);
 
-    let title = Paragraph::new(vec![Line::from(tab_line), Line::from(title_text)])
+    let title = Paragraph::new(vec![tab_line, Line::from(title_text)])
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -3233,7 +6128,7 @@ fn ui_channel_with_tabs_playlists(
     f.render_widget(log_widget, chunks[2]);
 
     // Status bar
-    let help_text = "↑/↓: Navigate | 1-9: Select item | PageUp/PageDown: Prev/Next Page | Enter/Space: View Playlist | ←/→/1/2/3: Switch Tab | Esc: Back | Ctrl+Q/Ctrl+C: Quit";
+    let help_text = "↑/↓: Navigate | 1-9: Select item | PageUp/PageDown: Prev/Next Page | Enter/Space: View Playlist | d: Download Playlist | ←/→/1/2/3: Switch Tab | Esc: Back | Ctrl+Q/Ctrl+C: Quit";
     let status_text = format!("{} | {}", status, help_text);
     let status_widget = Paragraph::new(status_text)
         .style(Style::default().fg(Color::Green))
@@ -3242,6 +6137,100 @@ fn ui_channel_with_tabs_playlists(
     f.render_widget(status_widget, chunks[3]);
 }
 
+// Second line of a video list item: channel, date, then duration/views when known
+fn video_details_line<'a>(
+    video: &'a Video,
+    date: String,
+    progress: Option<&HistoryEntry>,
+) -> Line<'a> {
+    let mut spans = vec![
+        Span::raw("  "),
+        Span::styled(&video.channel_title, Style::default().fg(Color::Blue)),
+        Span::raw(" • "),
+        Span::styled(date, Style::default().fg(Color::Gray)),
+    ];
+    if video.is_live {
+        spans.push(Span::raw(" • "));
+        spans.push(Span::styled(
+            "LIVE",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    } else if let Some(duration) = video.duration {
+        spans.push(Span::raw(" • "));
+        spans.push(Span::styled(
+            format_duration(duration),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    if let Some(views) = video.view_count {
+        spans.push(Span::raw(" • "));
+        spans.push(Span::styled(
+            t_with_args("video_views", &[("count", &format_count(views))]),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    if let Some(bar) = progress.and_then(|entry| progress_bar(entry, video.duration)) {
+        spans.push(Span::raw(" • "));
+        spans.push(bar);
+    }
+    Line::from(spans)
+}
+
+// Helper: How much of a video was played, e.g. "▓▓▓▓░░░░░░ 40%". Entries
+// recorded without a duration use the one from the list.
+fn progress_bar(entry: &HistoryEntry, duration: Option<u64>) -> Option<Span<'static>> {
+    if entry.watched {
+        return Some(Span::styled("✓ Watched", Style::default().fg(Color::Green)));
+    }
+    let duration = entry.duration.or(duration).filter(|d| *d > 0)?;
+    let progress = (entry.position? as f64 / duration as f64).min(1.0);
+    let filled = (progress * 10.0).round() as usize;
+    Some(Span::styled(
+        format!(
+            "{}{} {:.0}%",
+            "▓".repeat(filled),
+            "░".repeat(10 - filled),
+            progress * 100.0
+        ),
+        Style::default().fg(Color::Red),
+    ))
+}
+
+fn format_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let secs = seconds % 60;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1 << 30 {
+        format!("{:.1} GiB", bytes as f64 / (1u64 << 30) as f64)
+    } else if bytes >= 1 << 20 {
+        format!("{:.1} MiB", bytes as f64 / (1u64 << 20) as f64)
+    } else if bytes >= 1 << 10 {
+        format!("{:.1} KiB", bytes as f64 / (1u64 << 10) as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn format_count(count: u64) -> String {
+    if count >= 1_000_000_000 {
+        format!("{:.1}B", count as f64 / 1_000_000_000.0)
+    } else if count >= 1_000_000 {
+        format!("{:.1}M", count as f64 / 1_000_000.0)
+    } else if count >= 1_000 {
+        format!("{:.1}K", count as f64 / 1_000.0)
+    } else {
+        count.to_string()
+    }
+}
+
 fn format_date(date_str: &str) -> String {
     if date_str.is_empty() {
         return "Unknown date".to_string();
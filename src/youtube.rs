@@ -1,25 +1,56 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use futures_util::stream::{self, StreamExt};
 use reqwest::Client;
-use serde::Deserialize;
-use tokio::process::Command as TokioCommand;
-
-#[derive(Debug, Clone)]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::auth::{StaticToken, TokenProvider};
+use crate::cache::{MetadataCache, FEED_TTL, LIST_TTL, VIDEO_TTL};
+use crate::data_api::DataApiSource;
+use crate::feed::FeedSource;
+use crate::history::{HistoryEntry, WatchHistory};
+use crate::ytdlp::YtDlpSource;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Video {
     pub id: String,
     pub title: String,
+    pub channel_id: String,
     pub channel_title: String,
     pub published_at: String,
     pub thumbnail_url: String,
+    pub description: String,
+    pub duration: Option<u64>, // seconds
+    pub view_count: Option<u64>,
+    pub is_live: bool,
+    pub is_short: bool,
+    pub thumbnails: Vec<VideoThumbnail>,
+    // Set when the video was listed from one of the user's playlists
+    pub playlist_item_id: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoThumbnail {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Subscription {
     pub channel_id: String,
     pub channel_title: String,
     pub thumbnail_url: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Playlist {
     pub id: String,
     pub title: String,
@@ -27,808 +58,842 @@ pub struct Playlist {
     pub item_count: u32,
 }
 
-#[derive(Clone)]
-pub struct YouTubeClient {
-    client: Option<Client>,
-    access_token: Option<String>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    Like,
+    Dislike,
+    None,
 }
 
-impl YouTubeClient {
-    pub fn with_auth(client: Client, access_token: String) -> Self {
-        Self {
-            client: Some(client),
-            access_token: Some(access_token),
+impl Rating {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rating::Like => "like",
+            Rating::Dislike => "dislike",
+            Rating::None => "none",
         }
     }
+}
 
-    pub fn is_authenticated(&self) -> bool {
-        self.client.is_some() && self.access_token.is_some()
+// A backend the client can fetch videos from. Backends only implement what they
+// can answer; everything else reports `Unsupported` so the client moves on to the
+// next source configured for that feature.
+#[async_trait]
+pub trait VideoSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn is_authenticated(&self) -> bool {
+        false
     }
 
-    pub async fn get_channel_videos(&self, channel_url: &str) -> Result<Vec<Video>> {
-        use crate::deps;
-
-        // Check if yt-dlp is available, try to install if not
-        if !deps::check_ytdlp().await {
-            println!("yt-dlp not found. Attempting to install...");
-            if let Err(e) = deps::ensure_ytdlp().await {
-                return Err(anyhow::anyhow!(
-                    "yt-dlp is not installed and auto-installation failed: {}\n\
-                    Please install it manually:\n\
-                    Windows: winget install yt-dlp.yt-dlp",
-                    e
-                ));
-            }
-            // Verify it's now available
-            if !deps::check_ytdlp().await {
-                return Err(anyhow::anyhow!(
-                    "yt-dlp installation completed but it's still not available.\n\
-                    Please restart the program or install manually:\n\
-                    Windows: winget install yt-dlp.yt-dlp"
-                ));
-            }
-        }
+    async fn search_videos(&self, _query: &str) -> Result<Vec<Video>> {
+        Err(unsupported(self.name(), "search"))
+    }
 
-        // Use yt-dlp to get channel videos - prefer local version if available
-        #[cfg(windows)]
-        let ytdlp_cmd = if let Some(local_ytdlp) = deps::get_ytdlp_path().await {
-            local_ytdlp.to_str().unwrap().to_string()
-        } else {
-            "yt-dlp.exe".to_string()
-        };
-        #[cfg(not(windows))]
-        let ytdlp_cmd = "yt-dlp";
-
-        // Normalize the URL - ensure it's a full YouTube URL
-        let normalized_url = if channel_url.starts_with("http") {
-            channel_url.to_string()
-        } else if channel_url.starts_with("@") {
-            format!("https://www.youtube.com/{}/videos", channel_url)
-        } else {
-            format!("https://www.youtube.com/{}", channel_url)
-        };
+    async fn get_channel_videos(&self, _channel_url: &str) -> Result<Vec<Video>> {
+        Err(unsupported(self.name(), "channel videos"))
+    }
 
-        println!("Fetching videos from: {}", normalized_url);
-
-        // Use yt-dlp to get channel videos
-        let output = TokioCommand::new(ytdlp_cmd)
-            .args(&[
-                "--flat-playlist",
-                "--print",
-                "%(id)s|%(title)s|%(uploader)s|%(upload_date)s",
-                "--playlist-end",
-                "20", // Get top 20 videos
-                &normalized_url,
-            ])
-            .output()
-            .await;
+    async fn get_channel_videos_by_id(&self, _channel_id: &str) -> Result<Vec<Video>> {
+        Err(unsupported(self.name(), "channel videos"))
+    }
 
-        let output = match output {
-            Ok(output) => output,
-            Err(e) => {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    return Err(anyhow::anyhow!(
-                        "yt-dlp not found. Please install it:\n\
-                        Windows: winget install yt-dlp\n\
-                        Or the program will try to install it automatically on next run."
-                    ));
-                }
-                return Err(anyhow::anyhow!("Failed to run yt-dlp: {}", e));
-            }
-        };
+    async fn get_channel_playlists(&self, _channel_id: &str) -> Result<Vec<Playlist>> {
+        Err(unsupported(self.name(), "channel playlists"))
+    }
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            // Check if it's a "not found" error
-            if error.contains("not found") || error.contains("not recognized") || error.is_empty() {
-                return Err(anyhow::anyhow!(
-                    "yt-dlp is not installed or not in PATH.\n\
-                    Please install it:\n\
-                    Windows: winget install yt-dlp\n\
-                    Or run the program again to auto-install."
-                ));
-            }
+    async fn get_recommendations(&self) -> Result<Vec<Video>> {
+        Err(unsupported(self.name(), "recommendations"))
+    }
 
-            return Err(anyhow::anyhow!(
-                "Failed to get channel videos.\nError: {}\nOutput: {}",
-                error,
-                stdout
-            ));
-        }
+    async fn get_subscriptions(&self) -> Result<Vec<Subscription>> {
+        Err(unsupported(self.name(), "subscriptions"))
+    }
 
-        self.parse_ytdlp_output(&output.stdout).await
+    async fn get_playlists(&self) -> Result<Vec<Playlist>> {
+        Err(unsupported(self.name(), "playlists"))
     }
 
-    async fn parse_ytdlp_output(&self, output: &[u8]) -> Result<Vec<Video>> {
-        let mut videos = Vec::new();
-        let output_str = String::from_utf8_lossy(output);
+    // Recent uploads of these subscribed channels, newest first. `refresh` skips
+    // any cached results.
+    async fn get_subscription_feed(
+        &self,
+        _subscriptions: &[Subscription],
+        _refresh: bool,
+    ) -> Result<Vec<Video>> {
+        Err(unsupported(self.name(), "subscription feed"))
+    }
 
-        for line in output_str.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
+    async fn get_playlist_videos(&self, _playlist_id: &str) -> Result<Vec<Video>> {
+        Err(unsupported(self.name(), "playlist videos"))
+    }
 
-            let parts: Vec<&str> = line.split('|').collect();
-            if parts.len() >= 4 {
-                let id = parts[0].to_string();
-                let title = parts[1].to_string();
-                let uploader = parts[2].to_string();
-                let upload_date = parts.get(3).unwrap_or(&"").to_string();
-
-                // Format date
-                let formatted_date = if upload_date.len() >= 8 {
-                    format!(
-                        "{}-{}-{}",
-                        &upload_date[0..4],
-                        &upload_date[4..6],
-                        &upload_date[6..8]
-                    )
-                } else {
-                    upload_date
-                };
-
-                videos.push(Video {
-                    id,
-                    title,
-                    channel_title: uploader,
-                    published_at: formatted_date,
-                    thumbnail_url: String::new(),
-                });
-            }
-        }
+    async fn get_video(&self, _video_id: &str) -> Result<Video> {
+        Err(unsupported(self.name(), "video info"))
+    }
 
-        Ok(videos)
+    // Account changes; these need the write scope (see `auth::WRITE_SCOPE`)
+    async fn subscribe(&self, _channel_id: &str) -> Result<()> {
+        Err(unsupported(self.name(), "subscribing"))
     }
 
-    // Get subscriptions (requires authentication)
-    pub async fn get_subscriptions(&self) -> Result<Vec<Subscription>> {
-        let client = self.client.as_ref().context("Not authenticated")?;
-        let token = self.access_token.as_ref().context("Not authenticated")?;
+    async fn unsubscribe(&self, _channel_id: &str) -> Result<()> {
+        Err(unsupported(self.name(), "unsubscribing"))
+    }
 
-        let mut subscriptions = Vec::new();
-        let mut page_token: Option<String> = None;
+    async fn rate_video(&self, _video_id: &str, _rating: Rating) -> Result<()> {
+        Err(unsupported(self.name(), "rating videos"))
+    }
 
-        loop {
-            let mut url = "https://www.googleapis.com/youtube/v3/subscriptions?part=snippet&mine=true&maxResults=50".to_string();
-            if let Some(token) = &page_token {
-                url.push_str(&format!("&pageToken={}", token));
-            }
+    async fn add_to_playlist(&self, _playlist_id: &str, _video_id: &str) -> Result<()> {
+        Err(unsupported(self.name(), "adding to playlists"))
+    }
+
+    async fn remove_from_playlist(&self, _playlist_item_id: &str) -> Result<()> {
+        Err(unsupported(self.name(), "removing from playlists"))
+    }
+}
 
-            let response = client.get(&url).bearer_auth(token).send().await?;
+// Error returned by a source for operations it doesn't implement
+#[derive(Debug)]
+pub struct Unsupported {
+    source: &'static str,
+    operation: &'static str,
+}
 
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                return Err(anyhow::anyhow!("API error: {}", error_text));
-            }
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is not supported by the {} backend",
+            self.operation, self.source
+        )
+    }
+}
 
-            let response_text = response.text().await?;
-            let data: SubscriptionResponse = serde_json::from_str(&response_text).map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to parse subscription response: {}\nResponse: {}",
-                    e,
-                    response_text
-                )
-            })?;
-
-            let items_count = data.items.len();
-            for item in data.items {
-                if let Some(resource_id) = &item.snippet.resource_id {
-                    subscriptions.push(Subscription {
-                        channel_id: resource_id.channel_id.clone(),
-                        channel_title: item.snippet.title.clone(),
-                        thumbnail_url: item.snippet.thumbnails.default.url.clone(),
-                    });
-                } else {
-                    // Log warning for items without resource_id
-                    eprintln!(
-                        "Warning: Subscription item missing resource_id: {:?}",
-                        item.snippet.title
-                    );
-                }
-            }
+impl std::error::Error for Unsupported {}
 
-            // If we got items but none had resource_id, log warning
-            if items_count > 0 && subscriptions.is_empty() {
-                eprintln!(
-                    "Warning: Received {} subscription items but none had resource_id",
-                    items_count
-                );
-            }
+pub(crate) fn unsupported(source: &'static str, operation: &'static str) -> anyhow::Error {
+    anyhow::Error::new(Unsupported { source, operation })
+}
 
-            page_token = data.next_page_token;
-            if page_token.is_none() {
-                break;
-            }
+// Groups of client methods that can each be routed to their own sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    Search,
+    Channels,
+    Recommendations,
+    Subscriptions,
+    // The chronological feed of subscribed channels
+    Feed,
+    Playlists,
+    Metadata,
+    // Subscribing, rating and editing playlists
+    Actions,
+}
+
+const ALL_FEATURES: [Feature; 8] = [
+    Feature::Search,
+    Feature::Channels,
+    Feature::Recommendations,
+    Feature::Subscriptions,
+    Feature::Feed,
+    Feature::Playlists,
+    Feature::Metadata,
+    Feature::Actions,
+];
+
+// History entries whose metadata is looked up at the same time
+const HISTORY_CONCURRENCY: usize = 4;
+
+// Front door for the UI: routes every call to the sources configured for its
+// feature, trying them in order until one returns something. With a cache, every
+// call reads through it.
+#[derive(Clone)]
+pub struct YouTubeClient {
+    sources: HashMap<Feature, Vec<Arc<dyn VideoSource>>>,
+    cache: Option<Arc<MetadataCache>>,
+    bypass_cache: bool,
+    history: Option<Arc<WatchHistory>>,
+}
+
+impl YouTubeClient {
+    // Use a single source for every feature
+    pub fn new(source: Arc<dyn VideoSource>) -> Self {
+        Self {
+            sources: ALL_FEATURES
+                .iter()
+                .map(|feature| (*feature, vec![source.clone()]))
+                .collect(),
+            cache: None,
+            bypass_cache: false,
+            history: None,
         }
+    }
 
-        Ok(subscriptions)
+    pub fn with_cache(mut self, cache: Arc<MetadataCache>) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
-    // Get playlists
-    pub async fn get_playlists(&self) -> Result<Vec<Playlist>> {
-        let client = self.client.as_ref().context("Not authenticated")?;
-        let token = self.access_token.as_ref().context("Not authenticated")?;
+    // Keep the watch history somewhere other than the config directory
+    pub fn with_history(mut self, history: Arc<WatchHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
 
-        let mut playlists = Vec::new();
-        let mut page_token: Option<String> = None;
+    // The same client, but always asking the sources (results are still cached)
+    pub fn fresh(&self) -> Self {
+        Self {
+            bypass_cache: true,
+            ..self.clone()
+        }
+    }
 
-        loop {
-            let mut url = "https://www.googleapis.com/youtube/v3/playlists?part=snippet,contentDetails&mine=true&maxResults=50".to_string();
-            if let Some(token) = &page_token {
-                url.push_str(&format!("&pageToken={}", token));
-            }
+    pub fn builder() -> YouTubeClientBuilder {
+        YouTubeClientBuilder::default()
+    }
+
+    // Replace the sources used for a feature
+    pub fn with_source(mut self, feature: Feature, source: Arc<dyn VideoSource>) -> Self {
+        self.sources.insert(feature, vec![source]);
+        self
+    }
 
-            let response = client.get(&url).bearer_auth(token).send().await?;
+    // Add a source to try after the existing ones for a feature
+    pub fn with_fallback(mut self, feature: Feature, source: Arc<dyn VideoSource>) -> Self {
+        self.sources.entry(feature).or_default().push(source);
+        self
+    }
 
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                return Err(anyhow::anyhow!("API error: {}", error_text));
-            }
+    pub fn is_authenticated(&self) -> bool {
+        self.sources
+            .values()
+            .flatten()
+            .any(|source| source.is_authenticated())
+    }
 
-            let response_text = response.text().await?;
-            let data: PlaylistResponse = serde_json::from_str(&response_text).map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to parse playlist response: {}\nResponse: {}",
-                    e,
-                    response_text
-                )
-            })?;
-
-            for item in data.items {
-                let item_count = item
-                    .content_details
-                    .as_ref()
-                    .map(|cd| cd.item_count)
-                    .unwrap_or(0);
-                playlists.push(Playlist {
-                    id: item.id,
-                    title: item.snippet.title,
-                    description: item.snippet.description,
-                    item_count,
-                });
-            }
+    fn sources(&self, feature: Feature) -> &[Arc<dyn VideoSource>] {
+        self.sources.get(&feature).map(Vec::as_slice).unwrap_or(&[])
+    }
 
-            page_token = data.next_page_token;
-            if page_token.is_none() {
-                break;
+    // Helper: Ask each source for a feature in turn. The first non-empty result wins;
+    // an empty result is only returned if no later source has anything either.
+    async fn first_success<T, F, Fut>(&self, feature: Feature, call: F) -> Result<Vec<T>>
+    where
+        F: Fn(Arc<dyn VideoSource>) -> Fut,
+        Fut: Future<Output = Result<Vec<T>>>,
+    {
+        let mut empty_result = None;
+        let mut last_error: Option<anyhow::Error> = None;
+
+        for source in self.sources(feature) {
+            match call(source.clone()).await {
+                Ok(items) if !items.is_empty() => return Ok(items),
+                Ok(items) => {
+                    empty_result.get_or_insert(items);
+                }
+                Err(e) => {
+                    // Don't let "not supported" hide a real failure from an earlier source
+                    if last_error.is_none() || !e.is::<Unsupported>() {
+                        last_error = Some(e);
+                    }
+                }
             }
         }
 
-        Ok(playlists)
+        if let Some(items) = empty_result {
+            return Ok(items);
+        }
+        Err(last_error
+            .unwrap_or_else(|| anyhow::anyhow!("No video source configured for {:?}", feature)))
     }
 
-    // Get videos from a playlist
-    pub async fn get_playlist_videos(&self, playlist_id: &str) -> Result<Vec<Video>> {
-        let client = self.client.as_ref().context("Not authenticated")?;
-        let token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let mut videos = Vec::new();
-        let mut page_token: Option<String> = None;
-
-        loop {
-            let mut url = format!("https://www.googleapis.com/youtube/v3/playlistItems?part=snippet,contentDetails&playlistId={}&maxResults=50", playlist_id);
-            if let Some(token) = &page_token {
-                url.push_str(&format!("&pageToken={}", token));
-            }
+    // Helper: Use the cached value while it is younger than `ttl`, otherwise fetch
+    // and cache it. Stale values stand in when fetching fails (e.g. offline).
+    async fn read_through<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl: std::time::Duration,
+        refresh: bool,
+        fetch: F,
+    ) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let result = self.read_through_unsaved(key, ttl, refresh, fetch).await;
+        self.save_cache();
+        result
+    }
 
-            let response = client.get(&url).bearer_auth(token).send().await?;
+    // Helper: `read_through` without writing the cache to disk, for batches
+    async fn read_through_unsaved<T, F, Fut>(
+        &self,
+        key: &str,
+        ttl: std::time::Duration,
+        refresh: bool,
+        fetch: F,
+    ) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let Some(cache) = &self.cache else {
+            return fetch().await;
+        };
 
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                return Err(anyhow::anyhow!("API error: {}", error_text));
+        if !refresh && !self.bypass_cache {
+            if let Some(value) = cache.get(key, ttl) {
+                return Ok(value);
             }
+        }
 
-            let response_text = response.text().await?;
-            let data: PlaylistItemsResponse =
-                serde_json::from_str(&response_text).map_err(|e| {
-                    anyhow::anyhow!(
-                        "Failed to parse playlist items response: {}\nResponse: {}",
-                        e,
-                        response_text
-                    )
-                })?;
-
-            for item in data.items {
-                // Try to get video ID from content_details first, then from resourceId
-                let video_id = if let Some(content_details) = &item.content_details {
-                    Some(content_details.video_id.clone())
-                } else if let Some(resource_id) = &item.snippet.resource_id {
-                    resource_id.video_id.clone()
-                } else {
-                    None
-                };
-
-                if let Some(vid_id) = video_id {
-                    videos.push(Video {
-                        id: vid_id,
-                        title: item.snippet.title.clone(),
-                        channel_title: item
-                            .snippet
-                            .channel_title
-                            .clone()
-                            .unwrap_or_else(|| "Unknown Channel".to_string()),
-                        published_at: item
-                            .snippet
-                            .published_at
-                            .clone()
-                            .unwrap_or_else(|| "Unknown date".to_string()),
-                        thumbnail_url: item.snippet.thumbnails.default.url.clone(),
-                    });
-                } else {
-                    // If we can't get video ID, skip this item
-                    eprintln!(
-                        "Warning: Playlist item missing video ID, skipping: {}",
-                        item.snippet.title
-                    );
-                }
+        match fetch().await {
+            Ok(value) => {
+                cache.put(key, &value);
+                Ok(value)
             }
+            Err(e) => cache.get_stale(key).ok_or(e),
+        }
+    }
 
-            page_token = data.next_page_token;
-            if page_token.is_none() {
-                break;
-            }
+    // Helper: Write new cache entries now and then; the rest goes to disk
+    // when the cache is dropped
+    fn save_cache(&self) {
+        if let Some(cache) = &self.cache {
+            // Not being able to write the cache shouldn't fail the call
+            cache.save_if_due().ok();
         }
+    }
 
-        Ok(videos)
+    // Drop everything cached for this client's account (e.g. on sign-out)
+    pub fn clear_cache(&self) -> Result<()> {
+        match &self.cache {
+            Some(cache) => cache.clear(),
+            None => Ok(()),
+        }
     }
 
-    // Helper: Get channel videos by channel ID (using API)
-    pub async fn get_channel_videos_by_id(&self, channel_id: &str) -> Result<Vec<Video>> {
-        let client = self.client.as_ref().context("Not authenticated")?;
-        let token = self.access_token.as_ref().context("Not authenticated")?;
+    async fn cached<T, F, Fut>(&self, key: &str, ttl: std::time::Duration, fetch: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.read_through(key, ttl, false, fetch).await
+    }
 
-        // Get uploads playlist ID
-        let url = format!(
-            "https://www.googleapis.com/youtube/v3/channels?part=contentDetails&id={}",
-            channel_id
-        );
-        let response = client.get(&url).bearer_auth(token).send().await?;
+    // Helper: Forget cached lists an account change made outdated
+    fn invalidate(&self, prefixes: &[&str]) {
+        if let Some(cache) = &self.cache {
+            for prefix in prefixes {
+                cache.remove_prefix(prefix);
+            }
+            // Right away, so a later run never sees the outdated lists
+            cache.save().ok();
+        }
+    }
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("Failed to get channel info"));
+    // Helper: Run an account change on the first source that supports it. Real
+    // failures are returned right away so a change is never applied twice.
+    async fn first_supported<F, Fut>(&self, call: F) -> Result<()>
+    where
+        F: Fn(Arc<dyn VideoSource>) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let mut unsupported_error = None;
+
+        for source in self.sources(Feature::Actions) {
+            match call(source.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(e) if e.is::<Unsupported>() => {
+                    unsupported_error.get_or_insert(e);
+                }
+                Err(e) => return Err(e),
+            }
         }
 
-        let data: serde_json::Value = response.json().await?;
-        let uploads_playlist_id = data["items"][0]["contentDetails"]["relatedPlaylists"]["uploads"]
-            .as_str()
-            .context("No uploads playlist found")?;
+        Err(unsupported_error
+            .unwrap_or_else(|| anyhow::anyhow!("No video source configured for account changes")))
+    }
+
+    pub async fn get_channel_videos(&self, channel_url: &str) -> Result<Vec<Video>> {
+        self.cached(&format!("channel-url:{}", channel_url), LIST_TTL, || {
+            self.first_success(Feature::Channels, |source| async move {
+                source.get_channel_videos(channel_url).await
+            })
+        })
+        .await
+    }
 
-        // Get videos from uploads playlist
-        self.get_playlist_videos(uploads_playlist_id).await
+    pub async fn get_channel_videos_by_id(&self, channel_id: &str) -> Result<Vec<Video>> {
+        self.cached(&format!("channel-videos:{}", channel_id), LIST_TTL, || {
+            self.first_success(Feature::Channels, |source| async move {
+                source.get_channel_videos_by_id(channel_id).await
+            })
+        })
+        .await
     }
 
-    // Get channel playlists by channel ID
     pub async fn get_channel_playlists(&self, channel_id: &str) -> Result<Vec<Playlist>> {
-        let client = self.client.as_ref().context("Not authenticated")?;
-        let token = self.access_token.as_ref().context("Not authenticated")?;
-
-        let mut playlists = Vec::new();
-        let mut page_token: Option<String> = None;
+        self.cached(
+            &format!("channel-playlists:{}", channel_id),
+            LIST_TTL,
+            || {
+                self.first_success(Feature::Channels, |source| async move {
+                    source.get_channel_playlists(channel_id).await
+                })
+            },
+        )
+        .await
+    }
 
-        loop {
-            let mut url = format!("https://www.googleapis.com/youtube/v3/playlists?part=snippet,contentDetails&channelId={}&maxResults=50", channel_id);
-            if let Some(token) = &page_token {
-                url.push_str(&format!("&pageToken={}", token));
-            }
+    pub async fn get_subscriptions(&self) -> Result<Vec<Subscription>> {
+        self.cached("subscriptions", LIST_TTL, || {
+            self.first_success(Feature::Subscriptions, |source| async move {
+                source.get_subscriptions().await
+            })
+        })
+        .await
+    }
 
-            let response = client.get(&url).bearer_auth(token).send().await?;
+    // The channel list comes through the cache like any other call, so
+    // refreshing the feed doesn't spend quota on listing subscriptions again
+    pub async fn get_subscription_feed(&self, refresh: bool) -> Result<Vec<Video>> {
+        self.read_through("feed", FEED_TTL, refresh, || async {
+            let subscriptions = self.get_subscriptions().await?;
+            let subscriptions = &subscriptions;
+            self.first_success(Feature::Feed, |source| async move {
+                source.get_subscription_feed(subscriptions, refresh).await
+            })
+            .await
+        })
+        .await
+    }
 
-            if !response.status().is_success() {
-                let error_text = response.text().await?;
-                return Err(anyhow::anyhow!("API error: {}", error_text));
-            }
+    pub async fn get_playlists(&self) -> Result<Vec<Playlist>> {
+        self.cached("playlists", LIST_TTL, || {
+            self.first_success(Feature::Playlists, |source| async move {
+                source.get_playlists().await
+            })
+        })
+        .await
+    }
 
-            let response_text = response.text().await?;
-            let data: PlaylistResponse = serde_json::from_str(&response_text).map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to parse playlist response: {}\nResponse: {}",
-                    e,
-                    response_text
-                )
-            })?;
-
-            for item in data.items {
-                let item_count = item
-                    .content_details
-                    .as_ref()
-                    .map(|cd| cd.item_count)
-                    .unwrap_or(0);
-                playlists.push(Playlist {
-                    id: item.id,
-                    title: item.snippet.title,
-                    description: item.snippet.description,
-                    item_count,
-                });
-            }
+    pub async fn get_playlist_videos(&self, playlist_id: &str) -> Result<Vec<Video>> {
+        self.cached(
+            &format!("playlist-videos:{}", playlist_id),
+            LIST_TTL,
+            || {
+                self.first_success(Feature::Playlists, |source| async move {
+                    source.get_playlist_videos(playlist_id).await
+                })
+            },
+        )
+        .await
+    }
 
-            page_token = data.next_page_token;
-            if page_token.is_none() {
-                break;
-            }
-        }
+    pub async fn get_recommendations(&self) -> Result<Vec<Video>> {
+        self.cached("recommendations", FEED_TTL, || {
+            self.first_success(Feature::Recommendations, |source| async move {
+                source.get_recommendations().await
+            })
+        })
+        .await
+    }
 
-        Ok(playlists)
+    pub async fn search_videos(&self, query: &str) -> Result<Vec<Video>> {
+        self.cached(&format!("search:{}", query), FEED_TTL, || {
+            self.first_success(Feature::Search, |source| async move {
+                source.search_videos(query).await
+            })
+        })
+        .await
     }
 
-    // Get YouTube homepage recommendations
-    // Note: YouTube's personalized homepage requires authentication and JavaScript.
-    // We try multiple methods to get trending/popular videos.
-    pub async fn get_recommendations(&self) -> Result<Vec<Video>> {
-        use crate::deps;
+    // A single video's details, from the first metadata source that has them
+    pub async fn get_video(&self, video_id: &str) -> Result<Video> {
+        let result = self.get_video_unsaved(video_id).await;
+        self.save_cache();
+        result
+    }
 
-        // First, try using authenticated API if available (for personalized recommendations)
-        if self.is_authenticated() {
-            if let Ok(videos) = self.get_recommendations_via_api().await {
-                if !videos.is_empty() {
-                    return Ok(videos);
+    async fn get_video_unsaved(&self, video_id: &str) -> Result<Video> {
+        let key = format!("video:{}", video_id);
+        self.read_through_unsaved(&key, VIDEO_TTL, false, || async {
+            let mut last_error = None;
+            for source in self.sources(Feature::Metadata) {
+                match source.get_video(video_id).await {
+                    Ok(video) => return Ok(video),
+                    Err(e) => last_error = Some(e),
                 }
             }
-        }
+            Err(last_error
+                .unwrap_or_else(|| anyhow::anyhow!("No video source configured for video info")))
+        })
+        .await
+    }
 
-        // Check if yt-dlp is available
-        if !deps::check_ytdlp().await {
-            if let Err(e) = deps::ensure_ytdlp().await {
-                return Err(anyhow::anyhow!("yt-dlp is not installed: {}", e));
-            }
-        }
+    pub async fn subscribe(&self, channel_id: &str) -> Result<()> {
+        self.first_supported(|source| async move { source.subscribe(channel_id).await })
+            .await?;
+        self.invalidate(&["subscriptions", "feed"]);
+        Ok(())
+    }
 
-        #[cfg(windows)]
-        let ytdlp_cmd = if let Some(local_ytdlp) = deps::get_ytdlp_path().await {
-            local_ytdlp.to_str().unwrap().to_string()
-        } else {
-            "yt-dlp.exe".to_string()
-        };
-        #[cfg(not(windows))]
-        let ytdlp_cmd = "yt-dlp";
-
-        // Try multiple methods to get trending/popular videos
-        // YouTube feeds don't work well with yt-dlp, so we use alternative approaches
-        // Note: YouTube's homepage requires JavaScript, so we use trending/popular content instead
-        let methods: Vec<(&str, Vec<&str>)> = vec![
-            // Method 1: Use trending URL with web client (most reliable)
-            (
-                "https://www.youtube.com/feed/trending",
-                vec!["--extractor-args", "youtube:player_client=web"],
-            ),
-            // Method 2: Use trending URL without extra args
-            ("https://www.youtube.com/feed/trending", vec![]),
-            // Method 3: Use a popular channel's videos as fallback (MrBeast)
-            ("https://www.youtube.com/@MrBeast/videos", vec![]),
-            // Method 4: Try another popular channel (PewDiePie)
-            ("https://www.youtube.com/@PewDiePie/videos", vec![]),
-        ];
-
-        let mut last_error = None;
-
-        for (url, extra_args) in methods {
-            let mut args = vec![
-                "--flat-playlist",
-                "--print",
-                "%(id)s|%(title)s|%(uploader)s|%(upload_date)s",
-                "--playlist-end",
-                "50",
-                "--no-warnings",
-            ];
-            args.extend(extra_args);
-            args.push(url);
-
-            let result = TokioCommand::new(&ytdlp_cmd).args(&args).output().await;
-
-            match result {
-                Ok(cmd_output) if cmd_output.status.success() => {
-                    let stdout_str = String::from_utf8_lossy(&cmd_output.stdout);
-                    if !stdout_str.trim().is_empty() {
-                        if let Ok(videos) = self.parse_ytdlp_output(&cmd_output.stdout).await {
-                            if !videos.is_empty() {
-                                return Ok(videos);
-                            }
-                        }
-                    }
-                }
-                Ok(cmd_output) => {
-                    let error = String::from_utf8_lossy(&cmd_output.stderr);
-                    if !error.contains("Unsupported URL") {
-                        last_error = Some(error.trim().to_string());
-                    }
-                }
-                Err(e) => {
-                    last_error = Some(format!("Error: {}", e));
-                }
-            }
-        }
+    pub async fn unsubscribe(&self, channel_id: &str) -> Result<()> {
+        self.first_supported(|source| async move { source.unsubscribe(channel_id).await })
+            .await?;
+        self.invalidate(&["subscriptions", "feed"]);
+        Ok(())
+    }
+
+    pub async fn rate_video(&self, video_id: &str, rating: Rating) -> Result<()> {
+        self.first_supported(|source| async move { source.rate_video(video_id, rating).await })
+            .await
+    }
 
-        // If all methods failed, return a helpful error
-        Err(anyhow::anyhow!(
-            "Could not fetch recommendations.\n\nYouTube's personalized homepage requires authentication and JavaScript rendering.\nTrending feeds may not be available in your region.\n\nSuggestions:\n- Use 'Search' to find videos\n- Use 'Subscriptions' if you're authenticated\n- Try authenticating to get personalized recommendations\n\nLast error: {}",
-            last_error.unwrap_or_else(|| "Unknown error".to_string())
-        ))
+    pub async fn add_to_playlist(&self, playlist_id: &str, video_id: &str) -> Result<()> {
+        self.first_supported(|source| async move {
+            source.add_to_playlist(playlist_id, video_id).await
+        })
+        .await?;
+        self.invalidate(&[&format!("playlist-videos:{}", playlist_id), "playlists"]);
+        Ok(())
     }
 
-    // Try to get recommendations via YouTube Data API (if authenticated)
-    // Gets recent videos from user's subscriptions as personalized recommendations
-    async fn get_recommendations_via_api(&self) -> Result<Vec<Video>> {
-        // Note: The 'home' parameter in activities.list is deprecated
-        // So we get videos from user's subscriptions as a form of recommendations
-        let subscriptions = self.get_subscriptions().await?;
+    pub async fn remove_from_playlist(&self, playlist_item_id: &str) -> Result<()> {
+        self.first_supported(|source| async move {
+            source.remove_from_playlist(playlist_item_id).await
+        })
+        .await?;
+        // Only the item ID is known here, so forget every playlist
+        self.invalidate(&["playlist-videos:", "playlists"]);
+        Ok(())
+    }
 
-        if subscriptions.is_empty() {
-            return Ok(Vec::new());
+    // Watch history, newest first. Entries migrated from history.txt only have
+    // an ID, so their details are looked up (and stored) here.
+    pub async fn get_watch_history(&self) -> Result<Vec<HistoryEntry>> {
+        let history = self.watch_history()?;
+        let mut entries = history.load()?;
+
+        let missing: Vec<String> = entries
+            .iter()
+            .filter(|entry| entry.needs_details())
+            .map(|entry| entry.video_id.clone())
+            .collect();
+        if missing.is_empty() {
+            return Ok(entries);
         }
 
-        // Get recent videos from first few subscriptions
-        let mut all_videos = Vec::new();
-        for sub in subscriptions.iter().take(5) {
-            if let Ok(videos) = self.get_channel_videos_by_id(&sub.channel_id).await {
-                all_videos.extend(videos.into_iter().take(10));
-            }
+        // Cached ones load instantly; videos that are gone keep just their ID
+        let videos: Vec<Video> = stream::iter(missing)
+            .map(|video_id| async move { self.get_video_unsaved(&video_id).await })
+            .buffered(HISTORY_CONCURRENCY)
+            .filter_map(|result| async move { result.ok() })
+            .collect()
+            .await;
+        self.save_cache();
+
+        if !videos.is_empty() {
+            history.update_details(&videos)?;
+            entries = history.load()?;
         }
+        Ok(entries)
+    }
 
-        // Limit to 50 videos
-        all_videos.truncate(50);
-        Ok(all_videos)
+    // Add a video to the top of the watch history
+    pub async fn add_to_history(&self, video: &Video) -> Result<()> {
+        self.watch_history()?.record(video)
     }
 
-    // Get watch history from local file
-    pub async fn get_watch_history(&self) -> Result<Vec<Video>> {
-        use crate::deps;
-        use std::fs;
+    // Where playback stopped, so the video can be resumed later
+    pub fn set_watch_position(&self, video_id: &str, position: u64) -> Result<()> {
+        self.watch_history()?.set_position(video_id, position)
+    }
 
-        // Get history file path
-        let history_file = get_history_file_path()?;
+    pub fn mark_watched(&self, video_id: &str) -> Result<()> {
+        self.watch_history()?.mark_watched(video_id)
+    }
 
-        // Read history file
-        let history_content = if history_file.exists() {
-            fs::read_to_string(&history_file).unwrap_or_default()
-        } else {
-            return Ok(vec![]); // Return empty if file doesn't exist
-        };
+    // History entries by video ID, for showing progress in any list (no lookups)
+    pub fn watch_progress(&self) -> Result<HashMap<String, HistoryEntry>> {
+        Ok(self
+            .watch_history()?
+            .load()?
+            .into_iter()
+            .map(|entry| (entry.video_id.clone(), entry))
+            .collect())
+    }
 
-        // Parse video IDs from file (one per line)
-        let video_ids: Vec<String> = history_content
-            .lines()
-            .map(|line| line.trim().to_string())
-            .filter(|line| !line.is_empty())
-            .collect();
+    pub fn remove_from_history(&self, video_id: &str) -> Result<()> {
+        self.watch_history()?.remove(video_id)
+    }
 
-        if video_ids.is_empty() {
-            return Ok(vec![]);
-        }
+    // Forget everything watched between two days (inclusive)
+    pub fn clear_history_between(&self, from: NaiveDate, to: NaiveDate) -> Result<usize> {
+        self.watch_history()?.remove_between(from, to)
+    }
 
-        // Check if yt-dlp is available for fetching video metadata
-        if !deps::check_ytdlp().await {
-            if let Err(e) = deps::ensure_ytdlp().await {
-                return Err(anyhow::anyhow!("yt-dlp is not installed: {}", e));
-            }
-        }
+    pub fn clear_history(&self) -> Result<()> {
+        self.watch_history()?.clear()
+    }
 
-        #[cfg(windows)]
-        let ytdlp_cmd = if let Some(local_ytdlp) = deps::get_ytdlp_path().await {
-            local_ytdlp.to_str().unwrap().to_string()
-        } else {
-            "yt-dlp.exe".to_string()
-        };
-        #[cfg(not(windows))]
-        let ytdlp_cmd = "yt-dlp";
-
-        // Fetch metadata for each video ID
-        let mut videos = Vec::new();
-        for video_id in video_ids {
-            // Use yt-dlp to get video metadata
-            let output = TokioCommand::new(&ytdlp_cmd)
-                .args(&[
-                    "--skip-download",
-                    "--print",
-                    "%(id)s|%(title)s|%(uploader)s|%(upload_date)s",
-                    "--no-warnings",
-                    &format!("https://www.youtube.com/watch?v={}", video_id),
-                ])
-                .output()
-                .await;
-
-            if let Ok(output) = output {
-                if output.status.success() {
-                    let parsed = self.parse_ytdlp_output(&output.stdout).await;
-                    if let Ok(mut parsed_videos) = parsed {
-                        videos.append(&mut parsed_videos);
-                    }
-                }
-            }
+    // Helper: The configured history, or the one in the config directory
+    fn watch_history(&self) -> Result<Arc<WatchHistory>> {
+        match &self.history {
+            Some(history) => Ok(history.clone()),
+            None => Ok(Arc::new(WatchHistory::open_default()?)),
         }
-
-        Ok(videos)
     }
+}
 
-    // Add a video to watch history (insert at top, limit to 200)
-    pub async fn add_to_history(&self, video_id: &str) -> Result<()> {
-        use std::fs;
-        use std::io::Write;
+// Optional Data API key for browsing public data without signing in
+pub const API_KEY_ENV: &str = "YOUTUBE_API_KEY";
+
+// Builds the default source layout: account data comes from the Data API when an
+// access token or token provider is given, everything else from yt-dlp. With only
+// an API key, public channel and playlist data comes from the Data API.
+#[derive(Default)]
+pub struct YouTubeClientBuilder {
+    http_client: Option<Client>,
+    tokens: Option<Arc<dyn TokenProvider>>,
+    api_key: Option<String>,
+    api_base_url: Option<String>,
+    feed_base_url: Option<String>,
+    cache: Option<Arc<MetadataCache>>,
+}
 
-        // Get history file path
-        let history_file = get_history_file_path()?;
+impl YouTubeClientBuilder {
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
 
-        // Ensure config directory exists
-        if let Some(parent) = history_file.parent() {
-            fs::create_dir_all(parent)?;
-        }
+    // A fixed access token that is never refreshed
+    pub fn access_token(self, access_token: impl Into<String>) -> Self {
+        self.token_provider(Arc::new(StaticToken(access_token.into())))
+    }
 
-        // Read existing history
-        let mut history_lines: Vec<String> = if history_file.exists() {
-            fs::read_to_string(&history_file)?
-                .lines()
-                .map(|line| line.trim().to_string())
-                .filter(|line| !line.is_empty() && line != video_id) // Remove duplicates
-                .collect()
-        } else {
-            Vec::new()
-        };
+    pub fn token_provider(mut self, tokens: Arc<dyn TokenProvider>) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
 
-        // Insert new video ID at the top
-        history_lines.insert(0, video_id.to_string());
+    // Without an account, read public channel and playlist data from the Data API
+    // with this key instead of only yt-dlp
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
 
-        // Limit to 200 lines
-        if history_lines.len() > 200 {
-            history_lines.truncate(200);
+    // Use the key from YOUTUBE_API_KEY, if set
+    pub fn api_key_from_env(self) -> Self {
+        match std::env::var(API_KEY_ENV) {
+            Ok(api_key) if !api_key.trim().is_empty() => self.api_key(api_key.trim()),
+            _ => self,
         }
+    }
 
-        // Write back to file
-        let mut file = fs::File::create(&history_file)?;
-        for line in history_lines {
-            writeln!(file, "{}", line)?;
-        }
+    // Defaults to https://www.googleapis.com/youtube/v3
+    pub fn api_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.api_base_url = Some(base_url.into());
+        self
+    }
 
-        Ok(())
+    // Defaults to https://www.youtube.com/feeds/videos.xml
+    pub fn feed_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.feed_base_url = Some(base_url.into());
+        self
     }
 
-    // Search YouTube videos
-    pub async fn search_videos(&self, query: &str) -> Result<Vec<Video>> {
-        use crate::deps;
+    // Keep results on disk (see `cache::MetadataCache`)
+    pub fn cache(mut self, cache: MetadataCache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
 
-        // Check if yt-dlp is available
-        if !deps::check_ytdlp().await {
-            if let Err(e) = deps::ensure_ytdlp().await {
-                return Err(anyhow::anyhow!("yt-dlp is not installed: {}", e));
-            }
+    pub fn build(self) -> YouTubeClient {
+        let ytdlp: Arc<dyn VideoSource> = Arc::new(YtDlpSource::new());
+        let mut client = YouTubeClient::new(ytdlp.clone());
+        if let Some(cache) = self.cache {
+            client = client.with_cache(cache);
         }
 
-        #[cfg(windows)]
-        let ytdlp_cmd = if let Some(local_ytdlp) = deps::get_ytdlp_path().await {
-            local_ytdlp.to_str().unwrap().to_string()
-        } else {
-            "yt-dlp.exe".to_string()
+        let http_client = self.http_client.unwrap_or_default();
+        let base_url = self.api_base_url;
+        let with_base_url = |api: DataApiSource| match &base_url {
+            Some(base_url) => api.with_base_url(base_url),
+            None => api,
         };
-        #[cfg(not(windows))]
-        let ytdlp_cmd = "yt-dlp";
-
-        // Use yt-dlp to search videos
-        let search_url = format!("ytsearch30:{}", query);
-        let output = TokioCommand::new(ytdlp_cmd)
-            .args(&[
-                "--flat-playlist",
-                "--print",
-                "%(id)s|%(title)s|%(uploader)s|%(upload_date)s",
-                &search_url,
-            ])
-            .output()
-            .await?;
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("Failed to search videos: {}", error));
+        let Some(tokens) = self.tokens else {
+            let Some(api_key) = self.api_key else {
+                return client;
+            };
+            let api: Arc<dyn VideoSource> =
+                Arc::new(with_base_url(DataApiSource::public(http_client, api_key)));
+            return client
+                .with_source(Feature::Channels, api.clone())
+                .with_fallback(Feature::Channels, ytdlp.clone())
+                .with_source(Feature::Playlists, api)
+                .with_fallback(Feature::Playlists, ytdlp);
+        };
+
+        let api: Arc<dyn VideoSource> = Arc::new(with_base_url(DataApiSource::new(
+            http_client.clone(),
+            tokens,
+        )));
+        let mut feed = FeedSource::new(http_client);
+        if let Some(base_url) = self.feed_base_url {
+            feed = feed.with_base_url(base_url);
         }
 
-        self.parse_ytdlp_output(&output.stdout).await
+        client
+            .with_source(Feature::Subscriptions, api.clone())
+            .with_source(Feature::Feed, Arc::new(feed))
+            .with_source(Feature::Playlists, api.clone())
+            .with_source(Feature::Channels, api.clone())
+            .with_fallback(Feature::Channels, ytdlp.clone())
+            .with_source(Feature::Recommendations, api.clone())
+            .with_fallback(Feature::Recommendations, ytdlp)
+            .with_source(Feature::Actions, api)
     }
 }
 
-// API Response structures
-#[derive(Deserialize)]
-struct SubscriptionResponse {
-    items: Vec<SubscriptionItem>,
-    next_page_token: Option<String>,
+// Shorts are only marked by their title when the source doesn't tell us otherwise
+// (a #short or #shorts hashtag, but not e.g. #shortstory)
+pub fn title_looks_like_short(title: &str) -> bool {
+    title.to_lowercase().split('#').skip(1).any(|tag| {
+        let tag = tag
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap_or_default();
+        tag == "short" || tag == "shorts"
+    })
 }
 
-#[derive(Deserialize)]
-struct SubscriptionItem {
-    snippet: SubscriptionSnippet,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSource;
 
-#[derive(Deserialize)]
-struct SubscriptionSnippet {
-    title: String,
-    #[serde(rename = "resourceId", default)]
-    resource_id: Option<ResourceId>,
-    #[serde(default)]
-    thumbnails: Thumbnails,
-}
+    // A source that implements nothing
+    struct EmptySource;
 
-#[derive(Deserialize)]
-struct ResourceId {
-    #[serde(rename = "channelId")]
-    channel_id: String,
-}
+    impl VideoSource for EmptySource {
+        fn name(&self) -> &'static str {
+            "empty"
+        }
+    }
 
-#[derive(Deserialize)]
-struct PlaylistResponse {
-    items: Vec<PlaylistItem>,
-    next_page_token: Option<String>,
-}
+    fn mock_with_videos(ids: &[&str]) -> MockSource {
+        MockSource {
+            videos: ids
+                .iter()
+                .map(|id| Video {
+                    id: id.to_string(),
+                    title: format!("Video {}", id),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
 
-#[derive(Deserialize)]
-struct PlaylistItem {
-    id: String,
-    snippet: PlaylistSnippet,
-    #[serde(default, rename = "contentDetails")]
-    content_details: Option<PlaylistContentDetails>,
-}
+    #[test]
+    fn shorts_are_marked_by_a_whole_hashtag() {
+        assert!(title_looks_like_short("Rust in 30 seconds #Shorts"));
+        assert!(title_looks_like_short("#short: borrow checker"));
+        assert!(!title_looks_like_short("My #shortstory collection"));
+        assert!(!title_looks_like_short("A short video"));
+    }
 
-#[derive(Deserialize)]
-struct PlaylistSnippet {
-    title: String,
-    description: String,
-}
+    #[tokio::test]
+    async fn routes_each_feature_to_its_source() {
+        let client = YouTubeClient::new(Arc::new(mock_with_videos(&["general"])))
+            .with_source(Feature::Search, Arc::new(mock_with_videos(&["search"])));
 
-#[derive(Deserialize)]
-struct PlaylistContentDetails {
-    #[serde(rename = "itemCount")]
-    item_count: u32,
-}
+        let search = client.search_videos("video").await.unwrap();
+        assert_eq!(search[0].id, "search");
 
-#[derive(Deserialize)]
-struct PlaylistItemsResponse {
-    items: Vec<PlaylistVideoItem>,
-    next_page_token: Option<String>,
-}
+        let recommended = client.get_recommendations().await.unwrap();
+        assert_eq!(recommended[0].id, "general");
+    }
 
-#[derive(Deserialize)]
-struct PlaylistVideoItem {
-    snippet: PlaylistVideoSnippet,
-    #[serde(default)]
-    content_details: Option<PlaylistVideoContentDetails>,
-}
+    #[tokio::test]
+    async fn falls_back_when_source_does_not_support_feature() {
+        let client = YouTubeClient::new(Arc::new(EmptySource))
+            .with_fallback(Feature::Search, Arc::new(mock_with_videos(&["found"])));
 
-#[derive(Deserialize)]
-struct PlaylistVideoSnippet {
-    title: String,
-    #[serde(default)]
-    channel_title: Option<String>,
-    #[serde(default, rename = "publishedAt")]
-    published_at: Option<String>,
-    #[serde(default)]
-    thumbnails: Thumbnails,
-    #[serde(default, rename = "resourceId")]
-    resource_id: Option<PlaylistResourceId>,
-}
+        let videos = client.search_videos("found").await.unwrap();
+        assert_eq!(videos.len(), 1);
 
-#[derive(Deserialize)]
-struct PlaylistResourceId {
-    #[serde(rename = "videoId")]
-    video_id: Option<String>,
-}
+        let err = client.get_playlists().await.unwrap_err();
+        assert!(err.is::<Unsupported>());
+    }
 
-#[derive(Deserialize)]
-struct PlaylistVideoContentDetails {
-    video_id: String,
-}
+    #[tokio::test]
+    async fn falls_back_when_source_returns_nothing() {
+        let client = YouTubeClient::new(Arc::new(MockSource::default())).with_fallback(
+            Feature::Recommendations,
+            Arc::new(mock_with_videos(&["a", "b"])),
+        );
 
-#[derive(Deserialize, Default)]
-struct Thumbnails {
-    #[serde(default)]
-    default: Thumbnail,
-}
+        let videos = client.get_recommendations().await.unwrap();
+        assert_eq!(videos.len(), 2);
 
-#[derive(Deserialize, Default)]
-struct Thumbnail {
-    #[serde(default)]
-    url: String,
-}
+        // Nobody has anything: an empty list, not an error
+        assert!(client.get_subscriptions().await.unwrap().is_empty());
+    }
 
-// Helper function to get history file path
-fn get_history_file_path() -> Result<std::path::PathBuf> {
-    let dir = dirs::config_dir()
-        .or_else(|| dirs::home_dir().map(|d| d.join(".config")))
-        .context("Failed to find config directory")?
-        .join("rustyoutube");
-    Ok(dir.join("history.txt"))
+    #[tokio::test]
+    async fn actions_go_to_first_source_that_supports_them() {
+        let mock = MockSource::default();
+        let client = YouTubeClient::new(Arc::new(EmptySource))
+            .with_fallback(Feature::Actions, Arc::new(mock.clone()));
+
+        client.subscribe("UC-1").await.unwrap();
+        client.rate_video("vid-1", Rating::Like).await.unwrap();
+        client.remove_from_playlist("PLI-1").await.unwrap();
+
+        assert_eq!(
+            *mock.actions.lock().unwrap(),
+            ["subscribe UC-1", "rate vid-1 like", "remove PLI-1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn authenticated_if_any_source_is() {
+        let anonymous = YouTubeClient::new(Arc::new(MockSource::default()));
+        assert!(!anonymous.is_authenticated());
+
+        let signed_in = anonymous.with_source(Feature::Playlists, Arc::new(MockSource::sample()));
+        assert!(signed_in.is_authenticated());
+    }
+
+    #[tokio::test]
+    async fn sample_mock_serves_channels_and_playlists() {
+        let client = YouTubeClient::new(Arc::new(MockSource::sample()));
+
+        let subscriptions = client.get_subscriptions().await.unwrap();
+        let channel_videos = client
+            .get_channel_videos_by_id(&subscriptions[0].channel_id)
+            .await
+            .unwrap();
+        assert!(channel_videos
+            .iter()
+            .all(|v| v.channel_title == subscriptions[0].channel_title));
+
+        let by_handle = client.get_channel_videos("@RustChannel").await.unwrap();
+        assert_eq!(by_handle.len(), channel_videos.len());
+
+        let playlists = client.get_playlists().await.unwrap();
+        let items = client.get_playlist_videos(&playlists[0].id).await.unwrap();
+        assert_eq!(items.len() as u32, playlists[0].item_count);
+    }
 }
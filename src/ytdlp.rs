@@ -0,0 +1,727 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use tokio::process::Command as TokioCommand;
+
+use crate::deps;
+use crate::youtube::{title_looks_like_short, Video, VideoSource, VideoThumbnail};
+
+// Video source backed by the yt-dlp executable (no authentication needed)
+#[derive(Clone, Default)]
+pub struct YtDlpSource;
+
+impl YtDlpSource {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl VideoSource for YtDlpSource {
+    fn name(&self) -> &'static str {
+        "yt-dlp"
+    }
+
+    async fn get_channel_videos(&self, channel_url: &str) -> Result<Vec<Video>> {
+        // Check if yt-dlp is available, try to install if not
+        if !deps::check_ytdlp().await {
+            println!("yt-dlp not found. Attempting to install...");
+            if let Err(e) = deps::ensure_ytdlp().await {
+                return Err(anyhow::anyhow!(
+                    "yt-dlp is not installed and auto-installation failed: {}\n\
+                    Please install it manually:\n\
+                    Windows: winget install yt-dlp.yt-dlp",
+                    e
+                ));
+            }
+            // Verify it's now available
+            if !deps::check_ytdlp().await {
+                return Err(anyhow::anyhow!(
+                    "yt-dlp installation completed but it's still not available.\n\
+                    Please restart the program or install manually:\n\
+                    Windows: winget install yt-dlp.yt-dlp"
+                ));
+            }
+        }
+
+        // Use yt-dlp to get channel videos - prefer local version if available
+        let ytdlp_cmd = deps::ytdlp_cmd().await;
+
+        // Normalize the URL - ensure it's a full YouTube URL
+        let normalized_url = if channel_url.starts_with("http") {
+            channel_url.to_string()
+        } else if channel_url.starts_with("@") {
+            format!("https://www.youtube.com/{}/videos", channel_url)
+        } else {
+            format!("https://www.youtube.com/{}", channel_url)
+        };
+
+        println!("Fetching videos from: {}", normalized_url);
+
+        // Use yt-dlp to get channel videos
+        let output = TokioCommand::new(&ytdlp_cmd)
+            .args([
+                "--flat-playlist",
+                "--dump-json",
+                "--playlist-end",
+                "20", // Get top 20 videos
+                &normalized_url,
+            ])
+            .output()
+            .await;
+
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    return Err(anyhow::anyhow!(
+                        "yt-dlp not found. Please install it:\n\
+                        Windows: winget install yt-dlp\n\
+                        Or the program will try to install it automatically on next run."
+                    ));
+                }
+                return Err(anyhow::anyhow!("Failed to run yt-dlp: {}", e));
+            }
+        };
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+
+            // Check if it's a "not found" error
+            if error.contains("not found") || error.contains("not recognized") || error.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "yt-dlp is not installed or not in PATH.\n\
+                    Please install it:\n\
+                    Windows: winget install yt-dlp\n\
+                    Or run the program again to auto-install."
+                ));
+            }
+
+            return Err(anyhow::anyhow!(
+                "Failed to get channel videos.\nError: {}\nOutput: {}",
+                error,
+                stdout
+            ));
+        }
+
+        parse_ytdlp_output(&output.stdout)
+    }
+
+    async fn get_channel_videos_by_id(&self, channel_id: &str) -> Result<Vec<Video>> {
+        self.get_channel_videos(&format!(
+            "https://www.youtube.com/channel/{}/videos",
+            channel_id
+        ))
+        .await
+    }
+
+    // Note: YouTube's personalized homepage requires authentication and JavaScript.
+    // We try multiple methods to get trending/popular videos.
+    async fn get_recommendations(&self) -> Result<Vec<Video>> {
+        ensure_ytdlp().await?;
+
+        let ytdlp_cmd = deps::ytdlp_cmd().await;
+
+        // Try multiple methods to get trending/popular videos
+        // YouTube feeds don't work well with yt-dlp, so we use alternative approaches
+        // Note: YouTube's homepage requires JavaScript, so we use trending/popular content instead
+        let methods: Vec<(&str, Vec<&str>)> = vec![
+            // Method 1: Use trending URL with web client (most reliable)
+            (
+                "https://www.youtube.com/feed/trending",
+                vec!["--extractor-args", "youtube:player_client=web"],
+            ),
+            // Method 2: Use trending URL without extra args
+            ("https://www.youtube.com/feed/trending", vec![]),
+            // Method 3: Use a popular channel's videos as fallback (MrBeast)
+            ("https://www.youtube.com/@MrBeast/videos", vec![]),
+            // Method 4: Try another popular channel (PewDiePie)
+            ("https://www.youtube.com/@PewDiePie/videos", vec![]),
+        ];
+
+        let mut last_error = None;
+
+        for (url, extra_args) in methods {
+            let mut args = vec![
+                "--flat-playlist",
+                "--dump-json",
+                "--playlist-end",
+                "50",
+                "--no-warnings",
+            ];
+            args.extend(extra_args);
+            args.push(url);
+
+            let result = TokioCommand::new(&ytdlp_cmd).args(&args).output().await;
+
+            match result {
+                Ok(cmd_output) if cmd_output.status.success() => {
+                    let stdout_str = String::from_utf8_lossy(&cmd_output.stdout);
+                    if !stdout_str.trim().is_empty() {
+                        if let Ok(videos) = parse_ytdlp_output(&cmd_output.stdout) {
+                            if !videos.is_empty() {
+                                return Ok(videos);
+                            }
+                        }
+                    }
+                }
+                Ok(cmd_output) => {
+                    let error = String::from_utf8_lossy(&cmd_output.stderr);
+                    if !error.contains("Unsupported URL") {
+                        last_error = Some(error.trim().to_string());
+                    }
+                }
+                Err(e) => {
+                    last_error = Some(format!("Error: {}", e));
+                }
+            }
+        }
+
+        // If all methods failed, return a helpful error
+        Err(anyhow::anyhow!(
+            "Could not fetch recommendations.\n\nYouTube's personalized homepage requires authentication and JavaScript rendering.\nTrending feeds may not be available in your region.\n\nSuggestions:\n- Use 'Search' to find videos\n- Use 'Subscriptions' if you're authenticated\n- Try authenticating to get personalized recommendations\n\nLast error: {}",
+            last_error.unwrap_or_else(|| "Unknown error".to_string())
+        ))
+    }
+
+    async fn get_video(&self, video_id: &str) -> Result<Video> {
+        ensure_ytdlp().await?;
+
+        let ytdlp_cmd = deps::ytdlp_cmd().await;
+
+        let output = TokioCommand::new(&ytdlp_cmd)
+            .args([
+                "--skip-download",
+                "--dump-json",
+                "--no-warnings",
+                &format!("https://www.youtube.com/watch?v={}", video_id),
+            ])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("Failed to get video info: {}", error));
+        }
+
+        parse_ytdlp_output(&output.stdout)?
+            .into_iter()
+            .next()
+            .context("yt-dlp returned no video info")
+    }
+
+    async fn search_videos(&self, query: &str) -> Result<Vec<Video>> {
+        ensure_ytdlp().await?;
+
+        let ytdlp_cmd = deps::ytdlp_cmd().await;
+
+        // Use yt-dlp to search videos
+        let search_url = format!("ytsearch30:{}", query);
+        let output = TokioCommand::new(&ytdlp_cmd)
+            .args(["--flat-playlist", "--dump-json", &search_url])
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("Failed to search videos: {}", error));
+        }
+
+        parse_ytdlp_output(&output.stdout)
+    }
+}
+
+// One stream of a video as yt-dlp lists it (`-F`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoFormat {
+    pub format_id: String,
+    pub ext: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    // None for audio-only streams
+    pub vcodec: Option<String>,
+    // None for video-only streams
+    pub acodec: Option<String>,
+    pub dynamic_range: Option<String>,
+    // Bytes, exact or estimated
+    pub filesize: Option<u64>,
+    // Total bitrate in kbit/s
+    pub bitrate: Option<f64>,
+    pub note: String,
+}
+
+impl VideoFormat {
+    pub fn has_video(&self) -> bool {
+        self.vcodec.is_some()
+    }
+
+    pub fn has_audio(&self) -> bool {
+        self.acodec.is_some()
+    }
+
+    // Format selector for playing or downloading this stream; video-only streams
+    // get the best audio added
+    pub fn selector(&self) -> String {
+        if self.has_video() && !self.has_audio() {
+            format!("{}+bestaudio", self.format_id)
+        } else {
+            self.format_id.clone()
+        }
+    }
+}
+
+// A subtitle track of a video as yt-dlp lists it (`--list-subs`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptionTrack {
+    // Language code, as yt-dlp's --sub-langs and mpv's --slang take it
+    pub language: String,
+    // Language name shown by YouTube, e.g. "English (Original)"
+    pub name: String,
+    // Generated by YouTube rather than uploaded with the video
+    pub automatic: bool,
+    // File formats offered ("vtt", "srv1", ...)
+    pub formats: Vec<String>,
+}
+
+// List the formats available for a video, best first
+pub async fn list_formats(video_id: &str) -> Result<Vec<VideoFormat>> {
+    let output = video_info(video_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list formats: {}", e))?;
+    parse_formats(&output)
+}
+
+// List the subtitle tracks of a video: uploaded ones, then automatic captions
+pub async fn list_captions(video_id: &str) -> Result<Vec<CaptionTrack>> {
+    let output = video_info(video_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to list captions: {}", e))?;
+    parse_captions(&output)
+}
+
+// Helper: yt-dlp `-J` output for a video
+async fn video_info(video_id: &str) -> Result<Vec<u8>> {
+    ensure_ytdlp().await?;
+
+    let ytdlp_cmd = deps::ytdlp_cmd().await;
+
+    let output = TokioCommand::new(&ytdlp_cmd)
+        .args([
+            "--dump-single-json",
+            "--no-warnings",
+            &format!("https://www.youtube.com/watch?v={}", video_id),
+        ])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("{}", error));
+    }
+
+    Ok(output.stdout)
+}
+
+// Parse the formats out of yt-dlp `-J` output. Storyboards are left out and the
+// rest is returned best first (yt-dlp lists them worst first).
+pub fn parse_formats(output: &[u8]) -> Result<Vec<VideoFormat>> {
+    let info: YtDlpFormats =
+        serde_json::from_slice(output).context("Failed to parse yt-dlp JSON output")?;
+    // yt-dlp writes "none" for a missing codec
+    let codec = |codec: Option<String>| codec.filter(|c| c != "none");
+
+    Ok(info
+        .formats
+        .into_iter()
+        .rev()
+        .filter_map(|f| {
+            let vcodec = codec(f.vcodec);
+            let acodec = codec(f.acodec);
+            if vcodec.is_none() && acodec.is_none() {
+                return None;
+            }
+            Some(VideoFormat {
+                format_id: f.format_id,
+                ext: f.ext,
+                width: f.width,
+                height: f.height,
+                fps: f.fps,
+                vcodec,
+                acodec,
+                dynamic_range: f.dynamic_range,
+                filesize: f.filesize.or(f.filesize_approx),
+                bitrate: f.tbr,
+                note: f.format_note.unwrap_or_default(),
+            })
+        })
+        .collect())
+}
+
+// Parse the subtitle tracks out of yt-dlp `-J` output. Uploaded subtitles come
+// first, then automatic captions with the spoken language ("-orig") ahead of
+// YouTube's machine translations. Live chat replays are left out.
+pub fn parse_captions(output: &[u8]) -> Result<Vec<CaptionTrack>> {
+    let info: YtDlpCaptions =
+        serde_json::from_slice(output).context("Failed to parse yt-dlp JSON output")?;
+
+    let tracks = |map: BTreeMap<String, Vec<YtDlpSubtitle>>, automatic: bool| {
+        map.into_iter()
+            .filter(|(language, _)| language != "live_chat")
+            .map(move |(language, files)| CaptionTrack {
+                name: files
+                    .iter()
+                    .find_map(|f| f.name.clone())
+                    .unwrap_or_else(|| language.clone()),
+                formats: files.into_iter().map(|f| f.ext).collect(),
+                language,
+                automatic,
+            })
+    };
+    let mut automatic: Vec<CaptionTrack> = tracks(info.automatic_captions, true).collect();
+    automatic.sort_by_key(|track| !track.language.ends_with("-orig"));
+
+    Ok(tracks(info.subtitles, false).chain(automatic).collect())
+}
+
+// Helper: Make sure yt-dlp is available, installing it if possible
+async fn ensure_ytdlp() -> Result<()> {
+    if !deps::check_ytdlp().await {
+        if let Err(e) = deps::ensure_ytdlp().await {
+            return Err(anyhow::anyhow!("yt-dlp is not installed: {}", e));
+        }
+    }
+    Ok(())
+}
+
+// Parse yt-dlp `--dump-json` output (one JSON object per line). A line may also be a
+// single `-J` playlist object, in which case its entries are flattened.
+fn parse_ytdlp_output(output: &[u8]) -> Result<Vec<Video>> {
+    let mut videos = Vec::new();
+    let output_str = String::from_utf8_lossy(output);
+
+    for line in output_str.lines() {
+        let line = line.trim();
+        // Skip anything that isn't a JSON object (blank lines, stray messages)
+        if !line.starts_with('{') {
+            continue;
+        }
+
+        let info: YtDlpInfo =
+            serde_json::from_str(line).context("Failed to parse yt-dlp JSON output")?;
+
+        if let Some(entries) = info.entries {
+            videos.extend(entries.into_iter().filter_map(ytdlp_info_to_video));
+        } else if let Some(video) = ytdlp_info_to_video(info) {
+            videos.push(video);
+        }
+    }
+
+    Ok(videos)
+}
+
+fn ytdlp_info_to_video(info: YtDlpInfo) -> Option<Video> {
+    // Channel tabs and nested playlists show up as entries too; only keep videos
+    if info.id.is_empty() || info.ie_key.as_deref().is_some_and(|key| key != "Youtube") {
+        return None;
+    }
+
+    // Prefer the upload date, fall back to the timestamps flat entries carry
+    let published_at = match info.upload_date.as_deref() {
+        Some(date) if date.len() >= 8 => {
+            format!("{}-{}-{}", &date[0..4], &date[4..6], &date[6..8])
+        }
+        _ => info
+            .timestamp
+            .or(info.release_timestamp)
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+    };
+
+    let thumbnails: Vec<VideoThumbnail> = info
+        .thumbnails
+        .into_iter()
+        .filter(|t| !t.url.is_empty())
+        .map(|t| VideoThumbnail {
+            url: t.url,
+            width: t.width,
+            height: t.height,
+        })
+        .collect();
+
+    // yt-dlp lists thumbnails from worst to best
+    let thumbnail_url = info
+        .thumbnail
+        .or_else(|| thumbnails.last().map(|t| t.url.clone()))
+        .unwrap_or_default();
+
+    let is_live = info.is_live.unwrap_or(false) || info.live_status.as_deref() == Some("is_live");
+    let is_short = info
+        .url
+        .as_deref()
+        .is_some_and(|url| url.contains("/shorts/"))
+        || title_looks_like_short(&info.title);
+
+    Some(Video {
+        id: info.id,
+        title: info.title,
+        channel_id: info.channel_id.unwrap_or_default(),
+        channel_title: info
+            .channel
+            .or(info.uploader)
+            .unwrap_or_else(|| "Unknown Channel".to_string()),
+        published_at,
+        thumbnail_url,
+        description: info.description.unwrap_or_default(),
+        duration: info.duration.map(|d| d.round() as u64),
+        view_count: info.view_count,
+        is_live,
+        is_short,
+        thumbnails,
+        playlist_item_id: None,
+    })
+}
+
+#[derive(Deserialize)]
+struct YtDlpFormats {
+    #[serde(default)]
+    formats: Vec<YtDlpFormat>,
+}
+
+#[derive(Deserialize)]
+struct YtDlpCaptions {
+    #[serde(default)]
+    subtitles: BTreeMap<String, Vec<YtDlpSubtitle>>,
+    #[serde(default)]
+    automatic_captions: BTreeMap<String, Vec<YtDlpSubtitle>>,
+}
+
+#[derive(Deserialize)]
+struct YtDlpSubtitle {
+    #[serde(default)]
+    ext: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct YtDlpFormat {
+    #[serde(default)]
+    format_id: String,
+    #[serde(default)]
+    format_note: Option<String>,
+    #[serde(default)]
+    ext: String,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+    #[serde(default)]
+    fps: Option<f64>,
+    #[serde(default)]
+    vcodec: Option<String>,
+    #[serde(default)]
+    acodec: Option<String>,
+    #[serde(default)]
+    dynamic_range: Option<String>,
+    #[serde(default)]
+    filesize: Option<u64>,
+    #[serde(default)]
+    filesize_approx: Option<u64>,
+    #[serde(default)]
+    tbr: Option<f64>,
+}
+
+// yt-dlp JSON structures (shared by full and --flat-playlist output)
+#[derive(Deserialize)]
+struct YtDlpInfo {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    ie_key: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    channel_id: Option<String>,
+    #[serde(default)]
+    uploader: Option<String>,
+    #[serde(default)]
+    upload_date: Option<String>,
+    #[serde(default)]
+    timestamp: Option<i64>,
+    #[serde(default)]
+    release_timestamp: Option<i64>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    view_count: Option<u64>,
+    #[serde(default)]
+    is_live: Option<bool>,
+    #[serde(default)]
+    live_status: Option<String>,
+    #[serde(default)]
+    thumbnail: Option<String>,
+    #[serde(default)]
+    thumbnails: Vec<YtDlpThumbnail>,
+    #[serde(default)]
+    entries: Option<Vec<YtDlpInfo>>,
+}
+
+#[derive(Deserialize)]
+struct YtDlpThumbnail {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEARCH_FLAT: &str = include_str!("../tests/fixtures/ytdlp/search_flat.jsonl");
+    const CHANNEL_FLAT: &str = include_str!("../tests/fixtures/ytdlp/channel_flat.jsonl");
+    const VIDEO_FULL: &str = include_str!("../tests/fixtures/ytdlp/video_full.json");
+    const PLAYLIST_SINGLE: &str = include_str!("../tests/fixtures/ytdlp/playlist_single.json");
+    const FORMATS: &str = include_str!("../tests/fixtures/ytdlp/formats.json");
+
+    #[test]
+    fn parses_flat_search_results() {
+        let videos = parse_ytdlp_output(SEARCH_FLAT.as_bytes()).unwrap();
+        assert_eq!(videos.len(), 3);
+
+        let course = &videos[0];
+        assert_eq!(course.id, "Yw6u6YkTgQ4");
+        assert_eq!(course.title, "Rust | Full Course for Beginners");
+        assert_eq!(course.channel_title, "freeCodeCamp.org");
+        assert_eq!(course.duration, Some(3723));
+        assert_eq!(course.view_count, Some(1523401));
+        assert!(!course.is_live);
+        assert!(!course.is_short);
+        assert_eq!(course.thumbnails.len(), 2);
+        assert_eq!(course.thumbnail_url, course.thumbnails[1].url);
+        assert_eq!(course.published_at, "");
+
+        let stream = &videos[1];
+        assert!(stream.is_live);
+        assert_eq!(stream.duration, None);
+
+        let short = &videos[2];
+        assert!(short.is_short);
+        assert_eq!(short.channel_title, "No Boilerplate");
+        assert_eq!(short.duration, Some(31));
+    }
+
+    #[test]
+    fn skips_channel_tab_entries() {
+        let videos = parse_ytdlp_output(CHANNEL_FLAT.as_bytes()).unwrap();
+        assert_eq!(videos.len(), 1);
+        assert_eq!(videos[0].id, "Q3AhzHq8ogs");
+        assert_eq!(
+            videos[0].description,
+            "Rust is the most loved programming language | here's why."
+        );
+        // Flat entries only carry a timestamp
+        assert_eq!(videos[0].published_at, "2024-06-14");
+    }
+
+    #[test]
+    fn parses_full_video_info() {
+        let videos = parse_ytdlp_output(VIDEO_FULL.as_bytes()).unwrap();
+        assert_eq!(videos.len(), 1);
+
+        let video = &videos[0];
+        assert_eq!(video.id, "dQw4w9WgXcQ");
+        assert_eq!(video.channel_title, "Rick Astley");
+        assert_eq!(video.published_at, "2009-10-25");
+        assert_eq!(video.duration, Some(213));
+        assert_eq!(video.view_count, Some(1690000000));
+        assert!(video.description.starts_with("The official video"));
+        assert_eq!(
+            video.thumbnail_url,
+            "https://i.ytimg.com/vi_webp/dQw4w9WgXcQ/maxresdefault.webp"
+        );
+        assert_eq!(video.thumbnails[1].width, Some(1920));
+        assert_eq!(video.thumbnails[1].height, Some(1080));
+    }
+
+    #[test]
+    fn flattens_single_json_playlist() {
+        let videos = parse_ytdlp_output(PLAYLIST_SINGLE.as_bytes()).unwrap();
+        let ids: Vec<&str> = videos.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["zF34dRivLOw", "1jXf2l1TbfU"]);
+        assert_eq!(videos[0].title, "Ownership || Borrowing");
+        assert_eq!(videos[1].duration, Some(1188));
+        assert_eq!(videos[1].view_count, None);
+        assert_eq!(videos[1].thumbnail_url, "");
+    }
+
+    #[test]
+    fn ignores_non_json_lines() {
+        let output = format!("\nWARNING: something odd\n{}\n", VIDEO_FULL.trim());
+        let videos = parse_ytdlp_output(output.as_bytes()).unwrap();
+        assert_eq!(videos.len(), 1);
+    }
+
+    #[test]
+    fn parses_formats_best_first() {
+        let formats = parse_formats(FORMATS.as_bytes()).unwrap();
+        let ids: Vec<&str> = formats.iter().map(|f| f.format_id.as_str()).collect();
+        // The storyboard is gone
+        assert_eq!(ids, ["399", "137", "18", "251", "140"]);
+
+        let av1 = &formats[0];
+        assert_eq!(av1.height, Some(1080));
+        assert_eq!(av1.vcodec.as_deref(), Some("av01.0.08M.08"));
+        assert_eq!(av1.acodec, None);
+        assert_eq!(av1.selector(), "399+bestaudio");
+
+        // Combined and audio-only streams are used as they are
+        assert_eq!(formats[2].filesize, Some(13367214));
+        assert_eq!(formats[2].selector(), "18");
+        assert!(!formats[3].has_video());
+        assert_eq!(formats[3].selector(), "251");
+    }
+
+    #[test]
+    fn parses_captions_uploaded_first() {
+        let tracks = parse_captions(FORMATS.as_bytes()).unwrap();
+        let languages: Vec<(&str, bool)> = tracks
+            .iter()
+            .map(|t| (t.language.as_str(), t.automatic))
+            .collect();
+        // No live chat; the spoken language leads the automatic captions
+        assert_eq!(
+            languages,
+            [
+                ("de-DE", false),
+                ("en", false),
+                ("en-orig", true),
+                ("de", true),
+                ("en", true)
+            ]
+        );
+        assert_eq!(tracks[2].name, "English (Original)");
+        assert_eq!(tracks[0].formats, ["json3", "srv1", "vtt"]);
+
+        // Videos without any
+        let tracks = parse_captions(br#"{"id": "abc"}"#).unwrap();
+        assert!(tracks.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        assert!(parse_ytdlp_output(b"{\"id\": \"abc\", \"title\": ").is_err());
+    }
+}
@@ -0,0 +1,242 @@
+mod common;
+
+use common::{FakeServer, Response};
+use rustyoutube::auth::{
+    AuthClient, AuthFlow, AuthOptions, TokenData, READONLY_SCOPE, WRITE_SCOPE,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const DEVICE_CODE: &str = r#"{
+  "device_code": "AH-1Ng3-device",
+  "user_code": "GQVQ-JKEC",
+  "verification_url": "https://www.google.com/device",
+  "expires_in": 1800,
+  "interval": 0
+}"#;
+
+fn auth_client(server: &FakeServer, dir: &tempfile::TempDir) -> AuthClient {
+    AuthClient::with_credentials(
+        "client-id".to_string(),
+        "client-secret".to_string(),
+        dir.path().join("token.json"),
+    )
+    .with_oauth_base_url(&server.url)
+    .with_api_base_url(format!("{}/youtube/v3", server.url))
+}
+
+// Answers the token endpoint with the given responses in order
+async fn device_flow_server(token_responses: Vec<(u16, &'static str)>) -> FakeServer {
+    let polls = AtomicUsize::new(0);
+    FakeServer::start(move |request| match request.path.as_str() {
+        "/device/code" => Response::ok(DEVICE_CODE),
+        "/token" => {
+            let n = polls.fetch_add(1, Ordering::SeqCst);
+            let (status, body) = token_responses[n.min(token_responses.len() - 1)];
+            Response::json(status, body)
+        }
+        _ => Response::json(404, "{}"),
+    })
+    .await
+}
+
+// The clock is paused so the longer intervals pass instantly
+#[tokio::test(start_paused = true)]
+async fn polls_through_pending_and_slow_down() {
+    let server = device_flow_server(vec![
+        (428, r#"{"error": "authorization_pending"}"#),
+        (403, r#"{"error": "slow_down"}"#),
+        (428, r#"{"error": "authorization_pending"}"#),
+        (
+            200,
+            r#"{"access_token": "ya29.new", "refresh_token": "1//refresh", "expires_in": 3599, "token_type": "Bearer"}"#,
+        ),
+    ])
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let started = tokio::time::Instant::now();
+    let token = auth_client(&server, &dir).authenticate().await.unwrap();
+    assert_eq!(token, "ya29.new");
+    // slow_down raised the interval from 0 to 5s for both later polls
+    assert_eq!(started.elapsed().as_secs(), 10);
+
+    let device_requests = server.requests_to("/device/code");
+    assert_eq!(device_requests.len(), 1);
+    assert_eq!(device_requests[0].form()["client_id"], "client-id");
+
+    let polls = server.requests_to("/token");
+    assert_eq!(polls.len(), 4);
+    for poll in &polls {
+        let form = poll.form();
+        assert_eq!(form["device_code"], "AH-1Ng3-device");
+        assert_eq!(
+            form["grant_type"],
+            "urn:ietf:params:oauth:grant-type:device_code"
+        );
+    }
+
+    let saved: TokenData =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("token.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved.access_token, "ya29.new");
+    assert_eq!(saved.refresh_token.as_deref(), Some("1//refresh"));
+    assert!(saved.expires_at.is_some());
+}
+
+#[tokio::test]
+async fn denied_authorization_is_an_error() {
+    let server = device_flow_server(vec![
+        (428, r#"{"error": "authorization_pending"}"#),
+        (403, r#"{"error": "access_denied"}"#),
+    ])
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let err = auth_client(&server, &dir).authenticate().await.unwrap_err();
+    assert!(err.to_string().contains("access_denied"));
+    assert!(!dir.path().join("token.json").exists());
+}
+
+#[tokio::test]
+async fn expired_token_is_refreshed() {
+    let server = FakeServer::start(|request| match request.path.as_str() {
+        "/token"
+            if request.form().get("grant_type").map(String::as_str) == Some("refresh_token") =>
+        {
+            Response::ok(r#"{"access_token": "ya29.refreshed", "expires_in": 3599}"#)
+        }
+        _ => Response::json(404, "{}"),
+    })
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+    let expired = TokenData {
+        access_token: "ya29.old".to_string(),
+        refresh_token: Some("1//refresh".to_string()),
+        expires_at: Some(1),
+        scope: None,
+    };
+    std::fs::write(
+        dir.path().join("token.json"),
+        serde_json::to_string(&expired).unwrap(),
+    )
+    .unwrap();
+
+    let token = auth_client(&server, &dir).get_access_token().await.unwrap();
+    assert_eq!(token, "ya29.refreshed");
+
+    let refresh = &server.requests_to("/token")[0];
+    assert_eq!(refresh.form()["refresh_token"], "1//refresh");
+
+    let saved: TokenData =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("token.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved.access_token, "ya29.refreshed");
+    assert_eq!(saved.refresh_token.as_deref(), Some("1//refresh"));
+}
+
+#[tokio::test]
+async fn write_access_requests_youtube_scope() {
+    let server = device_flow_server(vec![(
+        200,
+        r#"{"access_token": "ya29.write", "refresh_token": "1//refresh", "expires_in": 3599, "scope": "https://www.googleapis.com/auth/youtube https://www.googleapis.com/auth/youtube.readonly"}"#,
+    )])
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    auth_client(&server, &dir)
+        .with_write_access(true)
+        .authenticate()
+        .await
+        .unwrap();
+
+    let scopes = server.requests_to("/device/code")[0].form()["scope"].clone();
+    assert!(scopes.split(' ').any(|s| s == WRITE_SCOPE), "{}", scopes);
+    assert!(scopes.split(' ').any(|s| s == READONLY_SCOPE), "{}", scopes);
+
+    let saved: TokenData =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("token.json")).unwrap())
+            .unwrap();
+    assert!(saved.has_scope(WRITE_SCOPE));
+}
+
+#[tokio::test]
+async fn read_only_token_is_upgraded_when_write_access_is_wanted() {
+    let server = device_flow_server(vec![(
+        200,
+        r#"{"access_token": "ya29.write", "refresh_token": "1//new", "expires_in": 3599}"#,
+    )])
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+    // A token saved before scopes were recorded: read-only, and still valid
+    let read_only = TokenData {
+        access_token: "ya29.readonly".to_string(),
+        refresh_token: Some("1//refresh".to_string()),
+        expires_at: None,
+        scope: None,
+    };
+    std::fs::write(
+        dir.path().join("token.json"),
+        serde_json::to_string(&read_only).unwrap(),
+    )
+    .unwrap();
+
+    // Tokens without recorded scopes only count as read-only
+    assert!(read_only.has_scope(READONLY_SCOPE));
+    assert!(!read_only.has_scope(WRITE_SCOPE));
+
+    let token = auth_client(&server, &dir)
+        .with_write_access(true)
+        .get_access_token()
+        .await
+        .unwrap();
+    assert_eq!(token, "ya29.write");
+    assert_eq!(server.requests_to("/device/code").len(), 1);
+
+    // Google didn't list the scopes, so the requested ones are recorded
+    let saved: TokenData =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("token.json")).unwrap())
+            .unwrap();
+    assert!(saved.has_scope(WRITE_SCOPE));
+}
+
+#[tokio::test]
+async fn switched_account_keeps_write_access() {
+    let server = device_flow_server(vec![(
+        200,
+        r#"{"access_token": "ya29.write", "refresh_token": "1//new", "expires_in": 3599}"#,
+    )])
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+    let options = AuthOptions {
+        write_access: true,
+        flow: AuthFlow::Device,
+    };
+    // Another account that signed in read-only
+    let token_path = dir.path().join("accounts").join("work.json");
+    std::fs::create_dir_all(token_path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &token_path,
+        serde_json::to_string(&TokenData {
+            access_token: "ya29.readonly".to_string(),
+            refresh_token: Some("1//refresh".to_string()),
+            expires_at: None,
+            scope: None,
+        })
+        .unwrap(),
+    )
+    .unwrap();
+
+    let switched = options.apply(
+        AuthClient::with_credentials(
+            "client-id".to_string(),
+            "client-secret".to_string(),
+            token_path,
+        )
+        .with_oauth_base_url(&server.url)
+        .with_api_base_url(format!("{}/youtube/v3", server.url)),
+    );
+    assert_eq!(switched.get_access_token().await.unwrap(), "ya29.write");
+
+    let scopes = server.requests_to("/device/code")[0].form()["scope"].clone();
+    assert!(scopes.split(' ').any(|s| s == WRITE_SCOPE), "{}", scopes);
+}
@@ -0,0 +1,257 @@
+#![cfg(unix)]
+
+use rustyoutube::downloads::{DownloadManager, DownloadTarget, ItemState, JobState};
+use rustyoutube::player::DownloadOptions;
+use rustyoutube::youtube::Video;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::Duration;
+
+// Stands in for yt-dlp: a shell script with the given body
+fn fake_ytdlp(dir: &Path, name: &str, body: &str) -> String {
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn video(id: &str) -> Video {
+    Video {
+        id: id.to_string(),
+        title: format!("Video {}", id),
+        ..Default::default()
+    }
+}
+
+// Helper: Wait until the job reaches a state the check accepts
+async fn wait_for(manager: &DownloadManager, id: u64, check: impl Fn(&JobState) -> bool) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !check(&manager.job(id).unwrap().state) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("job {} is {:?}", id, manager.job(id).unwrap().state));
+}
+
+#[tokio::test]
+async fn finished_downloads_are_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let program = fake_ytdlp(
+        dir.path(),
+        "yt-dlp",
+        "echo '[rustyoutube-progress]512 1024 NA 256.0 2'\n\
+         echo '[rustyoutube-file]/videos/Video a.webm'",
+    );
+    let record = dir.path().join("downloads.json");
+    let manager = DownloadManager::open(record.clone(), 2).with_program(program);
+
+    let id = manager.enqueue(video("a"), DownloadOptions::default());
+    wait_for(&manager, id, |state| *state == JobState::Finished).await;
+
+    let job = manager.job(id).unwrap();
+    assert_eq!(job.progress.unwrap().percent(), Some(50.0));
+    assert_eq!(job.file.unwrap(), Path::new("/videos/Video a.webm"));
+
+    // Kept for the next run; the record is written in the background
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while DownloadManager::open(record.clone(), 2)
+            .completed()
+            .is_empty()
+        {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("download was not recorded");
+    let reopened = DownloadManager::open(record, 2);
+    assert_eq!(reopened.completed().len(), 1);
+    assert_eq!(reopened.completed()[0].video_id, "a");
+}
+
+#[tokio::test]
+async fn failed_downloads_keep_the_error_and_can_be_retried() {
+    let dir = tempfile::tempdir().unwrap();
+    let program = fake_ytdlp(
+        dir.path(),
+        "yt-dlp",
+        "echo 'ERROR: [youtube] a: Video unavailable' >&2\nexit 1",
+    );
+    let manager = DownloadManager::new(1).with_program(program);
+
+    let id = manager.enqueue(video("a"), DownloadOptions::default());
+    wait_for(&manager, id, |state| matches!(state, JobState::Failed(_))).await;
+    assert_eq!(
+        manager.job(id).unwrap().state,
+        JobState::Failed("[youtube] a: Video unavailable".to_string())
+    );
+
+    assert!(manager.retry(id));
+    wait_for(&manager, id, |state| matches!(state, JobState::Failed(_))).await;
+    // Only failed or cancelled jobs are retried
+    assert!(!manager.resume(id));
+}
+
+#[tokio::test]
+async fn concurrency_limits_running_jobs() {
+    let dir = tempfile::tempdir().unwrap();
+    let program = fake_ytdlp(dir.path(), "yt-dlp", "sleep 30");
+    let manager = DownloadManager::new(1).with_program(program);
+
+    let first = manager.enqueue(video("a"), DownloadOptions::default());
+    let second = manager.enqueue(video("b"), DownloadOptions::default());
+    assert_eq!(manager.job(first).unwrap().state, JobState::Running);
+    assert_eq!(manager.job(second).unwrap().state, JobState::Queued);
+
+    // Pausing frees the slot for the next job once the paused yt-dlp has exited
+    assert!(manager.pause(first));
+    assert_eq!(manager.job(first).unwrap().state, JobState::Paused);
+    let third = manager.enqueue(video("c"), DownloadOptions::default());
+    assert_eq!(manager.job(second).unwrap().state, JobState::Queued);
+    wait_for(&manager, second, |state| *state == JobState::Running).await;
+    assert!(manager.cancel(third));
+
+    // A job resumed right away waits for its stopped run to end
+    assert!(manager.cancel(second));
+    assert!(manager.resume(first));
+    assert_eq!(manager.job(first).unwrap().state, JobState::Queued);
+    wait_for(&manager, first, |state| *state == JobState::Running).await;
+    assert_eq!(manager.job(second).unwrap().state, JobState::Cancelled);
+
+    assert!(manager.cancel(first));
+    manager.clear_finished();
+    assert!(manager.jobs().is_empty());
+}
+
+#[tokio::test]
+async fn videos_already_on_disk_are_skipped_until_retried() {
+    let dir = tempfile::tempdir().unwrap();
+    let program = fake_ytdlp(
+        dir.path(),
+        "yt-dlp",
+        "echo 'ERROR: should not run' >&2\nexit 1",
+    );
+    let videos = dir.path().join("videos");
+    fs::create_dir_all(videos.join("Channel")).unwrap();
+    let existing = videos.join("Channel").join("Video [abc123].mkv");
+    fs::write(&existing, "").unwrap();
+    let manager = DownloadManager::new(1).with_program(program);
+
+    let options = DownloadOptions {
+        directory: Some(videos.clone()),
+        ..Default::default()
+    };
+    let id = manager.enqueue(video("abc123"), options.clone());
+    wait_for(&manager, id, |state| *state == JobState::Skipped).await;
+    assert_eq!(manager.job(id).unwrap().file.unwrap(), existing);
+
+    // Another video downloads as usual
+    let other = manager.enqueue(video("xyz789"), options);
+    wait_for(&manager, other, |state| {
+        matches!(state, JobState::Failed(_))
+    })
+    .await;
+
+    // Retrying a skipped job downloads it anyway
+    assert!(manager.retry(id));
+    wait_for(&manager, id, |state| matches!(state, JobState::Failed(_))).await;
+    assert!(manager.job(id).unwrap().options.force);
+}
+
+#[tokio::test]
+async fn playlist_downloads_report_each_video_and_use_the_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let args_file = dir.path().join("args.txt");
+    let program = fake_ytdlp(
+        dir.path(),
+        "yt-dlp",
+        &format!(
+            "echo \"$@\" > '{}'\n\
+             # --print makes real yt-dlp quiet; only --no-quiet brings this line back\n\
+             case \" $* \" in *' --no-quiet '*) \
+             echo '[download] old: \"Already here\" has already been recorded in the archive';; \
+             esac\n\
+             echo '[rustyoutube-item]new1 4 First new video'\n\
+             echo '[rustyoutube-file]/videos/First new video.webm'\n\
+             echo '[rustyoutube-item]gone 4 Removed video'\n\
+             echo 'ERROR: [youtube] gone: Video unavailable' >&2\n\
+             echo '[rustyoutube-item]new2 4 Second new video'\n\
+             echo '[rustyoutube-file]/videos/Second new video.webm'\n\
+             exit 1",
+            args_file.display()
+        ),
+    );
+    let archive = dir.path().join("archive.txt");
+    let manager = DownloadManager::new(1)
+        .with_program(program)
+        .with_archive(archive.clone());
+
+    let target = DownloadTarget::Playlist {
+        id: "PL1".to_string(),
+        title: "Talks".to_string(),
+    };
+    let id = manager.enqueue_target(target, DownloadOptions::default());
+    wait_for(&manager, id, |state| matches!(state, JobState::Failed(_))).await;
+
+    let job = manager.job(id).unwrap();
+    assert_eq!(job.item_count, Some(4));
+    let states: Vec<(&str, &ItemState)> = job
+        .items
+        .iter()
+        .map(|item| (item.video_id.as_str(), &item.state))
+        .collect();
+    assert_eq!(
+        states,
+        [
+            ("old", &ItemState::Archived),
+            ("new1", &ItemState::Finished),
+            ("gone", &ItemState::Failed("Video unavailable".to_string())),
+            ("new2", &ItemState::Finished),
+        ]
+    );
+    assert_eq!(job.items[0].title, "Already here");
+
+    // Each finished video is recorded on its own
+    let completed: Vec<String> = manager
+        .completed()
+        .into_iter()
+        .map(|entry| entry.video_id)
+        .collect();
+    assert_eq!(completed, ["new2", "new1"]);
+
+    let args = fs::read_to_string(&args_file).unwrap();
+    assert!(args.contains(&format!("--download-archive {}", archive.display())));
+    assert!(args
+        .trim_end()
+        .ends_with("https://www.youtube.com/playlist?list=PL1"));
+}
+
+#[tokio::test]
+async fn single_downloads_stay_out_of_the_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let args_file = dir.path().join("args.txt");
+    let program = fake_ytdlp(
+        dir.path(),
+        "yt-dlp",
+        &format!(
+            "echo \"$@\" > '{}'\n\
+             echo '[rustyoutube-file]/videos/Video a.webm'",
+            args_file.display()
+        ),
+    );
+    let archive = dir.path().join("archive.txt");
+    fs::write(&archive, "youtube old\n").unwrap();
+    let manager = DownloadManager::new(1)
+        .with_program(program)
+        .with_archive(archive.clone());
+
+    let id = manager.enqueue(video("a"), DownloadOptions::default());
+    wait_for(&manager, id, |state| *state == JobState::Finished).await;
+
+    // A later playlist or channel download still fetches it
+    assert_eq!(fs::read_to_string(&archive).unwrap(), "youtube old\n");
+    assert!(!fs::read_to_string(&args_file)
+        .unwrap()
+        .contains("--download-archive"));
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use common::{FakeServer, Response};
+use rustyoutube::cache::MetadataCache;
+use rustyoutube::feed::FeedSource;
+use rustyoutube::youtube::{Subscription, VideoSource, YouTubeClient};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const SUBSCRIPTIONS_PAGE1: &str = include_str!("fixtures/api/subscriptions_page1.json");
+const SUBSCRIPTIONS_PAGE2: &str = include_str!("fixtures/api/subscriptions_page2.json");
+const RUST_FEED: &str = include_str!("fixtures/feeds/UC-rust.xml");
+const STREAM_FEED: &str = include_str!("fixtures/feeds/UC-stream.xml");
+const MUSIC_FEED: &str = include_str!("fixtures/feeds/UC-music.xml");
+
+// Serves the subscriptions (UC-rust, UC-stream, UC-music) and their feeds;
+// feeds for `broken_channel` fail
+async fn feed_server(broken_channel: Option<&'static str>) -> FakeServer {
+    FakeServer::start(move |request| match request.path.as_str() {
+        "/youtube/v3/subscriptions" => match request.query("pageToken") {
+            None => Response::ok(SUBSCRIPTIONS_PAGE1),
+            Some(_) => Response::ok(SUBSCRIPTIONS_PAGE2),
+        },
+        "/feeds/videos.xml" => {
+            let channel = request.query("channel_id").unwrap_or_default();
+            if Some(channel) == broken_channel {
+                return Response::json(404, "Not Found");
+            }
+            match channel {
+                "UC-rust" => Response::ok(RUST_FEED),
+                "UC-stream" => Response::ok(STREAM_FEED),
+                "UC-music" => Response::ok(MUSIC_FEED),
+                _ => Response::json(404, "Not Found"),
+            }
+        }
+        _ => Response::json(404, "{}"),
+    })
+    .await
+}
+
+fn client_for(server: &FakeServer) -> YouTubeClient {
+    YouTubeClient::builder()
+        .access_token("test-token")
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .feed_base_url(format!("{}/feeds/videos.xml", server.url))
+        .build()
+}
+
+#[tokio::test]
+async fn feed_merges_all_subscriptions_newest_first() {
+    let server = feed_server(None).await;
+
+    let videos = client_for(&server)
+        .get_subscription_feed(false)
+        .await
+        .unwrap();
+
+    // stream-short was published at 18:00 UTC, before music-1
+    let ids: Vec<&str> = videos.iter().map(|v| v.id.as_str()).collect();
+    assert_eq!(ids, ["rust-new", "music-1", "stream-short", "rust-old"]);
+
+    let newest = &videos[0];
+    assert_eq!(newest.title, "Async Rust & You");
+    assert_eq!(newest.channel_id, "UC-rust");
+    assert_eq!(newest.channel_title, "Rust Channel");
+    assert_eq!(newest.view_count, Some(1204));
+    assert_eq!(
+        newest.thumbnail_url,
+        "https://i1.ytimg.com/vi/rust-new/hqdefault.jpg"
+    );
+    assert!(videos[2].is_short);
+
+    // Every subscription, not only the first few
+    assert_eq!(server.requests_to("/feeds/videos.xml").len(), 3);
+}
+
+#[tokio::test]
+async fn feed_is_cached_until_refreshed() {
+    let server = feed_server(None).await;
+    let dir = tempfile::tempdir().unwrap();
+    let client = YouTubeClient::builder()
+        .access_token("test-token")
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .feed_base_url(format!("{}/feeds/videos.xml", server.url))
+        .cache(MetadataCache::open(dir.path().join("cache.json")))
+        .build();
+
+    client.get_subscription_feed(false).await.unwrap();
+    client.get_subscription_feed(false).await.unwrap();
+    assert_eq!(server.requests_to("/feeds/videos.xml").len(), 3);
+
+    client.get_subscription_feed(true).await.unwrap();
+    assert_eq!(server.requests_to("/feeds/videos.xml").len(), 6);
+}
+
+#[tokio::test]
+async fn feed_is_fetched_again_without_a_cache() {
+    let server = feed_server(None).await;
+    let client = client_for(&server);
+
+    client.get_subscription_feed(false).await.unwrap();
+    client.get_subscription_feed(false).await.unwrap();
+    assert_eq!(server.requests_to("/feeds/videos.xml").len(), 6);
+}
+
+#[tokio::test]
+async fn refreshing_the_feed_uses_cached_subscriptions() {
+    let server = feed_server(None).await;
+    let dir = tempfile::tempdir().unwrap();
+    let client = YouTubeClient::builder()
+        .access_token("test-token")
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .feed_base_url(format!("{}/feeds/videos.xml", server.url))
+        .cache(MetadataCache::open(dir.path().join("cache.json")))
+        .build();
+
+    client.get_subscription_feed(false).await.unwrap();
+    client.get_subscription_feed(true).await.unwrap();
+    client.get_subscriptions().await.unwrap();
+    // Both pages, listed once
+    assert_eq!(server.requests_to("/youtube/v3/subscriptions").len(), 2);
+    assert_eq!(server.requests_to("/feeds/videos.xml").len(), 6);
+}
+
+#[tokio::test]
+async fn broken_channel_feed_is_skipped() {
+    let server = feed_server(Some("UC-music")).await;
+
+    let videos = client_for(&server)
+        .get_subscription_feed(false)
+        .await
+        .unwrap();
+    assert_eq!(videos.len(), 3);
+    assert!(videos.iter().all(|v| v.channel_id != "UC-music"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn channel_feeds_are_fetched_with_bounded_concurrency() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let server = {
+        let in_flight = in_flight.clone();
+        let max_in_flight = max_in_flight.clone();
+        FakeServer::start(move |_| {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            Response::ok(MUSIC_FEED)
+        })
+        .await
+    };
+
+    let subscriptions: Vec<Subscription> = (0..10)
+        .map(|n| Subscription {
+            channel_id: format!("UC-{}", n),
+            channel_title: format!("Channel {}", n),
+            thumbnail_url: String::new(),
+        })
+        .collect();
+    let feed = FeedSource::new(reqwest::Client::new())
+        .with_base_url(format!("{}/feeds/videos.xml", server.url))
+        .with_concurrency(3);
+
+    let videos = feed
+        .get_subscription_feed(&subscriptions, false)
+        .await
+        .unwrap();
+    // Every channel serves the same video
+    assert_eq!(videos.len(), 1);
+    assert_eq!(server.requests().len(), 10);
+    let max = max_in_flight.load(Ordering::SeqCst);
+    assert!((2..=3).contains(&max), "{} requests at once", max);
+}
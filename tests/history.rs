@@ -0,0 +1,64 @@
+use rustyoutube::history::WatchHistory;
+use rustyoutube::mock::MockSource;
+use rustyoutube::youtube::{Video, YouTubeClient};
+use std::fs;
+use std::sync::Arc;
+
+fn video(id: &str, title: &str) -> Video {
+    Video {
+        id: id.to_string(),
+        title: title.to_string(),
+        channel_id: "UC-mock".to_string(),
+        channel_title: "Mock Channel".to_string(),
+        duration: Some(300),
+        ..Default::default()
+    }
+}
+
+fn client_with_history(dir: &tempfile::TempDir) -> YouTubeClient {
+    let source = MockSource {
+        videos: vec![video("known-1", "First"), video("known-2", "Second")],
+        ..Default::default()
+    };
+    YouTubeClient::new(Arc::new(source)).with_history(Arc::new(WatchHistory::new(
+        dir.path().join("history.json"),
+        dir.path().join("history.txt"),
+    )))
+}
+
+#[tokio::test]
+async fn migrated_history_is_filled_in_from_the_sources() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("history.txt"), "known-1\ngone\nknown-2\n").unwrap();
+    let client = client_with_history(&dir);
+
+    let entries = client.get_watch_history().await.unwrap();
+    let titles: Vec<&str> = entries.iter().map(|e| e.title.as_str()).collect();
+    // Videos that can't be found any more stay, so they can still be removed
+    assert_eq!(titles, ["First", "", "Second"]);
+    assert_eq!(entries[0].channel_title, "Mock Channel");
+    assert_eq!(entries[0].duration, Some(300));
+
+    // The details were stored, so the next read needs no lookups
+    let stored = fs::read_to_string(dir.path().join("history.json")).unwrap();
+    assert!(stored.contains("\"Second\""));
+}
+
+#[tokio::test]
+async fn played_videos_are_recorded_newest_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let client = client_with_history(&dir);
+
+    client.add_to_history(&video("a", "A")).await.unwrap();
+    client.add_to_history(&video("b", "B")).await.unwrap();
+    client.add_to_history(&video("a", "A")).await.unwrap();
+    client.remove_from_history("b").unwrap();
+
+    let entries = client.get_watch_history().await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].video_id, "a");
+    assert!(entries[0].watched_at > 0);
+
+    client.clear_history().unwrap();
+    assert!(client.get_watch_history().await.unwrap().is_empty());
+}
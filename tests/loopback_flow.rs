@@ -0,0 +1,132 @@
+mod common;
+
+use common::{FakeServer, Request, Response};
+use rustyoutube::auth::{AuthClient, AuthFlow, TokenData, READONLY_SCOPE};
+use std::sync::Arc;
+
+const TOKEN: &str = r#"{
+  "access_token": "ya29.browser",
+  "refresh_token": "1//browser-refresh",
+  "expires_in": 3599,
+  "token_type": "Bearer",
+  "scope": "https://www.googleapis.com/auth/youtube.readonly"
+}"#;
+
+fn auth_client(server: &FakeServer, dir: &tempfile::TempDir) -> AuthClient {
+    AuthClient::with_credentials(
+        "client-id".to_string(),
+        "client-secret".to_string(),
+        dir.path().join("token.json"),
+    )
+    .with_oauth_base_url(&server.url)
+    .with_authorize_url(format!("{}/auth", server.url))
+    .with_flow(AuthFlow::Loopback)
+    // Stand-in for the user's browser: follows the authorization page's
+    // redirect back to the app's loopback listener
+    .with_browser(Arc::new(|url: &str| {
+        let url = url.to_string();
+        tokio::spawn(async move {
+            let _ = reqwest::get(url).await;
+        });
+    }))
+}
+
+// Fake authorization server; `callback` picks the query the browser is sent
+// back with, given the state the app sent
+async fn authorization_server(callback: fn(&str) -> String) -> FakeServer {
+    FakeServer::start(move |request: &Request| match request.path.as_str() {
+        "/auth" => Response::redirect(format!(
+            "{}?{}",
+            request.query("redirect_uri").unwrap(),
+            callback(request.query("state").unwrap())
+        )),
+        "/token" => Response::ok(TOKEN),
+        _ => Response::json(404, "{}"),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn signs_in_through_browser_with_pkce() {
+    let server = authorization_server(|state| format!("code=4%2Fauth-code&state={}", state)).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let token = auth_client(&server, &dir).authenticate().await.unwrap();
+    assert_eq!(token, "ya29.browser");
+
+    let authorize = &server.requests_to("/auth")[0];
+    assert_eq!(authorize.query("response_type"), Some("code"));
+    assert_eq!(authorize.query("client_id"), Some("client-id"));
+    assert_eq!(authorize.query("scope"), Some(READONLY_SCOPE));
+    assert_eq!(authorize.query("code_challenge_method"), Some("S256"));
+    assert!(authorize.query("code_challenge").is_some());
+    assert_eq!(authorize.query("access_type"), Some("offline"));
+    let redirect_uri = authorize.query("redirect_uri").unwrap();
+    assert!(
+        redirect_uri.starts_with("http://127.0.0.1:"),
+        "{}",
+        redirect_uri
+    );
+
+    let exchange = server.requests_to("/token")[0].form();
+    assert_eq!(exchange["grant_type"], "authorization_code");
+    assert_eq!(exchange["code"], "4/auth-code");
+    assert_eq!(exchange["redirect_uri"], redirect_uri);
+    assert_eq!(exchange["client_id"], "client-id");
+    assert_eq!(exchange["client_secret"], "client-secret");
+    assert!(exchange["code_verifier"].len() >= 43);
+
+    let saved: TokenData =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("token.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved.refresh_token.as_deref(), Some("1//browser-refresh"));
+    assert_eq!(saved.scope.as_deref(), Some(READONLY_SCOPE));
+    assert!(saved.expires_at.is_some());
+}
+
+#[tokio::test]
+async fn denied_consent_is_an_error() {
+    let server = authorization_server(|state| format!("error=access_denied&state={}", state)).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let err = auth_client(&server, &dir).authenticate().await.unwrap_err();
+    assert!(err.to_string().contains("access_denied"), "{}", err);
+    assert!(server.requests_to("/token").is_empty());
+    assert!(!dir.path().join("token.json").exists());
+}
+
+#[tokio::test]
+async fn redirect_with_wrong_state_is_rejected() {
+    let server = authorization_server(|state| format!("code=4%2Fauth-code&state={}", state)).await;
+    let dir = tempfile::tempdir().unwrap();
+    let (status_tx, mut status_rx) = tokio::sync::mpsc::unbounded_channel();
+
+    // Something else hits the loopback port with a forged redirect before the
+    // browser comes back with the real one
+    let client = auth_client(&server, &dir).with_browser(Arc::new(move |url: &str| {
+        let url = url.to_string();
+        let status_tx = status_tx.clone();
+        tokio::spawn(async move {
+            let no_redirects = reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap();
+            let response = no_redirects.get(url).send().await.unwrap();
+            let location = response.headers()["location"].to_str().unwrap().to_string();
+            let listener = location.split('?').next().unwrap();
+
+            let forged = reqwest::get(format!("{}?code=4%2Fforged&state=forged", listener))
+                .await
+                .unwrap();
+            let _ = status_tx.send(forged.status().as_u16());
+            let _ = reqwest::get(location).await;
+        });
+    }));
+
+    let token = client.authenticate().await.unwrap();
+    assert_eq!(token, "ya29.browser");
+    assert_eq!(status_rx.recv().await, Some(400));
+    let exchanges = server.requests_to("/token");
+    assert_eq!(exchanges.len(), 1);
+    assert_eq!(exchanges[0].form()["code"], "4/auth-code");
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{FakeServer, Response};
+use rustyoutube::cache::MetadataCache;
+use rustyoutube::youtube::YouTubeClient;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const SUBSCRIPTIONS_PAGE1: &str = include_str!("fixtures/api/subscriptions_page1.json");
+const SUBSCRIPTIONS_PAGE2: &str = include_str!("fixtures/api/subscriptions_page2.json");
+
+// Serves subscriptions until `offline` is set
+async fn api_server(offline: Arc<AtomicBool>) -> FakeServer {
+    FakeServer::start(move |request| {
+        if offline.load(Ordering::SeqCst) {
+            return Response::json(503, r#"{"error": {"code": 503, "message": "offline"}}"#);
+        }
+        if request.path != "/youtube/v3/subscriptions" {
+            return Response::json(404, "{}");
+        }
+        match (request.method.as_str(), request.query("pageToken")) {
+            ("POST", _) => Response::ok(r#"{"id": "sub-new"}"#),
+            (_, None) => Response::ok(SUBSCRIPTIONS_PAGE1),
+            (_, Some(_)) => Response::ok(SUBSCRIPTIONS_PAGE2),
+        }
+    })
+    .await
+}
+
+fn client_for(server: &FakeServer, cache_path: &std::path::Path) -> YouTubeClient {
+    YouTubeClient::builder()
+        .access_token("test-token")
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .cache(MetadataCache::open(cache_path.to_path_buf()))
+        .build()
+}
+
+#[tokio::test]
+async fn repeat_views_come_from_the_cache() {
+    let server = api_server(Arc::new(AtomicBool::new(false))).await;
+    let dir = tempfile::tempdir().unwrap();
+    let cache_path = dir.path().join("cache.json");
+    let client = client_for(&server, &cache_path);
+
+    let first = client.get_subscriptions().await.unwrap();
+    let second = client.get_subscriptions().await.unwrap();
+    assert_eq!(first.len(), 3);
+    assert_eq!(second.len(), 3);
+    assert_eq!(server.requests().len(), 2); // both pages, once
+
+    // A new client (e.g. the next run) reads the file
+    let next_run = client_for(&server, &cache_path);
+    assert_eq!(next_run.get_subscriptions().await.unwrap().len(), 3);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn fresh_client_skips_the_cache() {
+    let server = api_server(Arc::new(AtomicBool::new(false))).await;
+    let dir = tempfile::tempdir().unwrap();
+    let client = client_for(&server, &dir.path().join("cache.json"));
+
+    client.get_subscriptions().await.unwrap();
+    client.fresh().get_subscriptions().await.unwrap();
+    assert_eq!(server.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn cached_data_is_used_when_offline() {
+    let offline = Arc::new(AtomicBool::new(false));
+    let server = api_server(offline.clone()).await;
+    let dir = tempfile::tempdir().unwrap();
+    let client = client_for(&server, &dir.path().join("cache.json"));
+
+    client.get_subscriptions().await.unwrap();
+    offline.store(true, Ordering::SeqCst);
+
+    let subscriptions = client.fresh().get_subscriptions().await.unwrap();
+    assert_eq!(subscriptions[0].channel_id, "UC-rust");
+
+    // Nothing cached for playlists, so the error comes through
+    assert!(client.get_playlists().await.is_err());
+}
+
+#[tokio::test]
+async fn subscribing_forgets_cached_subscriptions() {
+    let server = api_server(Arc::new(AtomicBool::new(false))).await;
+    let dir = tempfile::tempdir().unwrap();
+    let client = client_for(&server, &dir.path().join("cache.json"));
+
+    client.get_subscriptions().await.unwrap();
+    client.subscribe("UC-new").await.unwrap();
+    client.get_subscriptions().await.unwrap();
+    assert_eq!(server.requests_to("/subscriptions").len(), 5);
+}
@@ -0,0 +1,287 @@
+#![cfg(unix)]
+
+use rustyoutube::player::{MpvController, MpvEvent};
+use rustyoutube::settings::SponsorBlockPrefs;
+use rustyoutube::sponsorblock::{self, Segment};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+use tokio::sync::mpsc;
+
+// Stands in for mpv: answers commands like mpv does and sends whatever is pushed
+// through `events` to the client
+struct FakeMpv {
+    socket: PathBuf,
+    commands: Arc<Mutex<Vec<Value>>>,
+    events: mpsc::UnboundedSender<Value>,
+    _dir: tempfile::TempDir,
+}
+
+impl FakeMpv {
+    fn start() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("mpv.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let (commands, events) = Self::serve(listener);
+        Self {
+            socket,
+            commands,
+            events,
+            _dir: dir,
+        }
+    }
+
+    fn serve(listener: UnixListener) -> (Arc<Mutex<Vec<Value>>>, mpsc::UnboundedSender<Value>) {
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel::<Value>();
+        let received = commands.clone();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            loop {
+                let message = tokio::select! {
+                    line = lines.next_line() => match line {
+                        Ok(Some(line)) => {
+                            let request: Value = serde_json::from_str(&line).unwrap();
+                            received.lock().unwrap().push(request["command"].clone());
+                            reply(&request)
+                        }
+                        _ => break,
+                    },
+                    Some(event) = events_rx.recv() => event,
+                };
+                let mut line = message.to_string();
+                line.push('\n');
+                if writer.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        (commands, events_tx)
+    }
+
+    fn commands(&self) -> Vec<Value> {
+        self.commands.lock().unwrap().clone()
+    }
+}
+
+// Helper: What mpv would answer for the properties the tests use
+fn reply(request: &Value) -> Value {
+    let request_id = request["request_id"].clone();
+    let command = &request["command"];
+    match (command[0].as_str(), command[1].as_str()) {
+        (Some("get_property"), Some("time-pos")) => {
+            json!({"request_id": request_id, "error": "success", "data": 12.5})
+        }
+        (Some("get_property"), Some("duration")) => {
+            json!({"request_id": request_id, "error": "property unavailable"})
+        }
+        (Some("get_property"), Some("pause")) => {
+            json!({"request_id": request_id, "error": "success", "data": true})
+        }
+        (Some("get_property"), Some("chapter-list")) => {
+            json!({"request_id": request_id, "error": "success", "data": [{"title": "Start", "time": 0.0}]})
+        }
+        (Some("get_property"), _) => {
+            json!({"request_id": request_id, "error": "property not found"})
+        }
+        _ => json!({"request_id": request_id, "error": "success", "data": null}),
+    }
+}
+
+async fn connect(socket: &Path) -> MpvController {
+    MpvController::connect(socket).await.unwrap()
+}
+
+#[tokio::test]
+async fn properties_are_read() {
+    let mpv = FakeMpv::start();
+    let controller = connect(&mpv.socket).await;
+
+    assert_eq!(controller.time_pos().await.unwrap(), Some(12.5));
+    // Nothing loaded yet
+    assert_eq!(controller.duration().await.unwrap(), None);
+    assert!(controller.is_paused().await.unwrap());
+
+    let error = controller.get_property("no-such-thing").await.unwrap_err();
+    assert!(error.to_string().contains("property not found"));
+}
+
+#[tokio::test]
+async fn controls_send_mpv_commands() {
+    let mpv = FakeMpv::start();
+    let controller = connect(&mpv.socket).await;
+
+    controller.toggle_pause().await.unwrap();
+    controller.seek(-10.0).await.unwrap();
+    controller.seek_to(90.0).await.unwrap();
+    controller.set_volume(70.0).await.unwrap();
+    controller.add_speed(0.25).await.unwrap();
+    controller.quit().await.unwrap();
+
+    assert_eq!(
+        mpv.commands(),
+        [
+            json!(["cycle", "pause"]),
+            json!(["seek", -10.0, "relative"]),
+            json!(["seek", 90.0, "absolute"]),
+            json!(["set_property", "volume", 70.0]),
+            json!(["add", "speed", 0.25]),
+            json!(["quit"]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn events_are_delivered() {
+    let mpv = FakeMpv::start();
+    let controller = connect(&mpv.socket).await;
+    let mut events = controller.events();
+
+    controller.observe("time-pos").await.unwrap();
+    assert_eq!(mpv.commands()[0][0], "observe_property");
+    assert_eq!(mpv.commands()[0][2], "time-pos");
+
+    mpv.events
+        .send(json!({"event": "property-change", "id": 1, "name": "time-pos", "data": 42.0}))
+        .unwrap();
+    mpv.events
+        .send(json!({"event": "end-file", "reason": "quit"}))
+        .unwrap();
+
+    assert_eq!(
+        next_event(&mut events).await,
+        MpvEvent::PropertyChange {
+            name: "time-pos".to_string(),
+            data: json!(42.0)
+        }
+    );
+    assert_eq!(
+        next_event(&mut events).await,
+        MpvEvent::EndFile {
+            reason: "quit".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn mpv_shutdown_is_reported() {
+    let mpv = FakeMpv::start();
+    let controller = connect(&mpv.socket).await;
+    let mut events = controller.events();
+
+    mpv.events.send(json!({"event": "shutdown"})).unwrap();
+    assert_eq!(next_event(&mut events).await, MpvEvent::Shutdown);
+}
+
+#[tokio::test]
+async fn sponsor_segments_are_skipped_and_marked() {
+    let mpv = FakeMpv::start();
+    let controller = connect(&mpv.socket).await;
+    let segment = |category: &str, start: f64, end: f64| Segment {
+        category: category.to_string(),
+        start,
+        end,
+        uuid: category.to_string(),
+    };
+    let segments = vec![
+        segment("sponsor", 30.0, 60.0),
+        segment("interaction", 100.0, 110.0),
+        segment("preview", 200.0, 220.0),
+    ];
+    let following = tokio::spawn(async move {
+        sponsorblock::follow_playback(&controller, &segments, &SponsorBlockPrefs::default(), None)
+            .await
+    });
+    wait_for(&mpv, |commands| commands.len() == 2).await;
+
+    // Loaded: the marked segment joins the video's chapters
+    mpv.events
+        .send(json!({"event": "property-change", "name": "duration", "data": 600.0}))
+        .unwrap();
+    wait_for(&mpv, |commands| {
+        commands.iter().any(|c| c[0] == "set_property")
+    })
+    .await;
+    let chapters = mpv
+        .commands()
+        .into_iter()
+        .find(|c| c[0] == "set_property")
+        .unwrap();
+    assert_eq!(chapters[1], "chapter-list");
+    assert_eq!(
+        chapters[2],
+        json!([
+            {"title": "Start", "time": 0.0},
+            {"title": "[SponsorBlock] Interaction reminder", "time": 100.0},
+            {"title": "Start", "time": 110.0}
+        ])
+    );
+
+    // Into the sponsor read: jump to its end, once
+    for position in [31.0, 45.0, 205.0] {
+        mpv.events
+            .send(json!({"event": "property-change", "name": "time-pos", "data": position}))
+            .unwrap();
+    }
+    wait_for(&mpv, |commands| {
+        commands.iter().any(|c| c[0] == "show-text")
+    })
+    .await;
+    mpv.events
+        .send(json!({"event": "end-file", "reason": "eof"}))
+        .unwrap();
+    tokio::time::timeout(Duration::from_secs(5), following)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    let seeks: Vec<Value> = mpv
+        .commands()
+        .into_iter()
+        .filter(|c| c[0] == "seek")
+        .collect();
+    assert_eq!(seeks, [json!(["seek", 60.0, "absolute"])]);
+}
+
+// Helper: Wait until the commands mpv received pass `check`
+async fn wait_for(mpv: &FakeMpv, check: impl Fn(&[Value]) -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !check(&mpv.commands()) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+async fn next_event(events: &mut tokio::sync::broadcast::Receiver<MpvEvent>) -> MpvEvent {
+    tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn waits_for_mpv_to_open_the_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("late.sock");
+
+    let server_socket = socket.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let listener = UnixListener::bind(&server_socket).unwrap();
+        FakeMpv::serve(listener);
+    });
+
+    assert!(MpvController::connect(&socket).await.is_err());
+    let controller = MpvController::connect_when_ready(&socket, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(controller.time_pos().await.unwrap(), Some(12.5));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{FakeServer, Response};
+use rustyoutube::auth::{is_corrupt_token, AuthClient, RevokeOutcome, TokenData};
+use rustyoutube::token_store::TokenStore;
+
+fn auth_client(server: &FakeServer, dir: &tempfile::TempDir) -> AuthClient {
+    AuthClient::with_credentials(
+        "client-id".to_string(),
+        "client-secret".to_string(),
+        dir.path().join("token.json"),
+    )
+    .with_oauth_base_url(&server.url)
+}
+
+fn save_token(dir: &tempfile::TempDir, refresh_token: Option<&str>) {
+    let token = TokenData {
+        access_token: "ya29.access".to_string(),
+        refresh_token: refresh_token.map(str::to_string),
+        expires_at: None,
+        scope: None,
+    };
+    std::fs::write(
+        dir.path().join("token.json"),
+        serde_json::to_string(&token).unwrap(),
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn revokes_refresh_token_and_deletes_it() {
+    let server = FakeServer::start(|_| Response::ok("{}")).await;
+    let dir = tempfile::tempdir().unwrap();
+    save_token(&dir, Some("1//refresh"));
+
+    let outcome = auth_client(&server, &dir).revoke().await.unwrap();
+    assert_eq!(outcome, RevokeOutcome::Revoked);
+    assert!(!dir.path().join("token.json").exists());
+
+    let requests = server.requests_to("/revoke");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].form()["token"], "1//refresh");
+}
+
+#[tokio::test]
+async fn revokes_access_token_without_refresh_token() {
+    let server = FakeServer::start(|_| Response::ok("{}")).await;
+    let dir = tempfile::tempdir().unwrap();
+    save_token(&dir, None);
+
+    auth_client(&server, &dir).revoke().await.unwrap();
+    assert_eq!(
+        server.requests_to("/revoke")[0].form()["token"],
+        "ya29.access"
+    );
+}
+
+#[tokio::test]
+async fn already_revoked_token_is_still_deleted() {
+    let server = FakeServer::start(|_| {
+        Response::json(
+            400,
+            r#"{"error": "invalid_token", "error_description": "Token expired or revoked"}"#,
+        )
+    })
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+    save_token(&dir, Some("1//refresh"));
+
+    let outcome = auth_client(&server, &dir).revoke().await.unwrap();
+    assert_eq!(outcome, RevokeOutcome::AlreadyRevoked);
+    assert!(!dir.path().join("token.json").exists());
+}
+
+#[tokio::test]
+async fn server_error_keeps_token() {
+    let server = FakeServer::start(|_| Response::json(503, r#"{"error": "backend_error"}"#)).await;
+    let dir = tempfile::tempdir().unwrap();
+    save_token(&dir, Some("1//refresh"));
+
+    let err = auth_client(&server, &dir).revoke().await.unwrap_err();
+    assert!(err.to_string().contains("503"), "{}", err);
+    assert!(dir.path().join("token.json").exists());
+}
+
+#[tokio::test]
+async fn not_signed_in_makes_no_request() {
+    let server = FakeServer::start(|_| Response::ok("{}")).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let outcome = auth_client(&server, &dir).revoke().await.unwrap();
+    assert_eq!(outcome, RevokeOutcome::NotSignedIn);
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn encrypted_token_without_passphrase_is_kept() {
+    let server = FakeServer::start(|_| Response::ok("{}")).await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token.json");
+    TokenStore::file(path.clone())
+        .with_passphrase("correct horse")
+        .save(&TokenData {
+            access_token: "ya29.access".to_string(),
+            refresh_token: Some("1//refresh".to_string()),
+            expires_at: None,
+            scope: None,
+        })
+        .await
+        .unwrap();
+
+    // No RUSTYOUTUBE_TOKEN_PASSPHRASE: the token can't be read, so it can't be revoked
+    let client = auth_client(&server, &dir);
+    let err = client.revoke().await.unwrap_err();
+    assert!(format!("{:#}", err).contains("encrypted"), "{:#}", err);
+    assert!(!is_corrupt_token(&err));
+    assert!(path.exists());
+    assert!(server.requests().is_empty());
+
+    // Nor is it dropped as corrupt
+    assert!(client.forget_corrupt_token().await.is_err());
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn corrupt_token_is_only_deleted_when_confirmed() {
+    let server = FakeServer::start(|_| Response::ok("{}")).await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token.json");
+    std::fs::write(&path, "not a token").unwrap();
+
+    let client = auth_client(&server, &dir);
+    let err = client.revoke().await.unwrap_err();
+    assert!(is_corrupt_token(&err), "{:#}", err);
+    assert!(path.exists());
+
+    let outcome = client.forget_corrupt_token().await.unwrap();
+    assert_eq!(outcome, RevokeOutcome::AlreadyRevoked);
+    assert!(!path.exists());
+    assert!(server.requests().is_empty());
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::{FakeServer, Response};
+use rustyoutube::sponsorblock::SponsorBlockClient;
+
+const SEGMENTS: &str = r#"[
+    {"category": "sponsor", "actionType": "skip", "segment": [12.5, 45.0], "UUID": "uuid-sponsor", "videoDuration": 600.0},
+    {"category": "intro", "actionType": "skip", "segment": [0.0, 8.25], "UUID": "uuid-intro", "videoDuration": 600.0}
+]"#;
+
+// Answers like the SponsorBlock API: segments for "abc", 404 for unknown videos
+async fn sponsorblock_server() -> FakeServer {
+    FakeServer::start(
+        |request| match (request.path.as_str(), request.query("videoID")) {
+            ("/api/skipSegments", Some("abc")) => Response::ok(SEGMENTS),
+            ("/api/skipSegments", Some("broken")) => Response::json(500, "Internal Server Error"),
+            _ => Response::json(404, "Not Found"),
+        },
+    )
+    .await
+}
+
+fn client(server: &FakeServer) -> SponsorBlockClient {
+    SponsorBlockClient::new(reqwest::Client::new()).with_base_url(format!("{}/", server.url))
+}
+
+#[tokio::test]
+async fn segments_are_fetched_for_the_configured_categories() {
+    let server = sponsorblock_server().await;
+    let categories = vec!["sponsor".to_string(), "intro".to_string()];
+
+    let segments = client(&server).segments("abc", &categories).await.unwrap();
+    let found: Vec<(&str, f64, f64)> = segments
+        .iter()
+        .map(|s| (s.category.as_str(), s.start, s.end))
+        .collect();
+    assert_eq!(found, [("intro", 0.0, 8.25), ("sponsor", 12.5, 45.0)]);
+
+    let requests = server.requests_to("/api/skipSegments");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].query("categories"),
+        Some(r#"["sponsor","intro"]"#)
+    );
+}
+
+#[tokio::test]
+async fn videos_without_segments_have_none() {
+    let server = sponsorblock_server().await;
+    let categories = vec!["sponsor".to_string()];
+
+    let segments = client(&server).segments("xyz", &categories).await.unwrap();
+    assert!(segments.is_empty());
+
+    let error = client(&server)
+        .segments("broken", &categories)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("500"));
+}
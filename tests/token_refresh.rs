@@ -0,0 +1,145 @@
+mod common;
+
+use common::{FakeServer, Request, Response};
+use rustyoutube::auth::{AuthClient, AuthTokenProvider, TokenData};
+use rustyoutube::youtube::YouTubeClient;
+use std::path::Path;
+use std::sync::Arc;
+
+const SUBSCRIPTIONS: &str = include_str!("fixtures/api/subscriptions_page2.json");
+const INVALID_CREDENTIALS: &str = include_str!("fixtures/api/invalid_credentials.json");
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn write_token(path: &Path, access_token: &str, expires_at: u64) {
+    let token = TokenData {
+        access_token: access_token.to_string(),
+        refresh_token: Some("1//refresh".to_string()),
+        expires_at: Some(expires_at),
+        scope: None,
+    };
+    std::fs::write(path, serde_json::to_string(&token).unwrap()).unwrap();
+}
+
+fn read_token(path: &Path) -> TokenData {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// Accepts only "ya29.fresh" on the API and hands it out from the token endpoint
+fn fake_google(request: &Request) -> Response {
+    match request.path.as_str() {
+        "/token" => Response::ok(r#"{"access_token": "ya29.fresh", "expires_in": 3599}"#),
+        _ if request.header("authorization") == Some("Bearer ya29.fresh") => {
+            Response::ok(SUBSCRIPTIONS)
+        }
+        _ => Response::json(401, INVALID_CREDENTIALS),
+    }
+}
+
+fn client_for(server: &FakeServer, token_path: &Path) -> YouTubeClient {
+    let auth = AuthClient::with_credentials(
+        "client-id".to_string(),
+        "client-secret".to_string(),
+        token_path.to_path_buf(),
+    )
+    .with_oauth_base_url(&server.url);
+
+    YouTubeClient::builder()
+        .token_provider(Arc::new(AuthTokenProvider::new(auth)))
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .build()
+}
+
+#[tokio::test]
+async fn rejected_token_is_refreshed_and_request_retried() {
+    let server = FakeServer::start(fake_google).await;
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join("token.json");
+    // Not expired on paper, but revoked server-side
+    write_token(&token_path, "ya29.revoked", now() + 3000);
+
+    let subscriptions = client_for(&server, &token_path)
+        .get_subscriptions()
+        .await
+        .unwrap();
+    assert_eq!(subscriptions.len(), 1);
+
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+        paths,
+        [
+            "/youtube/v3/subscriptions",
+            "/token",
+            "/youtube/v3/subscriptions"
+        ]
+    );
+    assert_eq!(read_token(&token_path).access_token, "ya29.fresh");
+}
+
+#[tokio::test]
+async fn expired_token_is_refreshed_before_the_request() {
+    let server = FakeServer::start(fake_google).await;
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join("token.json");
+    write_token(&token_path, "ya29.expired", now() - 10);
+
+    let client = client_for(&server, &token_path);
+    client.get_subscriptions().await.unwrap();
+    // The refreshed token is reused for later calls
+    client.get_subscriptions().await.unwrap();
+
+    assert_eq!(server.requests_to("/token").len(), 1);
+    assert!(server
+        .requests_to("/subscriptions")
+        .iter()
+        .all(|r| r.header("authorization") == Some("Bearer ya29.fresh")));
+
+    let saved = read_token(&token_path);
+    assert_eq!(saved.access_token, "ya29.fresh");
+    assert_eq!(saved.refresh_token.as_deref(), Some("1//refresh"));
+    assert!(saved.expires_at.unwrap() > now());
+}
+
+#[tokio::test]
+async fn failed_refresh_is_reported() {
+    let server = FakeServer::start(|request| match request.path.as_str() {
+        "/token" => Response::json(400, r#"{"error": "invalid_grant"}"#),
+        _ => Response::json(401, INVALID_CREDENTIALS),
+    })
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+    let token_path = dir.path().join("token.json");
+    write_token(&token_path, "ya29.revoked", now() + 3000);
+
+    let err = client_for(&server, &token_path)
+        .get_subscriptions()
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("invalid_grant"), "{:#}", err);
+}
+
+#[tokio::test]
+async fn static_token_is_not_retried() {
+    let server = FakeServer::start(fake_google).await;
+
+    let err = YouTubeClient::builder()
+        .access_token("ya29.revoked")
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .build()
+        .get_subscriptions()
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("401"), "{:#}", err);
+    assert!(
+        format!("{:#}", err).contains("cannot be refreshed"),
+        "{:#}",
+        err
+    );
+    assert_eq!(server.requests().len(), 1);
+}
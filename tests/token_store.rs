@@ -0,0 +1,126 @@
+use rustyoutube::auth::TokenData;
+use rustyoutube::token_store::TokenStore;
+
+fn token() -> TokenData {
+    TokenData {
+        access_token: "ya29.access".to_string(),
+        refresh_token: Some("1//refresh".to_string()),
+        expires_at: Some(1_700_000_000),
+        scope: None,
+    }
+}
+
+// The format older versions wrote: pretty JSON with default permissions
+fn write_legacy_token(path: &std::path::Path) {
+    std::fs::write(path, serde_json::to_string_pretty(&token()).unwrap()).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o644)).unwrap();
+    }
+}
+
+#[cfg(unix)]
+fn mode(path: &std::path::Path) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[tokio::test]
+async fn saves_private_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("accounts").join("work.json");
+    let store = TokenStore::file(path.clone());
+
+    store.save(&token()).await.unwrap();
+    #[cfg(unix)]
+    assert_eq!(mode(&path), 0o600);
+
+    let loaded = store.load().await.unwrap();
+    assert_eq!(loaded.access_token, "ya29.access");
+    assert_eq!(loaded.refresh_token.as_deref(), Some("1//refresh"));
+}
+
+#[tokio::test]
+async fn legacy_token_file_is_made_private() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token.json");
+    write_legacy_token(&path);
+
+    let loaded = TokenStore::file(path.clone()).load().await.unwrap();
+    assert_eq!(loaded.access_token, "ya29.access");
+    #[cfg(unix)]
+    assert_eq!(mode(&path), 0o600);
+}
+
+#[tokio::test]
+async fn encrypts_with_passphrase() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token.json");
+    let store = TokenStore::file(path.clone()).with_passphrase("correct horse");
+
+    store.save(&token()).await.unwrap();
+    let on_disk = std::fs::read_to_string(&path).unwrap();
+    assert!(!on_disk.contains("ya29.access"));
+    assert!(!on_disk.contains("1//refresh"));
+
+    let loaded = store.load().await.unwrap();
+    assert_eq!(loaded.refresh_token.as_deref(), Some("1//refresh"));
+}
+
+#[tokio::test]
+async fn wrong_or_missing_passphrase_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token.json");
+    TokenStore::file(path.clone())
+        .with_passphrase("correct horse")
+        .save(&token())
+        .await
+        .unwrap();
+
+    let err = TokenStore::file(path.clone())
+        .with_passphrase("battery staple")
+        .load()
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("passphrase"), "{}", err);
+
+    let err = TokenStore::file(path).load().await.unwrap_err();
+    assert!(
+        err.to_string().contains("RUSTYOUTUBE_TOKEN_PASSPHRASE"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn legacy_token_file_is_migrated_to_encrypted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token.json");
+    write_legacy_token(&path);
+    let store = TokenStore::file(path.clone()).with_passphrase("correct horse");
+
+    let loaded = store.load().await.unwrap();
+    assert_eq!(loaded.access_token, "ya29.access");
+    assert!(!std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("ya29.access"));
+    #[cfg(unix)]
+    assert_eq!(mode(&path), 0o600);
+
+    // And it still loads from the new format
+    assert_eq!(store.load().await.unwrap().access_token, "ya29.access");
+}
+
+#[tokio::test]
+async fn delete_removes_token() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token.json");
+    let store = TokenStore::file(path.clone());
+    store.save(&token()).await.unwrap();
+
+    store.delete().await.unwrap();
+    assert!(!store.exists());
+    // Deleting twice is fine
+    store.delete().await.unwrap();
+}
@@ -0,0 +1,349 @@
+mod common;
+
+use common::{FakeServer, Response};
+use rustyoutube::youtube::{Rating, Unsupported, YouTubeClient};
+
+const SUBSCRIPTIONS_PAGE1: &str = include_str!("fixtures/api/subscriptions_page1.json");
+const SUBSCRIPTIONS_PAGE2: &str = include_str!("fixtures/api/subscriptions_page2.json");
+const QUOTA_EXCEEDED: &str = include_str!("fixtures/api/quota_exceeded.json");
+const INVALID_CREDENTIALS: &str = include_str!("fixtures/api/invalid_credentials.json");
+const CHANNEL_UPLOADS: &str = include_str!("fixtures/api/channel_uploads.json");
+const UPLOADS_PAGE1: &str = include_str!("fixtures/api/uploads_page1.json");
+const INSUFFICIENT_PERMISSIONS: &str = include_str!("fixtures/api/insufficient_permissions.json");
+const PLAYLIST_ITEMS_PAGES: [&str; 3] = [
+    include_str!("fixtures/api/playlist_items_page1.json"),
+    include_str!("fixtures/api/playlist_items_page2.json"),
+    include_str!("fixtures/api/playlist_items_page3.json"),
+];
+
+fn client_for(server: &FakeServer) -> YouTubeClient {
+    YouTubeClient::builder()
+        .access_token("test-token")
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .build()
+}
+
+#[tokio::test]
+async fn subscriptions_follow_next_page_token() {
+    let server = FakeServer::start(|request| match request.query("pageToken") {
+        None => Response::ok(SUBSCRIPTIONS_PAGE1),
+        Some("CAIQAA") => Response::ok(SUBSCRIPTIONS_PAGE2),
+        Some(other) => Response::json(400, format!("unexpected page token {}", other)),
+    })
+    .await;
+
+    let subscriptions = client_for(&server).get_subscriptions().await.unwrap();
+
+    let channels: Vec<&str> = subscriptions
+        .iter()
+        .map(|s| s.channel_id.as_str())
+        .collect();
+    assert_eq!(channels, ["UC-rust", "UC-stream", "UC-music"]);
+    assert_eq!(subscriptions[2].channel_title, "Music Channel");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests
+        .iter()
+        .all(|r| r.path == "/youtube/v3/subscriptions" && r.query("mine") == Some("true")));
+    assert_eq!(
+        requests[0].header("authorization"),
+        Some("Bearer test-token")
+    );
+}
+
+#[tokio::test]
+async fn api_error_body_is_reported() {
+    let server = FakeServer::start(|_| Response::json(403, QUOTA_EXCEEDED)).await;
+
+    let err = client_for(&server).get_playlists().await.unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("403"), "{}", message);
+    assert!(message.contains("exceeded your"), "{}", message);
+    assert!(message.contains("quotaExceeded"), "{}", message);
+}
+
+#[tokio::test]
+async fn unauthorized_error_is_reported() {
+    let server = FakeServer::start(|_| Response::json(401, INVALID_CREDENTIALS)).await;
+
+    let err = client_for(&server).get_subscriptions().await.unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("401"), "{}", message);
+    assert!(message.contains("authError"), "{}", message);
+}
+
+#[tokio::test]
+async fn non_json_error_body_is_kept() {
+    let server = FakeServer::start(|_| Response::json(404, "Not Found")).await;
+
+    let err = client_for(&server).get_playlists().await.unwrap_err();
+    assert!(err.to_string().contains("Not Found"));
+}
+
+#[tokio::test]
+async fn channel_videos_come_from_uploads_playlist() {
+    let server = FakeServer::start(|request| match request.path.as_str() {
+        "/youtube/v3/channels" => Response::ok(CHANNEL_UPLOADS),
+        "/youtube/v3/playlistItems" if request.query("playlistId") == Some("UU-rust") => {
+            Response::ok(UPLOADS_PAGE1)
+        }
+        _ => Response::json(404, "{}"),
+    })
+    .await;
+
+    let videos = client_for(&server)
+        .get_channel_videos_by_id("UC-rust")
+        .await
+        .unwrap();
+
+    assert_eq!(videos.len(), 1);
+    assert_eq!(videos[0].id, "vid-1");
+    assert_eq!(videos[0].title, "Ownership || Borrowing");
+    assert_eq!(videos[0].channel_id, "UC-rust");
+    assert_eq!(
+        server.requests_to("/channels")[0].query("id"),
+        Some("UC-rust")
+    );
+}
+
+#[tokio::test]
+async fn channel_videos_stop_after_the_first_page() {
+    let server = FakeServer::start(|request| match request.path.as_str() {
+        "/youtube/v3/channels" => Response::ok(CHANNEL_UPLOADS),
+        "/youtube/v3/playlistItems" => match request.query("pageToken") {
+            None => Response::ok(PLAYLIST_ITEMS_PAGES[0]),
+            Some("CAIQAA") => Response::ok(PLAYLIST_ITEMS_PAGES[1]),
+            _ => Response::ok(PLAYLIST_ITEMS_PAGES[2]),
+        },
+        _ => Response::json(404, "{}"),
+    })
+    .await;
+
+    let videos = client_for(&server)
+        .get_channel_videos_by_id("UC-rust")
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = videos.iter().map(|v| v.id.as_str()).collect();
+    assert_eq!(ids, ["vid-aaa", "vid-bbb"]);
+    // The uploads playlist has more pages, but only the newest uploads are listed
+    let requests = server.requests_to("/playlistItems");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].query("maxResults"), Some("50"));
+}
+
+#[tokio::test]
+async fn playlist_videos_cover_all_three_pages() {
+    let server = FakeServer::start(|request| match request.query("pageToken") {
+        None => Response::ok(PLAYLIST_ITEMS_PAGES[0]),
+        Some("CAIQAA") => Response::ok(PLAYLIST_ITEMS_PAGES[1]),
+        Some("CAQQAA") => Response::ok(PLAYLIST_ITEMS_PAGES[2]),
+        Some(other) => Response::json(400, format!("unexpected page token {}", other)),
+    })
+    .await;
+
+    let videos = client_for(&server)
+        .get_playlist_videos("PL-watch-later")
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = videos.iter().map(|v| v.id.as_str()).collect();
+    assert_eq!(ids, ["vid-aaa", "vid-bbb", "vid-ccc", "vid-ddd", "vid-eee"]);
+    assert_eq!(server.requests_to("/playlistItems").len(), 3);
+
+    // The video's owner, not the playlist's, with the playlist owner as a fallback
+    assert_eq!(videos[0].channel_title, "Rust Channel");
+    assert_eq!(videos[0].channel_id, "UC-rust");
+    assert_eq!(videos[3].channel_title, "My Channel");
+    assert!(videos.iter().all(|v| v.channel_title != "Unknown Channel"));
+
+    // The video's publish date rather than when it was added
+    assert_eq!(videos[0].published_at, "2024-06-14T16:00:00Z");
+    assert_eq!(videos[3].published_at, "2024-07-04T12:00:00Z");
+
+    assert!(videos[2].is_short);
+    // Needed to remove the video from the playlist again
+    assert_eq!(videos[0].playlist_item_id.as_deref(), Some("PLitem-1"));
+    assert_eq!(
+        videos[4].thumbnail_url,
+        "https://i.ytimg.com/vi/vid-eee/default.jpg"
+    );
+}
+
+#[tokio::test]
+async fn playlists_read_item_count() {
+    let server = FakeServer::start(|_| {
+        Response::ok(
+            r#"{
+              "kind": "youtube#playlistListResponse",
+              "items": [
+                {
+                  "id": "PL-1",
+                  "snippet": { "title": "Watch later", "description": "" },
+                  "contentDetails": { "itemCount": 42 }
+                },
+                {
+                  "id": "PL-2",
+                  "snippet": { "title": "No details" }
+                }
+              ]
+            }"#,
+        )
+    })
+    .await;
+
+    let playlists = client_for(&server).get_playlists().await.unwrap();
+    assert_eq!(playlists.len(), 2);
+    assert_eq!(playlists[0].item_count, 42);
+    assert_eq!(playlists[1].item_count, 0);
+    assert_eq!(playlists[1].description, "");
+}
+
+#[tokio::test]
+async fn subscribe_posts_channel_resource() {
+    let server = FakeServer::start(|_| Response::ok(r#"{"id": "sub-1"}"#)).await;
+
+    client_for(&server).subscribe("UC-rust").await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/youtube/v3/subscriptions");
+    assert_eq!(request.query("part"), Some("snippet"));
+    let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(body["snippet"]["resourceId"]["channelId"], "UC-rust");
+    assert_eq!(body["snippet"]["resourceId"]["kind"], "youtube#channel");
+}
+
+#[tokio::test]
+async fn unsubscribe_deletes_subscription_for_channel() {
+    let server = FakeServer::start(|request| match request.method.as_str() {
+        "GET" => Response::ok(r#"{"items": [{"kind": "youtube#subscription", "id": "sub-42"}]}"#),
+        "DELETE" => Response::json(204, ""),
+        _ => Response::json(404, "{}"),
+    })
+    .await;
+
+    client_for(&server).unsubscribe("UC-rust").await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].query("forChannelId"), Some("UC-rust"));
+    assert_eq!(requests[0].query("mine"), Some("true"));
+    assert_eq!(requests[1].method, "DELETE");
+    assert_eq!(requests[1].query("id"), Some("sub-42"));
+}
+
+#[tokio::test]
+async fn unsubscribe_from_unknown_channel_is_an_error() {
+    let server = FakeServer::start(|_| Response::ok(r#"{"items": []}"#)).await;
+
+    let err = client_for(&server)
+        .unsubscribe("UC-other")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not subscribed"), "{}", err);
+    assert!(server.requests().iter().all(|r| r.method == "GET"));
+}
+
+#[tokio::test]
+async fn like_rates_video() {
+    let server = FakeServer::start(|_| Response::json(204, "")).await;
+
+    client_for(&server)
+        .rate_video("vid-aaa", Rating::Like)
+        .await
+        .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/youtube/v3/videos/rate");
+    assert_eq!(request.query("id"), Some("vid-aaa"));
+    assert_eq!(request.query("rating"), Some("like"));
+    assert_eq!(request.header("content-length"), Some("0"));
+}
+
+#[tokio::test]
+async fn playlist_items_are_added_and_removed() {
+    let server = FakeServer::start(|request| match request.method.as_str() {
+        "POST" => Response::ok(r#"{"id": "PLitem-new"}"#),
+        "DELETE" => Response::json(204, ""),
+        _ => Response::json(404, "{}"),
+    })
+    .await;
+    let client = client_for(&server);
+
+    client.add_to_playlist("PL-1", "vid-aaa").await.unwrap();
+    client.remove_from_playlist("PLitem-1").await.unwrap();
+
+    let requests = server.requests_to("/playlistItems");
+    let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(body["snippet"]["playlistId"], "PL-1");
+    assert_eq!(body["snippet"]["resourceId"]["videoId"], "vid-aaa");
+    assert_eq!(requests[1].method, "DELETE");
+    assert_eq!(requests[1].query("id"), Some("PLitem-1"));
+}
+
+#[tokio::test]
+async fn read_only_token_asks_for_write_access() {
+    let server = FakeServer::start(|_| Response::json(403, INSUFFICIENT_PERMISSIONS)).await;
+
+    let err = client_for(&server).subscribe("UC-rust").await.unwrap_err();
+    assert!(err.to_string().contains("--write-access"), "{}", err);
+    // The API's own error is kept as the cause
+    assert!(format!("{:#}", err).contains("insufficientPermissions"));
+}
+
+#[tokio::test]
+async fn actions_need_an_account() {
+    let client = YouTubeClient::builder().build();
+
+    let err = client.subscribe("UC-rust").await.unwrap_err();
+    assert!(err.is::<Unsupported>(), "{}", err);
+}
+
+fn api_key_client_for(server: &FakeServer) -> YouTubeClient {
+    YouTubeClient::builder()
+        .api_key("public-key")
+        .api_base_url(format!("{}/youtube/v3", server.url))
+        .build()
+}
+
+#[tokio::test]
+async fn api_key_reads_public_channel_data() {
+    let server = FakeServer::start(|request| match request.path.as_str() {
+        "/youtube/v3/playlists" => Response::ok(
+            r#"{"items": [{"id": "PL-talks", "snippet": {"title": "Talks"}, "contentDetails": {"itemCount": 3}}]}"#,
+        ),
+        "/youtube/v3/channels" => Response::ok(CHANNEL_UPLOADS),
+        "/youtube/v3/playlistItems" => Response::ok(UPLOADS_PAGE1),
+        _ => Response::json(404, "{}"),
+    })
+    .await;
+    let client = api_key_client_for(&server);
+    assert!(!client.is_authenticated());
+
+    let playlists = client.get_channel_playlists("UC-rust").await.unwrap();
+    assert_eq!(playlists[0].id, "PL-talks");
+    let videos = client.get_channel_videos_by_id("UC-rust").await.unwrap();
+    assert_eq!(videos[0].id, "vid-1");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(requests
+        .iter()
+        .all(|r| r.query("key") == Some("public-key") && r.header("authorization").is_none()));
+    assert_eq!(requests[0].query("channelId"), Some("UC-rust"));
+}
+
+#[tokio::test]
+async fn api_key_cannot_read_account_data() {
+    let server = FakeServer::start(|_| Response::ok("{}")).await;
+    let client = api_key_client_for(&server);
+
+    let err = client.get_playlists().await.unwrap_err();
+    assert!(err.is::<Unsupported>(), "{}", err);
+    let err = client.get_subscriptions().await.unwrap_err();
+    assert!(err.is::<Unsupported>(), "{}", err);
+    assert!(server.requests().is_empty());
+}